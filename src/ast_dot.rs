@@ -0,0 +1,219 @@
+//! Renders the syntax tree as a Graphviz digraph for `jlox ast --format dot`.
+//!
+//! Every statement and expression becomes a box labelled with its kind and
+//! the tokens it holds (operator, name, literal value), and every child an
+//! edge labelled with the field it sits in, such as `left`, `callee` or
+//! `then_branch`. Elements of a list field are labelled `body[0]`,
+//! `body[1]` and so on; absent optional children have no edge.
+
+use crate::{
+    ast_printer::AstPrinter, expr::ExpressionType, stmt::StmtType, token_type::Token,
+};
+
+pub fn to_dot(statements: &[StmtType]) -> String {
+    let mut graph = DotGraph {
+        lines: Vec::new(),
+        next_id: 0,
+    };
+    let root = graph.node("program");
+    graph.stmts(root, "statements", statements);
+
+    let mut builder = String::from("digraph ast {\n");
+    builder.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+    builder.push_str("  edge [fontname=\"monospace\", fontsize=10];\n");
+    for line in graph.lines {
+        builder.push_str("  ");
+        builder.push_str(&line);
+        builder.push('\n');
+    }
+    builder.push_str("}\n");
+    builder
+}
+
+struct DotGraph {
+    lines: Vec<String>,
+    next_id: usize,
+}
+
+impl DotGraph {
+    fn node(&mut self, label: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.lines.push(format!("n{} [label=\"{}\"];", id, escape(label)));
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: &str) {
+        self.lines.push(format!("n{} -> n{} [label=\"{}\"];", from, to, escape(label)));
+    }
+
+    fn stmts(&mut self, parent: usize, field: &str, statements: &[StmtType]) {
+        for (index, statement) in statements.iter().enumerate() {
+            self.stmt(parent, &format!("{}[{}]", field, index), statement);
+        }
+    }
+
+    fn exprs(&mut self, parent: usize, field: &str, expressions: &[ExpressionType]) {
+        for (index, expression) in expressions.iter().enumerate() {
+            self.expr(parent, &format!("{}[{}]", field, index), expression);
+        }
+    }
+
+    fn stmt(&mut self, parent: usize, field: &str, stmt: &StmtType) {
+        let id = self.node(&stmt_label(stmt));
+        self.edge(parent, id, field);
+
+        match stmt {
+            StmtType::Expression(stmt) => self.expr(id, "expression", &stmt.expression),
+            StmtType::Print(stmt) => self.expr(id, "expression", &stmt.expression),
+            StmtType::Var(stmt) => {
+                if let Some(initializer) = &stmt.initializer {
+                    self.expr(id, "initializer", initializer);
+                }
+            }
+            StmtType::Block(stmt) => self.stmts(id, "statements", &stmt.statements),
+            StmtType::If(stmt) => {
+                self.expr(id, "condition", &stmt.condition);
+                self.stmt(id, "then_branch", &stmt.then_branch);
+                if let Some(else_branch) = &stmt.else_branch {
+                    self.stmt(id, "else_branch", else_branch);
+                }
+            }
+            StmtType::While(stmt) => {
+                self.expr(id, "condition", &stmt.condition);
+                self.stmt(id, "body", &stmt.body);
+            }
+            StmtType::For(stmt) => {
+                if let Some(initializer) = &stmt.initializer {
+                    self.stmt(id, "initializer", initializer);
+                }
+                if let Some(condition) = &stmt.condition {
+                    self.expr(id, "condition", condition);
+                }
+                if let Some(increment) = &stmt.increment {
+                    self.expr(id, "increment", increment);
+                }
+                self.stmt(id, "body", &stmt.body);
+            }
+            StmtType::Function(stmt) => self.stmts(id, "body", &stmt.body),
+            StmtType::Return(stmt) => {
+                if let Some(value) = &stmt.value {
+                    self.expr(id, "value", value);
+                }
+            }
+            StmtType::Throw(stmt) => self.expr(id, "value", &stmt.value),
+            StmtType::Try(stmt) => {
+                self.stmts(id, "body", &stmt.body);
+                if let Some(body) = &stmt.catch_body {
+                    self.stmts(id, "catch_body", body);
+                }
+                if let Some(body) = &stmt.finally_body {
+                    self.stmts(id, "finally_body", body);
+                }
+            }
+            StmtType::Import(_) | StmtType::Debugger(_) => {}
+            StmtType::Test(stmt) => self.stmts(id, "body", &stmt.body),
+        }
+    }
+
+    fn expr(&mut self, parent: usize, field: &str, expr: &ExpressionType) {
+        let id = self.node(&expr_label(expr));
+        self.edge(parent, id, field);
+
+        match expr {
+            ExpressionType::Assign(expr) => self.expr(id, "value", &expr.value),
+            ExpressionType::Binary(expr) => {
+                self.expr(id, "left", &expr.left);
+                self.expr(id, "right", &expr.right);
+            }
+            ExpressionType::Call(expr) => {
+                self.expr(id, "callee", &expr.callee);
+                self.exprs(id, "arguments", &expr.arguments);
+            }
+            ExpressionType::Grouping(expr) => self.expr(id, "expression", &expr.expression),
+            ExpressionType::Literal(_) | ExpressionType::Variable(_) | ExpressionType::Update(_) => {}
+            ExpressionType::Logical(expr) => {
+                self.expr(id, "left", &expr.left);
+                self.expr(id, "right", &expr.right);
+            }
+            ExpressionType::Unary(expr) => self.expr(id, "right", &expr.right),
+            ExpressionType::CompoundAssign(expr) => self.expr(id, "value", &expr.value),
+            ExpressionType::Conditional(expr) => {
+                self.expr(id, "condition", &expr.condition);
+                self.expr(id, "then_branch", &expr.then_branch);
+                self.expr(id, "else_branch", &expr.else_branch);
+            }
+            ExpressionType::Get(expr) => self.expr(id, "object", &expr.object),
+        }
+    }
+}
+
+fn stmt_label(stmt: &StmtType) -> String {
+    match stmt {
+        StmtType::Expression(_) => "Expression".to_string(),
+        StmtType::Print(_) => "Print".to_string(),
+        StmtType::Var(stmt) => format!("Var {}", stmt.name.lexeme),
+        StmtType::Block(_) => "Block".to_string(),
+        StmtType::If(_) => "If".to_string(),
+        StmtType::While(_) => "While".to_string(),
+        StmtType::For(_) => "For".to_string(),
+        StmtType::Function(stmt) => format!("Function {}({})", stmt.name.lexeme, names(&stmt.params, ", ")),
+        StmtType::Return(_) => "Return".to_string(),
+        StmtType::Throw(_) => "Throw".to_string(),
+        StmtType::Try(stmt) => match &stmt.catch_name {
+            Some(name) => format!("Try catch {}", name.lexeme),
+            None => "Try".to_string(),
+        },
+        StmtType::Import(stmt) => match &stmt.alias {
+            Some(alias) => format!("Import {} as {}", stmt.path.lexeme, alias.lexeme),
+            None => format!("Import {} ({})", stmt.path.lexeme, names(&stmt.names, " ")),
+        },
+        StmtType::Test(stmt) => format!("Test {}", stmt.description.lexeme),
+        StmtType::Debugger(_) => "Debugger".to_string(),
+    }
+}
+
+fn expr_label(expr: &ExpressionType) -> String {
+    match expr {
+        ExpressionType::Assign(expr) => format!("Assign {} =", expr.name.lexeme),
+        ExpressionType::Binary(expr) => format!("Binary {}", expr.operator.lexeme),
+        ExpressionType::Call(expr) if expr.optional => "Call ?.()".to_string(),
+        ExpressionType::Call(_) => "Call".to_string(),
+        ExpressionType::Grouping(_) => "Grouping".to_string(),
+        ExpressionType::Literal(_) => format!("Literal {}", AstPrinter::print(expr)),
+        ExpressionType::Variable(expr) => format!("Variable {}", expr.name.lexeme),
+        ExpressionType::Logical(expr) => format!("Logical {}", expr.operator.lexeme),
+        ExpressionType::Unary(expr) => format!("Unary {}", expr.operator.lexeme),
+        ExpressionType::CompoundAssign(expr) => {
+            format!("CompoundAssign {} {}", expr.name.lexeme, expr.operator.lexeme)
+        }
+        ExpressionType::Update(expr) if expr.prefix => {
+            format!("Update {}{}", expr.operator.lexeme, expr.name.lexeme)
+        }
+        ExpressionType::Update(expr) => format!("Update {}{}", expr.name.lexeme, expr.operator.lexeme),
+        ExpressionType::Conditional(_) => "Conditional ?:".to_string(),
+        ExpressionType::Get(expr) if expr.optional => format!("Get ?.{}", expr.name.lexeme),
+        ExpressionType::Get(expr) => format!("Get .{}", expr.name.lexeme),
+    }
+}
+
+fn names(tokens: &[Token], separator: &str) -> String {
+    let names: Vec<&str> = tokens.iter().map(|token| token.lexeme.as_str()).collect();
+    names.join(separator)
+}
+
+// Escapes text for a double-quoted DOT string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,649 @@
+//! The JSON form of the syntax tree, written by `jlox ast --format json`
+//! and read back by `jlox ast --run`.
+//!
+//! Schema, version 2:
+//!
+//! ```text
+//! Program    {"version": 2, "statements": [Stmt]}
+//! Token      {"lexeme": string, "line": number}
+//! Node       {"kind": <variant>, <fields>}
+//! ```
+//!
+//! Every node has a `"kind"` naming its `ExpressionType` or `StmtType`
+//! variant and one member per field of that variant, in declaration order
+//! (see `src/bin/generate_ast.rs`). Fields map as follows: a token to a
+//! Token object, a node to a Node object, `Vec` to an array, `Option` to
+//! `null` when absent and `bool` to a boolean. `Literal`'s `value` is a JSON
+//! null, boolean, number or string.
+//!
+//! Expressions:
+//!
+//! ```text
+//! Assign          name: Token, value: Expr
+//! Binary          left: Expr, operator: Token, right: Expr
+//! Call            callee: Expr, paren: Token, arguments: [Expr], optional: bool
+//! Grouping        expression: Expr
+//! Literal         value: null | bool | number | string
+//! Variable        name: Token
+//! Logical         left: Expr, operator: Token, right: Expr
+//! Unary           operator: Token, right: Expr
+//! CompoundAssign  name: Token, operator: Token, value: Expr
+//! Update          name: Token, operator: Token, prefix: bool
+//! Conditional     condition: Expr, then_branch: Expr, else_branch: Expr
+//! Get             object: Expr, name: Token, optional: bool
+//! ```
+//!
+//! Statements:
+//!
+//! ```text
+//! Expression  expression: Expr
+//! Function    name: Token, params: [Token], body: [Stmt]
+//! If          keyword: Token, condition: Expr, then_branch: Stmt, else_branch: Stmt?
+//! Block       statements: [Stmt]
+//! Var         name: Token, initializer: Expr?
+//! Print       keyword: Token, expression: Expr
+//! Return      keyword: Token, value: Expr?
+//! While       keyword: Token, condition: Expr, body: Stmt
+//! For         keyword: Token, initializer: Stmt?, condition: Expr?, increment: Expr?,
+//!             body: Stmt
+//! Throw       keyword: Token, value: Expr
+//! Try         body: [Stmt], catch_name: Token?, catch_body: [Stmt]?, finally_body: [Stmt]?
+//! Import      keyword: Token, path: Token, alias: Token?, names: [Token]
+//! Test        keyword: Token, description: Token, body: [Stmt]
+//! Debugger    keyword: Token
+//! ```
+//!
+//! The loader rescans each lexeme to recover the token's type and literal,
+//! so a lexeme must be exactly one Lox token, spelled as in source.
+//! Columns are not part of the schema; loaded tokens have column 0.
+//!
+//! Version 2 added `keyword` to `If`, `Print`, `While` and `For`, and the
+//! `Debugger` statement. Version 1 programs still load: each of those four
+//! statements gets a keyword token on the first line found inside it, which
+//! is the statement's own line unless its first token was on a later one.
+
+use crate::{
+    expr::{
+        Assign, Binary, Call, CompoundAssign, Conditional, ExpressionType, Get, Grouping, Literal,
+        Logical, Unary, Update, Variable,
+    },
+    json::JsonValue,
+    scanner::Scanner,
+    stmt::{
+        Block, Debugger, Expression, For, Function, If, Import, Print, Return, StmtType, Test,
+        Throw, Try, Var, While,
+    },
+    token_type::{LiteralType, Token, TokenType},
+};
+
+pub const VERSION: f64 = 2.0;
+const VERSION_1: f64 = 1.0;
+
+pub fn to_json(statements: &[StmtType]) -> JsonValue {
+    JsonValue::Object(Vec::from([
+        ("version".to_string(), JsonValue::Number(VERSION)),
+        ("statements".to_string(), stmts_to_json(statements)),
+    ]))
+}
+
+pub fn from_json(json: &JsonValue) -> Result<Vec<StmtType>, String> {
+    let statements = match json.get("statements") {
+        Some(statements) => statements,
+        None => return Err("Program is missing 'statements'.".to_string()),
+    };
+    match json.get("version") {
+        Some(JsonValue::Number(version)) if *version == VERSION => stmts_from_json(statements),
+        Some(JsonValue::Number(version)) if *version == VERSION_1 => {
+            stmts_from_json(&upgrade_version_1(statements))
+        }
+        Some(_) => Err(format!(
+            "Unsupported AST version; expected {} or {}.",
+            VERSION_1, VERSION
+        )),
+        None => Err("Program is missing 'version'.".to_string()),
+    }
+}
+
+// Adds the `keyword` that version 1 left out of `If`, `Print`, `While` and
+// `For` nodes.
+fn upgrade_version_1(json: &JsonValue) -> JsonValue {
+    match json {
+        JsonValue::Array(items) => JsonValue::Array(items.iter().map(upgrade_version_1).collect()),
+        JsonValue::Object(members) => {
+            let mut members: Vec<(String, JsonValue)> = members
+                .iter()
+                .map(|(name, value)| (name.clone(), upgrade_version_1(value)))
+                .collect();
+            let lexeme = match json.get("kind") {
+                Some(JsonValue::String(kind)) => match kind.as_str() {
+                    "If" => Some("if"),
+                    "Print" => Some("print"),
+                    "While" => Some("while"),
+                    "For" => Some("for"),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let (Some(lexeme), None) = (lexeme, json.get("keyword")) {
+                let line = first_line(json).unwrap_or(1.0);
+                let keyword = JsonValue::Object(Vec::from([
+                    ("lexeme".to_string(), JsonValue::String(lexeme.to_string())),
+                    ("line".to_string(), JsonValue::Number(line)),
+                ]));
+                members.push(("keyword".to_string(), keyword));
+            }
+            JsonValue::Object(members)
+        }
+        other => other.clone(),
+    }
+}
+
+// The line of the first token in `json`, in field order.
+fn first_line(json: &JsonValue) -> Option<f64> {
+    match json {
+        JsonValue::Object(members) => members.iter().find_map(|(name, value)| match value {
+            JsonValue::Number(line) if name == "line" => Some(*line),
+            _ => first_line(value),
+        }),
+        JsonValue::Array(items) => items.iter().find_map(first_line),
+        _ => None,
+    }
+}
+
+fn node(kind: &str, fields: Vec<(&str, JsonValue)>) -> JsonValue {
+    let mut members = Vec::from([("kind".to_string(), JsonValue::String(kind.to_string()))]);
+    members.extend(fields.into_iter().map(|(name, value)| (name.to_string(), value)));
+    JsonValue::Object(members)
+}
+
+fn token_to_json(token: &Token) -> JsonValue {
+    JsonValue::Object(Vec::from([
+        ("lexeme".to_string(), JsonValue::String(token.lexeme.clone())),
+        ("line".to_string(), JsonValue::Number(token.line as f64)),
+    ]))
+}
+
+fn tokens_to_json(tokens: &[Token]) -> JsonValue {
+    JsonValue::Array(tokens.iter().map(token_to_json).collect())
+}
+
+fn stmts_to_json(statements: &[StmtType]) -> JsonValue {
+    JsonValue::Array(statements.iter().map(stmt_to_json).collect())
+}
+
+fn optional<T>(value: Option<T>, to_json: impl Fn(T) -> JsonValue) -> JsonValue {
+    value.map_or(JsonValue::Null, to_json)
+}
+
+fn stmt_to_json(stmt: &StmtType) -> JsonValue {
+    match stmt {
+        StmtType::Expression(stmt) => {
+            node("Expression", Vec::from([("expression", expr_to_json(&stmt.expression))]))
+        }
+        StmtType::Function(stmt) => node(
+            "Function",
+            Vec::from([
+                ("name", token_to_json(&stmt.name)),
+                ("params", tokens_to_json(&stmt.params)),
+                ("body", stmts_to_json(&stmt.body)),
+            ]),
+        ),
+        StmtType::If(stmt) => node(
+            "If",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("condition", expr_to_json(&stmt.condition)),
+                ("then_branch", stmt_to_json(&stmt.then_branch)),
+                ("else_branch", optional(stmt.else_branch.as_deref(), stmt_to_json)),
+            ]),
+        ),
+        StmtType::Block(stmt) => {
+            node("Block", Vec::from([("statements", stmts_to_json(&stmt.statements))]))
+        }
+        StmtType::Var(stmt) => node(
+            "Var",
+            Vec::from([
+                ("name", token_to_json(&stmt.name)),
+                ("initializer", optional(stmt.initializer.as_ref(), expr_to_json)),
+            ]),
+        ),
+        StmtType::Print(stmt) => node(
+            "Print",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("expression", expr_to_json(&stmt.expression)),
+            ]),
+        ),
+        StmtType::Return(stmt) => node(
+            "Return",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("value", optional(stmt.value.as_ref(), expr_to_json)),
+            ]),
+        ),
+        StmtType::While(stmt) => node(
+            "While",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("condition", expr_to_json(&stmt.condition)),
+                ("body", stmt_to_json(&stmt.body)),
+            ]),
+        ),
+        StmtType::For(stmt) => node(
+            "For",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("initializer", optional(stmt.initializer.as_deref(), stmt_to_json)),
+                ("condition", optional(stmt.condition.as_ref(), expr_to_json)),
+                ("increment", optional(stmt.increment.as_ref(), expr_to_json)),
+                ("body", stmt_to_json(&stmt.body)),
+            ]),
+        ),
+        StmtType::Throw(stmt) => node(
+            "Throw",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("value", expr_to_json(&stmt.value)),
+            ]),
+        ),
+        StmtType::Try(stmt) => node(
+            "Try",
+            Vec::from([
+                ("body", stmts_to_json(&stmt.body)),
+                ("catch_name", optional(stmt.catch_name.as_ref(), token_to_json)),
+                ("catch_body", optional(stmt.catch_body.as_deref(), stmts_to_json)),
+                ("finally_body", optional(stmt.finally_body.as_deref(), stmts_to_json)),
+            ]),
+        ),
+        StmtType::Import(stmt) => node(
+            "Import",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("path", token_to_json(&stmt.path)),
+                ("alias", optional(stmt.alias.as_ref(), token_to_json)),
+                ("names", tokens_to_json(&stmt.names)),
+            ]),
+        ),
+        StmtType::Test(stmt) => node(
+            "Test",
+            Vec::from([
+                ("keyword", token_to_json(&stmt.keyword)),
+                ("description", token_to_json(&stmt.description)),
+                ("body", stmts_to_json(&stmt.body)),
+            ]),
+        ),
+        StmtType::Debugger(stmt) => {
+            node("Debugger", Vec::from([("keyword", token_to_json(&stmt.keyword))]))
+        }
+    }
+}
+
+fn expr_to_json(expr: &ExpressionType) -> JsonValue {
+    match expr {
+        ExpressionType::Assign(expr) => node(
+            "Assign",
+            Vec::from([
+                ("name", token_to_json(&expr.name)),
+                ("value", expr_to_json(&expr.value)),
+            ]),
+        ),
+        ExpressionType::Binary(expr) => node(
+            "Binary",
+            Vec::from([
+                ("left", expr_to_json(&expr.left)),
+                ("operator", token_to_json(&expr.operator)),
+                ("right", expr_to_json(&expr.right)),
+            ]),
+        ),
+        ExpressionType::Call(expr) => node(
+            "Call",
+            Vec::from([
+                ("callee", expr_to_json(&expr.callee)),
+                ("paren", token_to_json(&expr.paren)),
+                (
+                    "arguments",
+                    JsonValue::Array(expr.arguments.iter().map(expr_to_json).collect()),
+                ),
+                ("optional", JsonValue::Bool(expr.optional)),
+            ]),
+        ),
+        ExpressionType::Grouping(expr) => {
+            node("Grouping", Vec::from([("expression", expr_to_json(&expr.expression))]))
+        }
+        ExpressionType::Literal(expr) => {
+            let value = match &expr.value {
+                LiteralType::Nil => JsonValue::Null,
+                LiteralType::Bool(value) => JsonValue::Bool(*value),
+                LiteralType::F64(value) => JsonValue::Number(*value),
+                LiteralType::String(value) => JsonValue::String(value.clone()),
+            };
+            node("Literal", Vec::from([("value", value)]))
+        }
+        ExpressionType::Variable(expr) => {
+            node("Variable", Vec::from([("name", token_to_json(&expr.name))]))
+        }
+        ExpressionType::Logical(expr) => node(
+            "Logical",
+            Vec::from([
+                ("left", expr_to_json(&expr.left)),
+                ("operator", token_to_json(&expr.operator)),
+                ("right", expr_to_json(&expr.right)),
+            ]),
+        ),
+        ExpressionType::Unary(expr) => node(
+            "Unary",
+            Vec::from([
+                ("operator", token_to_json(&expr.operator)),
+                ("right", expr_to_json(&expr.right)),
+            ]),
+        ),
+        ExpressionType::CompoundAssign(expr) => node(
+            "CompoundAssign",
+            Vec::from([
+                ("name", token_to_json(&expr.name)),
+                ("operator", token_to_json(&expr.operator)),
+                ("value", expr_to_json(&expr.value)),
+            ]),
+        ),
+        ExpressionType::Update(expr) => node(
+            "Update",
+            Vec::from([
+                ("name", token_to_json(&expr.name)),
+                ("operator", token_to_json(&expr.operator)),
+                ("prefix", JsonValue::Bool(expr.prefix)),
+            ]),
+        ),
+        ExpressionType::Conditional(expr) => node(
+            "Conditional",
+            Vec::from([
+                ("condition", expr_to_json(&expr.condition)),
+                ("then_branch", expr_to_json(&expr.then_branch)),
+                ("else_branch", expr_to_json(&expr.else_branch)),
+            ]),
+        ),
+        ExpressionType::Get(expr) => node(
+            "Get",
+            Vec::from([
+                ("object", expr_to_json(&expr.object)),
+                ("name", token_to_json(&expr.name)),
+                ("optional", JsonValue::Bool(expr.optional)),
+            ]),
+        ),
+    }
+}
+
+fn stmts_from_json(json: &JsonValue) -> Result<Vec<StmtType>, String> {
+    match json {
+        JsonValue::Array(items) => items.iter().map(stmt_from_json).collect(),
+        _ => Err("Expected an array of statements.".to_string()),
+    }
+}
+
+fn stmt_from_json(json: &JsonValue) -> Result<StmtType, String> {
+    let node = Node::new(json)?;
+
+    let stmt = match node.kind {
+        "Expression" => StmtType::Expression(Expression {
+            expression: node.expr("expression")?,
+        }),
+        "Function" => StmtType::Function(Function {
+            name: node.identifier("name")?,
+            params: node.identifiers("params")?,
+            body: node.stmts("body")?,
+        }),
+        "If" => StmtType::If(If {
+            keyword: node.token("keyword")?,
+            condition: Box::new(node.expr("condition")?),
+            then_branch: Box::new(node.stmt("then_branch")?),
+            else_branch: node.optional("else_branch", stmt_from_json)?.map(Box::new),
+        }),
+        "Block" => StmtType::Block(Block {
+            statements: node.stmts("statements")?,
+        }),
+        "Var" => StmtType::Var(Var {
+            name: node.identifier("name")?,
+            initializer: node.optional("initializer", expr_from_json)?,
+        }),
+        "Print" => StmtType::Print(Print {
+            keyword: node.token("keyword")?,
+            expression: node.expr("expression")?,
+        }),
+        "Return" => StmtType::Return(Return {
+            keyword: node.token("keyword")?,
+            value: node.optional("value", expr_from_json)?,
+        }),
+        "While" => StmtType::While(While {
+            keyword: node.token("keyword")?,
+            condition: node.expr("condition")?,
+            body: Box::new(node.stmt("body")?),
+        }),
+        "For" => StmtType::For(For {
+            keyword: node.token("keyword")?,
+            initializer: node.optional("initializer", stmt_from_json)?.map(Box::new),
+            condition: node.optional("condition", expr_from_json)?,
+            increment: node.optional("increment", expr_from_json)?,
+            body: Box::new(node.stmt("body")?),
+        }),
+        "Throw" => StmtType::Throw(Throw {
+            keyword: node.token("keyword")?,
+            value: node.expr("value")?,
+        }),
+        "Try" => StmtType::Try(Try {
+            body: node.stmts("body")?,
+            catch_name: node.optional("catch_name", |json| identifier_from_json(json, "catch_name"))?,
+            catch_body: node.optional("catch_body", stmts_from_json)?,
+            finally_body: node.optional("finally_body", stmts_from_json)?,
+        }),
+        "Import" => StmtType::Import(Import {
+            keyword: node.token("keyword")?,
+            path: node.string_token("path")?,
+            alias: node.optional("alias", |json| identifier_from_json(json, "alias"))?,
+            names: node.identifiers("names")?,
+        }),
+        "Test" => StmtType::Test(Test {
+            keyword: node.token("keyword")?,
+            description: node.string_token("description")?,
+            body: node.stmts("body")?,
+        }),
+        "Debugger" => StmtType::Debugger(Debugger {
+            keyword: node.token("keyword")?,
+        }),
+        kind => return Err(format!("Unknown statement kind '{}'.", kind)),
+    };
+    Ok(stmt)
+}
+
+fn expr_from_json(json: &JsonValue) -> Result<ExpressionType, String> {
+    let node = Node::new(json)?;
+
+    let expr = match node.kind {
+        "Assign" => ExpressionType::Assign(Assign {
+            name: node.identifier("name")?,
+            value: Box::new(node.expr("value")?),
+        }),
+        "Binary" => ExpressionType::Binary(Binary {
+            left: Box::new(node.expr("left")?),
+            operator: node.token("operator")?,
+            right: Box::new(node.expr("right")?),
+        }),
+        "Call" => ExpressionType::Call(Call {
+            callee: Box::new(node.expr("callee")?),
+            paren: node.token("paren")?,
+            arguments: match node.field("arguments")? {
+                JsonValue::Array(items) => items.iter().map(expr_from_json).collect::<Result<_, _>>()?,
+                _ => return Err(node.error("arguments", "an array of expressions")),
+            },
+            optional: node.bool("optional")?,
+        }),
+        "Grouping" => ExpressionType::Grouping(Grouping {
+            expression: Box::new(node.expr("expression")?),
+        }),
+        "Literal" => ExpressionType::Literal(Literal {
+            value: match node.field("value")? {
+                JsonValue::Null => LiteralType::Nil,
+                JsonValue::Bool(value) => LiteralType::Bool(*value),
+                JsonValue::Number(value) => LiteralType::F64(*value),
+                JsonValue::String(value) => LiteralType::String(value.clone()),
+                _ => return Err(node.error("value", "null, a boolean, a number or a string")),
+            },
+        }),
+        "Variable" => ExpressionType::Variable(Variable {
+            name: node.identifier("name")?,
+        }),
+        "Logical" => ExpressionType::Logical(Logical {
+            left: Box::new(node.expr("left")?),
+            operator: node.token("operator")?,
+            right: Box::new(node.expr("right")?),
+        }),
+        "Unary" => ExpressionType::Unary(Unary {
+            operator: node.token("operator")?,
+            right: Box::new(node.expr("right")?),
+        }),
+        "CompoundAssign" => ExpressionType::CompoundAssign(CompoundAssign {
+            name: node.identifier("name")?,
+            operator: node.token("operator")?,
+            value: Box::new(node.expr("value")?),
+        }),
+        "Update" => ExpressionType::Update(Update {
+            name: node.identifier("name")?,
+            operator: node.token("operator")?,
+            prefix: node.bool("prefix")?,
+        }),
+        "Conditional" => ExpressionType::Conditional(Conditional {
+            condition: Box::new(node.expr("condition")?),
+            then_branch: Box::new(node.expr("then_branch")?),
+            else_branch: Box::new(node.expr("else_branch")?),
+        }),
+        "Get" => ExpressionType::Get(Get {
+            object: Box::new(node.expr("object")?),
+            name: node.identifier("name")?,
+            optional: node.bool("optional")?,
+        }),
+        kind => return Err(format!("Unknown expression kind '{}'.", kind)),
+    };
+    Ok(expr)
+}
+
+// Rebuilds a token by scanning its lexeme, which must be a single token.
+fn token_from_json(json: &JsonValue, field: &str) -> Result<Token, String> {
+    let (Some(JsonValue::String(lexeme)), Some(JsonValue::Number(line))) =
+        (json.get("lexeme"), json.get("line"))
+    else {
+        return Err(format!(
+            "Field '{}' must be a token with a string 'lexeme' and a number 'line'.",
+            field
+        ));
+    };
+
+    let mut scanner = Scanner {
+        source: Vec::new(),
+        tokens: Vec::new(),
+        comments: Vec::new(),
+        positions: Vec::new(),
+        errors: Vec::new(),
+        start: 0,
+        current: 0,
+        line: 1,
+    };
+    let mut tokens = scanner.scan_tokens(lexeme.clone());
+    tokens.pop();
+
+    match tokens.pop() {
+        Some(mut token) if tokens.is_empty() && token.lexeme == *lexeme => {
+            token.line = *line as u32;
+            token.column = 0;
+            Ok(token)
+        }
+        _ => Err(format!("Field '{}' has '{}', which is not a single token.", field, lexeme)),
+    }
+}
+
+fn identifier_from_json(json: &JsonValue, field: &str) -> Result<Token, String> {
+    let token = token_from_json(json, field)?;
+    match token.ttype {
+        TokenType::Identifier => Ok(token),
+        _ => Err(format!("Field '{}' must be an identifier, not '{}'.", field, token.lexeme)),
+    }
+}
+
+/// A node object being loaded, with accessors for its typed fields.
+struct Node<'a> {
+    kind: &'a str,
+    json: &'a JsonValue,
+}
+
+impl<'a> Node<'a> {
+    fn new(json: &'a JsonValue) -> Result<Node<'a>, String> {
+        match json.get("kind") {
+            Some(JsonValue::String(kind)) => Ok(Node { kind, json }),
+            _ => Err("Expected a node object with a string 'kind'.".to_string()),
+        }
+    }
+
+    fn field(&self, name: &str) -> Result<&'a JsonValue, String> {
+        self.json
+            .get(name)
+            .ok_or_else(|| format!("{} node is missing '{}'.", self.kind, name))
+    }
+
+    fn error(&self, name: &str, expected: &str) -> String {
+        format!("{} node's '{}' must be {}.", self.kind, name, expected)
+    }
+
+    fn expr(&self, name: &str) -> Result<ExpressionType, String> {
+        expr_from_json(self.field(name)?)
+    }
+
+    fn stmt(&self, name: &str) -> Result<StmtType, String> {
+        stmt_from_json(self.field(name)?)
+    }
+
+    fn stmts(&self, name: &str) -> Result<Vec<StmtType>, String> {
+        stmts_from_json(self.field(name)?)
+    }
+
+    fn token(&self, name: &str) -> Result<Token, String> {
+        token_from_json(self.field(name)?, name)
+    }
+
+    fn identifier(&self, name: &str) -> Result<Token, String> {
+        identifier_from_json(self.field(name)?, name)
+    }
+
+    fn identifiers(&self, name: &str) -> Result<Vec<Token>, String> {
+        match self.field(name)? {
+            JsonValue::Array(items) => items
+                .iter()
+                .map(|item| identifier_from_json(item, name))
+                .collect(),
+            _ => Err(self.error(name, "an array of tokens")),
+        }
+    }
+
+    fn string_token(&self, name: &str) -> Result<Token, String> {
+        let token = self.token(name)?;
+        match token.ttype {
+            TokenType::String => Ok(token),
+            _ => Err(self.error(name, "a string token")),
+        }
+    }
+
+    fn bool(&self, name: &str) -> Result<bool, String> {
+        match self.field(name)? {
+            JsonValue::Bool(value) => Ok(*value),
+            _ => Err(self.error(name, "a boolean")),
+        }
+    }
+
+    // A field that may be null; a missing field counts as null too.
+    fn optional<T>(
+        &self,
+        name: &str,
+        from_json: impl Fn(&JsonValue) -> Result<T, String>,
+    ) -> Result<Option<T>, String> {
+        match self.json.get(name) {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(json) => from_json(json).map(Some),
+        }
+    }
+}
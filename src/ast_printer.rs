@@ -1,43 +1,194 @@
 use crate::expr::ExpressionType;
-use crate::token_type::LiteralType;
+use crate::stmt::StmtType;
+use crate::token_type::{LiteralType, Token};
 pub struct AstPrinter();
 
+/// Prints the tree as S-expressions, one top-level statement at a time.
+/// Statement lists inside blocks, functions, tries and tests go one per
+/// line, indented two spaces per level; missing parts of a `for` print
+/// as `_`.
 impl AstPrinter {
+    pub fn print_program(statements: &[StmtType]) -> String {
+        let mut builder = String::new();
+        for statement in statements {
+            builder.push_str(&Self::print_stmt(statement, 0));
+            builder.push('\n');
+        }
+        builder
+    }
+    pub fn print_stmt(stmt: &StmtType, depth: usize) -> String {
+        match stmt {
+            StmtType::Expression(stmt) => Self::parenthesize(";", [Self::print(&stmt.expression)].to_vec()),
+            StmtType::Print(stmt) => Self::parenthesize("print", [Self::print(&stmt.expression)].to_vec()),
+            StmtType::Var(stmt) => match &stmt.initializer {
+                Some(initializer) => Self::parenthesize(
+                    "var",
+                    [stmt.name.lexeme.clone(), "=".to_string(), Self::print(initializer)].to_vec(),
+                ),
+                None => Self::parenthesize("var", [stmt.name.lexeme.clone()].to_vec()),
+            },
+            StmtType::Block(stmt) => Self::parenthesize_body("block", Vec::new(), &stmt.statements, depth),
+            StmtType::If(stmt) => {
+                let mut parts = [
+                    Self::print(&stmt.condition),
+                    Self::print_stmt(&stmt.then_branch, depth),
+                ]
+                .to_vec();
+                if let Some(else_branch) = &stmt.else_branch {
+                    parts.push(Self::print_stmt(else_branch, depth));
+                }
+                Self::parenthesize("if", parts)
+            }
+            StmtType::While(stmt) => Self::parenthesize(
+                "while",
+                [Self::print(&stmt.condition), Self::print_stmt(&stmt.body, depth)].to_vec(),
+            ),
+            StmtType::For(stmt) => Self::parenthesize(
+                "for",
+                [
+                    stmt.initializer
+                        .as_ref()
+                        .map_or("_".to_string(), |initializer| Self::print_stmt(initializer, depth)),
+                    stmt.condition.as_ref().map_or("_".to_string(), Self::print),
+                    stmt.increment.as_ref().map_or("_".to_string(), Self::print),
+                    Self::print_stmt(&stmt.body, depth),
+                ]
+                .to_vec(),
+            ),
+            StmtType::Function(stmt) => Self::parenthesize_body(
+                "fun",
+                [stmt.name.lexeme.clone(), Self::names(&stmt.params)].to_vec(),
+                &stmt.body,
+                depth,
+            ),
+            StmtType::Return(stmt) => match &stmt.value {
+                Some(value) => Self::parenthesize("return", [Self::print(value)].to_vec()),
+                None => "(return)".to_string(),
+            },
+            StmtType::Throw(stmt) => Self::parenthesize("throw", [Self::print(&stmt.value)].to_vec()),
+            StmtType::Debugger(_) => "(debugger)".to_string(),
+            StmtType::Try(stmt) => {
+                let mut parts = [Self::parenthesize_body("block", Vec::new(), &stmt.body, depth + 1)].to_vec();
+                if let (Some(name), Some(body)) = (&stmt.catch_name, &stmt.catch_body) {
+                    parts.push(Self::parenthesize_body(
+                        "catch",
+                        [name.lexeme.clone()].to_vec(),
+                        body,
+                        depth + 1,
+                    ));
+                }
+                if let Some(body) = &stmt.finally_body {
+                    parts.push(Self::parenthesize_body("finally", Vec::new(), body, depth + 1));
+                }
+                Self::parenthesize_lines("try", Vec::new(), parts, depth)
+            }
+            StmtType::Import(stmt) => match &stmt.alias {
+                Some(alias) => Self::parenthesize(
+                    "import",
+                    [stmt.path.lexeme.clone(), "as".to_string(), alias.lexeme.clone()].to_vec(),
+                ),
+                None => Self::parenthesize(
+                    "import",
+                    [stmt.path.lexeme.clone(), Self::names(&stmt.names)].to_vec(),
+                ),
+            },
+            StmtType::Test(stmt) => Self::parenthesize_body(
+                "test",
+                [stmt.description.lexeme.clone()].to_vec(),
+                &stmt.body,
+                depth,
+            ),
+        }
+    }
     pub fn print(expr: &ExpressionType) -> String {
         match expr {
-            ExpressionType::Binary(expr) => {
-                Self::parenthesize(&expr.operator.lexeme, [&expr.left, &expr.right].to_vec())
-            }
+            ExpressionType::Binary(expr) => Self::parenthesize(
+                &expr.operator.lexeme,
+                [Self::print(&expr.left), Self::print(&expr.right)].to_vec(),
+            ),
             ExpressionType::Grouping(expr) => {
-                Self::parenthesize(&"group".to_string(), [&expr.expression].to_vec())
+                Self::parenthesize("group", [Self::print(&expr.expression)].to_vec())
             }
             ExpressionType::Literal(expr) => match &expr.value {
                 LiteralType::Nil => "nil".to_string(),
-                LiteralType::String(value) => value.clone(),
-                LiteralType::Bool(value) => value.to_string().clone(),
-                LiteralType::F32(value) => value.to_string(),
+                LiteralType::String(value) => format!("\"{}\"", value),
+                LiteralType::Bool(value) => value.to_string(),
+                LiteralType::F64(value) => value.to_string(),
             },
             ExpressionType::Unary(expr) => {
-                Self::parenthesize(&expr.operator.lexeme, [&expr.right].to_vec())
+                Self::parenthesize(&expr.operator.lexeme, [Self::print(&expr.right)].to_vec())
+            }
+            ExpressionType::Variable(expr) => expr.name.lexeme.clone(),
+            ExpressionType::Assign(expr) => Self::parenthesize(
+                "=",
+                [expr.name.lexeme.clone(), Self::print(&expr.value)].to_vec(),
+            ),
+            ExpressionType::Logical(expr) => Self::parenthesize(
+                &expr.operator.lexeme,
+                [Self::print(&expr.left), Self::print(&expr.right)].to_vec(),
+            ),
+            ExpressionType::Call(expr) => {
+                let mut parts = [Self::print(&expr.callee)].to_vec();
+                parts.extend(expr.arguments.iter().map(Self::print));
+                Self::parenthesize(if expr.optional { "?.call" } else { "call" }, parts)
             }
-            ExpressionType::Variable(_) => todo!(),
-            ExpressionType::Assign(_) => todo!(),
-            ExpressionType::Logical(_) => todo!(),
-            ExpressionType::Call(_) => todo!(),
+            ExpressionType::CompoundAssign(expr) => Self::parenthesize(
+                &expr.operator.lexeme,
+                [expr.name.lexeme.clone(), Self::print(&expr.value)].to_vec(),
+            ),
+            ExpressionType::Update(expr) => Self::parenthesize(
+                if expr.prefix { "prefix" } else { "postfix" },
+                [expr.operator.lexeme.clone(), expr.name.lexeme.clone()].to_vec(),
+            ),
+            ExpressionType::Conditional(expr) => Self::parenthesize(
+                "?:",
+                [
+                    Self::print(&expr.condition),
+                    Self::print(&expr.then_branch),
+                    Self::print(&expr.else_branch),
+                ]
+                .to_vec(),
+            ),
+            ExpressionType::Get(expr) => Self::parenthesize(
+                if expr.optional { "?." } else { "." },
+                [Self::print(&expr.object), expr.name.lexeme.clone()].to_vec(),
+            ),
         }
     }
-    pub fn parenthesize(name: &String, exprs: Vec<&Box<ExpressionType>>) -> String {
+    pub fn parenthesize(name: &str, parts: Vec<String>) -> String {
         let mut builder = String::new();
 
         builder.push('(');
-        builder.push_str(name.as_str());
+        builder.push_str(name);
 
-        for expr in exprs {
+        for part in parts {
             builder.push(' ');
-            let sub_expr = Self::print(&**expr);
-            builder.push_str(sub_expr.as_str());
+            builder.push_str(part.as_str());
         }
         builder.push(')');
-        return builder;
+        builder
+    }
+    fn parenthesize_body(name: &str, parts: Vec<String>, body: &[StmtType], depth: usize) -> String {
+        let lines = body
+            .iter()
+            .map(|statement| Self::print_stmt(statement, depth + 1))
+            .collect();
+        Self::parenthesize_lines(name, parts, lines, depth)
+    }
+    fn parenthesize_lines(name: &str, parts: Vec<String>, lines: Vec<String>, depth: usize) -> String {
+        let mut builder = Self::parenthesize(name, parts);
+        builder.pop();
+
+        for line in lines {
+            builder.push('\n');
+            builder.push_str(&"  ".repeat(depth + 1));
+            builder.push_str(&line);
+        }
+        builder.push(')');
+        builder
+    }
+    fn names(tokens: &[Token]) -> String {
+        let names: Vec<&str> = tokens.iter().map(|token| token.lexeme.as_str()).collect();
+        format!("({})", names.join(" "))
     }
 }
@@ -10,12 +10,16 @@ impl Main {
             Vec::from([
                 "Assign: Token name, Box<ExpressionType> value",
                 "Binary: Box<ExpressionType> left, Token operator, Box<ExpressionType> right",
-                "Call: Box<ExpressionType> callee, Token paren, Vec<ExpressionType> arguments",
+                "Call: Box<ExpressionType> callee, Token paren, Vec<ExpressionType> arguments, bool optional",
                 "Grouping: Box<ExpressionType> expression",
                 "Literal: LiteralType value",
                 "Variable: Token name",
                 "Logical: Box<ExpressionType> left, Token operator, Box<ExpressionType> right",
                 "Unary: Token operator, Box<ExpressionType> right",
+                "CompoundAssign: Token name, Token operator, Box<ExpressionType> value",
+                "Update: Token name, Token operator, bool prefix",
+                "Conditional: Box<ExpressionType> condition, Box<ExpressionType> then_branch, Box<ExpressionType> else_branch",
+                "Get: Box<ExpressionType> object, Token name, bool optional",
             ]),
         );
         Self::define_ast(
@@ -23,12 +27,18 @@ impl Main {
             Vec::from([
                 "Expression: ExpressionType expression",
                 "Function: Token name, Vec<Token> params, Vec<StmtType> body",
-                "If: Box<ExpressionType> condition, Box<StmtType> then_branch, Option<Box<StmtType>> else_branch",
+                "If: Token keyword, Box<ExpressionType> condition, Box<StmtType> then_branch, Option<Box<StmtType>> else_branch",
                 "Block: Vec<StmtType> statements",
                 "Var: Token name, Option<ExpressionType> initializer",
-                "Print: ExpressionType expression",
-                "Return: Token keyword, ExpressionType value",
-                "While: ExpressionType condition, Box<StmtType> body",
+                "Print: Token keyword, ExpressionType expression",
+                "Return: Token keyword, Option<ExpressionType> value",
+                "While: Token keyword, ExpressionType condition, Box<StmtType> body",
+                "For: Token keyword, Option<Box<StmtType>> initializer, Option<ExpressionType> condition, Option<ExpressionType> increment, Box<StmtType> body",
+                "Throw: Token keyword, ExpressionType value",
+                "Try: Vec<StmtType> body, Option<Token> catch_name, Option<Vec<StmtType>> catch_body, Option<Vec<StmtType>> finally_body",
+                "Import: Token keyword, Token path, Option<Token> alias, Vec<Token> names",
+                "Test: Token keyword, Token description, Vec<StmtType> body",
+                "Debugger: Token keyword",
             ]),
         );
     }
@@ -39,7 +49,7 @@ impl Main {
 
         if basename == "expr" {
             let _ = file_handler
-                .write(format!("use crate::token_type::{{LiteralType, Token}};\n\n").as_bytes());
+                .write("use crate::token_type::{LiteralType, Token};\n\n".as_bytes());
             let _ = file_handler.write(
                 ["#[derive(Debug, Clone)]\n", "pub enum ExpressionType {\n"]
                     .concat()
@@ -47,11 +57,11 @@ impl Main {
             );
         } else {
             let _ = file_handler.write(
-                format!("use crate::{{expr::ExpressionType, token_type::Token}};\n\n").as_bytes(),
+                "use crate::{expr::ExpressionType, token_type::Token};\n\n".as_bytes(),
             );
 
             let _ = file_handler
-                .write(format!("#[derive(Debug, Clone)]\npub enum StmtType {{\n").as_bytes());
+                .write("#[derive(Debug, Clone)]\npub enum StmtType {\n".as_bytes());
         }
         Self::define_expr_stmt_type(&mut file_handler, &types);
 
@@ -0,0 +1,8 @@
+use std::{io, process};
+
+use jlox_rustport::lsp;
+
+pub fn main() {
+    let stdin = io::stdin();
+    process::exit(lsp::serve(stdin.lock(), io::stdout()));
+}
@@ -0,0 +1,272 @@
+//! Line and branch coverage behind `jlox --coverage`.
+//!
+//! `Coverage` starts from the script's syntax tree, which gives every line
+//! with a statement on it and every branch point: each `if`, `while`,
+//! `for`, `and`, `or` and `??`, with two branches as numbered by
+//! `Hook::branch`. `CoverageRecorder` is the hook that counts them as the
+//! script runs. A line's count is the highest count of the statements on
+//! it, so a `for` line counts its increments.
+//!
+//! Statements and branch points are told apart by line and column, and
+//! only those found in the script's tree are counted; imported modules are
+//! not covered.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{
+    expr::ExpressionType,
+    hook::{self, Hook},
+    interpreter::{Interpreter, RuntimeError},
+    stmt::StmtType,
+    token_type::Token,
+};
+
+#[derive(Debug, Default)]
+pub struct Coverage {
+    /// Runs per statement, by line and column.
+    pub statements: BTreeMap<(u32, u32), u64>,
+    /// Times each branch was taken per branch point, by line and column.
+    pub branches: BTreeMap<(u32, u32), [u64; 2]>,
+}
+
+impl Coverage {
+    /// Coverage of `statements` with nothing run yet.
+    pub fn new(statements: &[StmtType]) -> Coverage {
+        let mut coverage = Coverage::default();
+        coverage.stmts(statements);
+        coverage
+    }
+
+    fn stmts(&mut self, statements: &[StmtType]) {
+        for statement in statements {
+            self.stmt(statement);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &StmtType) {
+        if let Some(position) = hook::position(stmt) {
+            self.statements.insert(position, 0);
+        }
+
+        match stmt {
+            StmtType::Expression(stmt) => self.expr(&stmt.expression),
+            StmtType::Print(stmt) => self.expr(&stmt.expression),
+            StmtType::Var(stmt) => {
+                if let Some(initializer) = &stmt.initializer {
+                    self.expr(initializer);
+                }
+            }
+            StmtType::Block(stmt) => self.stmts(&stmt.statements),
+            StmtType::If(stmt) => {
+                self.branch(&stmt.keyword);
+                self.expr(&stmt.condition);
+                self.stmt(&stmt.then_branch);
+                if let Some(else_branch) = &stmt.else_branch {
+                    self.stmt(else_branch);
+                }
+            }
+            StmtType::While(stmt) => {
+                self.branch(&stmt.keyword);
+                self.expr(&stmt.condition);
+                self.stmt(&stmt.body);
+            }
+            StmtType::For(stmt) => {
+                self.branch(&stmt.keyword);
+                if let Some(initializer) = &stmt.initializer {
+                    self.stmt(initializer);
+                }
+                if let Some(condition) = &stmt.condition {
+                    self.expr(condition);
+                }
+                // The increment runs as a statement of its own.
+                if let Some(increment) = &stmt.increment {
+                    if let Some(position) = hook::expr_position(increment) {
+                        self.statements.insert(position, 0);
+                    }
+                    self.expr(increment);
+                }
+                self.stmt(&stmt.body);
+            }
+            StmtType::Function(stmt) => self.stmts(&stmt.body),
+            StmtType::Return(stmt) => {
+                if let Some(value) = &stmt.value {
+                    self.expr(value);
+                }
+            }
+            StmtType::Throw(stmt) => self.expr(&stmt.value),
+            StmtType::Try(stmt) => {
+                self.stmts(&stmt.body);
+                if let Some(body) = &stmt.catch_body {
+                    self.stmts(body);
+                }
+                if let Some(body) = &stmt.finally_body {
+                    self.stmts(body);
+                }
+            }
+            StmtType::Import(_) | StmtType::Debugger(_) => {}
+            StmtType::Test(stmt) => self.stmts(&stmt.body),
+        }
+    }
+
+    fn expr(&mut self, expr: &ExpressionType) {
+        match expr {
+            ExpressionType::Assign(expr) => self.expr(&expr.value),
+            ExpressionType::Binary(expr) => {
+                self.expr(&expr.left);
+                self.expr(&expr.right);
+            }
+            ExpressionType::Call(expr) => {
+                self.expr(&expr.callee);
+                for argument in &expr.arguments {
+                    self.expr(argument);
+                }
+            }
+            ExpressionType::Grouping(expr) => self.expr(&expr.expression),
+            ExpressionType::Literal(_)
+            | ExpressionType::Variable(_)
+            | ExpressionType::Update(_) => {}
+            ExpressionType::Logical(expr) => {
+                self.branch(&expr.operator);
+                self.expr(&expr.left);
+                self.expr(&expr.right);
+            }
+            ExpressionType::Unary(expr) => self.expr(&expr.right),
+            ExpressionType::CompoundAssign(expr) => self.expr(&expr.value),
+            ExpressionType::Conditional(expr) => {
+                self.expr(&expr.condition);
+                self.expr(&expr.then_branch);
+                self.expr(&expr.else_branch);
+            }
+            ExpressionType::Get(expr) => self.expr(&expr.object),
+        }
+    }
+
+    fn branch(&mut self, token: &Token) {
+        self.branches.insert((token.line, token.column), [0, 0]);
+    }
+
+    /// The count of every line with a statement on it.
+    pub fn lines(&self) -> BTreeMap<u32, u64> {
+        let mut lines = BTreeMap::new();
+        for (&(line, _), &hits) in &self.statements {
+            let count = lines.entry(line).or_insert(0);
+            *count = hits.max(*count);
+        }
+        lines
+    }
+
+    /// One lcov record for the script at `path`.
+    pub fn lcov(&self, path: &str) -> String {
+        let mut lcov = format!("TN:\nSF:{}\n", path);
+
+        let mut branches_found = 0;
+        let mut branches_hit = 0;
+        let mut previous_line = 0;
+        let mut block = 0;
+        for (&(line, _), counts) in &self.branches {
+            block = if line == previous_line { block + 1 } else { 0 };
+            previous_line = line;
+            let reached = counts.iter().any(|&count| count > 0);
+            for (branch, count) in counts.iter().enumerate() {
+                let taken = if reached {
+                    count.to_string()
+                } else {
+                    "-".to_string()
+                };
+                lcov.push_str(&format!("BRDA:{},{},{},{}\n", line, block, branch, taken));
+                branches_found += 1;
+                if *count > 0 {
+                    branches_hit += 1;
+                }
+            }
+        }
+        if branches_found > 0 {
+            lcov.push_str(&format!("BRF:{}\nBRH:{}\n", branches_found, branches_hit));
+        }
+
+        let lines = self.lines();
+        for (line, hits) in &lines {
+            lcov.push_str(&format!("DA:{},{}\n", line, hits));
+        }
+        let lines_hit = lines.values().filter(|&&hits| hits > 0).count();
+        lcov.push_str(&format!("LF:{}\nLH:{}\n", lines.len(), lines_hit));
+        lcov.push_str("end_of_record\n");
+        lcov
+    }
+
+    /// `source` with each line's count in the gutter: blank for lines
+    /// without statements and `#####` for lines never run. Lines with a
+    /// branch that was never taken are marked with `!`.
+    pub fn annotate(&self, source: &str) -> String {
+        let lines = self.lines();
+        let mut partial = Vec::new();
+        for (&(line, _), counts) in &self.branches {
+            if counts.contains(&0) {
+                partial.push(line);
+            }
+        }
+
+        let mut annotated = String::new();
+        for (index, text) in source.lines().enumerate() {
+            let line = index as u32 + 1;
+            let hits = match lines.get(&line) {
+                Some(0) => "#####".to_string(),
+                Some(hits) => hits.to_string(),
+                None => String::new(),
+            };
+            let marker = if partial.contains(&line) { "!" } else { " " };
+            annotated.push_str(&format!("{:>9}{} {:>5} | {}\n", hits, marker, line, text));
+        }
+        annotated
+    }
+}
+
+pub struct CoverageRecorder {
+    coverage: Rc<RefCell<Coverage>>,
+}
+
+impl CoverageRecorder {
+    pub fn new(coverage: Rc<RefCell<Coverage>>) -> CoverageRecorder {
+        CoverageRecorder { coverage }
+    }
+
+    // Whether the script's own code is running, rather than a module's.
+    // Positions alone cannot tell: a module's statement may sit at the
+    // same line and column as one of the script's.
+    fn in_script(interpreter: &Interpreter) -> bool {
+        Rc::ptr_eq(&interpreter.root_environment(), &interpreter.globals)
+    }
+}
+
+impl Hook for CoverageRecorder {
+    fn statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        stmt: &StmtType,
+        _line: u32,
+    ) -> Result<(), RuntimeError> {
+        if !Self::in_script(interpreter) {
+            return Ok(());
+        }
+        if let Some(position) = hook::position(stmt) {
+            if let Some(hits) = self.coverage.borrow_mut().statements.get_mut(&position) {
+                *hits += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn branch(&mut self, interpreter: &mut Interpreter, token: &Token, branch: usize) {
+        if !Self::in_script(interpreter) {
+            return;
+        }
+        if let Some(counts) = self
+            .coverage
+            .borrow_mut()
+            .branches
+            .get_mut(&(token.line, token.column))
+        {
+            counts[branch] += 1;
+        }
+    }
+}
@@ -0,0 +1,618 @@
+//! The `jlox dap` debug adapter: the Debug Adapter Protocol over stdio,
+//! framed like the language server's messages.
+//!
+//! A session is `initialize`, `launch` with the script's `program` path
+//! (and optionally `args` and `stopOnEntry`), any `setBreakpoints`, then
+//! `configurationDone`, which runs the script under `debugger::Debugger`.
+//! Requests are only read while the script is stopped, so there is a
+//! single thread and no `pause`.
+//!
+//! Each frame has a "Locals" scope with every scope of its environment
+//! chain short of the globals, the innermost binding of a name winning,
+//! and a "Globals" scope. Lists and maps expand into their elements.
+//! `evaluate` looks up variable names only. Since stdout carries the
+//! protocol, the script's `print` output is sent as `output` events.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    debugger::{self, Debugger, Frontend, Resume, StackFrame, StopReason},
+    environment::BindableValue,
+    interpreter::{ErrorKind, Interpreter, Output, RuntimeError},
+    json::JsonValue,
+    lox::Lox,
+    lsp,
+    token_type::{Token, TokenType},
+};
+
+const THREAD_ID: f64 = 1.0;
+
+/// Serves a debug session from `input` and returns the process exit code:
+/// 0 once the client disconnects, 1 if it closes the stream first.
+pub fn serve<R: BufRead + 'static, W: Write + 'static>(input: R, output: W) -> i32 {
+    let connection = Rc::new(RefCell::new(Connection {
+        input,
+        output,
+        seq: 0,
+        disconnected: false,
+    }));
+    let mut launch: Option<Launch> = None;
+    let mut breakpoints = HashMap::new();
+
+    loop {
+        let Some(request) = connection.borrow_mut().read() else {
+            return 1;
+        };
+        let command = command(&request);
+        let result = match command.as_str() {
+            "initialize" => Ok(capabilities()),
+            "launch" => Launch::from_request(&request).map(|arguments| {
+                launch = Some(arguments);
+                JsonValue::Null
+            }),
+            "setBreakpoints" => breakpoint_lines(&request).map(|(path, lines)| {
+                let verified = launch.as_ref().is_none_or(|launch| launch.program == path);
+                let body = breakpoints_body(&lines, verified);
+                breakpoints.insert(path, lines.into_iter().collect());
+                body
+            }),
+            "setExceptionBreakpoints" => Ok(JsonValue::Null),
+            "threads" => Ok(threads()),
+            "configurationDone" if launch.is_none() => {
+                Err("Send 'launch' before 'configurationDone'.".to_string())
+            }
+            "configurationDone" => Ok(JsonValue::Null),
+            "disconnect" => {
+                connection
+                    .borrow_mut()
+                    .respond(&request, Ok(JsonValue::Null));
+                return 0;
+            }
+            _ => Err(format!("Unsupported request '{}'.", command)),
+        };
+        let configured = command == "configurationDone" && result.is_ok();
+        connection.borrow_mut().respond(&request, result);
+        if command == "initialize" {
+            connection
+                .borrow_mut()
+                .event("initialized", JsonValue::Null);
+        }
+        if configured {
+            break;
+        }
+    }
+
+    let launch = launch.unwrap();
+    let breakpoints = breakpoints.remove(&launch.program).unwrap_or_default();
+    let code = run(&connection, launch, breakpoints);
+    if connection.borrow().disconnected {
+        return 0;
+    }
+
+    let mut connection = connection.borrow_mut();
+    connection.event(
+        "exited",
+        JsonValue::object(Vec::from([("exitCode", JsonValue::Number(code as f64))])),
+    );
+    connection.event("terminated", JsonValue::Null);
+    loop {
+        let Some(request) = connection.read() else {
+            return 0;
+        };
+        match command(&request).as_str() {
+            "disconnect" => {
+                connection.respond(&request, Ok(JsonValue::Null));
+                return 0;
+            }
+            "threads" => connection.respond(&request, Ok(threads())),
+            _ => connection.respond(&request, Err("The program has exited.".to_string())),
+        }
+    }
+}
+
+/// Runs the launched script to the end and returns its exit code.
+fn run<R: BufRead + 'static, W: Write + 'static>(
+    connection: &Rc<RefCell<Connection<R, W>>>,
+    launch: Launch,
+    breakpoints: BTreeSet<u32>,
+) -> i32 {
+    let path = launch.program.to_string_lossy().to_string();
+    let Ok(source) = fs::read_to_string(&launch.program) else {
+        connection
+            .borrow_mut()
+            .output("stderr", &format!("Could not read '{}'.\n", path));
+        return 74;
+    };
+
+    let mut lox = Lox::default();
+    let statements = lox.parse(source);
+    if lox.had_error {
+        for error in &lox.compile_errors {
+            let report = format!("[line {}] Error: {}\n", error.token.line, error.message);
+            connection.borrow_mut().output("stderr", &report);
+        }
+        return 65;
+    }
+
+    let frontend = DapFrontend {
+        connection: connection.clone(),
+        program: launch.program,
+        handles: Vec::new(),
+    };
+    let mut interpreter = Interpreter::for_script(&path, launch.args);
+    interpreter.output = Output(Box::new(OutputEvents {
+        connection: connection.clone(),
+        buffer: Vec::new(),
+    }));
+    interpreter.hooks.push(Box::new(Debugger::new(
+        frontend,
+        breakpoints,
+        launch.stop_on_entry,
+    )));
+    interpreter.interpret(statements, &mut lox);
+
+    if let Some(report) = &lox.runtime_error_report {
+        connection
+            .borrow_mut()
+            .output("stderr", &format!("{}\n", report));
+    }
+    match lox.exit_code {
+        Some(code) => code,
+        None if lox.had_runtime_error => 70,
+        None => 0,
+    }
+}
+
+struct Launch {
+    program: PathBuf,
+    args: Vec<String>,
+    stop_on_entry: bool,
+}
+
+impl Launch {
+    fn from_request(request: &JsonValue) -> Result<Launch, String> {
+        let arguments = request.get("arguments").unwrap_or(&JsonValue::Null);
+        let Some(JsonValue::String(program)) = arguments.get("program") else {
+            return Err("'launch' needs a 'program' path.".to_string());
+        };
+        let Ok(program) = fs::canonicalize(program) else {
+            return Err(format!("Could not read '{}'.", program));
+        };
+
+        let mut args = Vec::new();
+        if let Some(JsonValue::Array(values)) = arguments.get("args") {
+            for value in values {
+                match value {
+                    JsonValue::String(value) => args.push(value.clone()),
+                    _ => return Err("'args' must be a list of strings.".to_string()),
+                }
+            }
+        }
+        let stop_on_entry = matches!(arguments.get("stopOnEntry"), Some(JsonValue::Bool(true)));
+
+        Ok(Launch {
+            program,
+            args,
+            stop_on_entry,
+        })
+    }
+}
+
+struct Connection<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    seq: u32,
+    // Whether the client left while the program was running.
+    disconnected: bool,
+}
+
+impl<R: BufRead, W: Write> Connection<R, W> {
+    /// Reads the next request, skipping malformed messages and anything
+    /// that is not a request. Returns `None` at the end of the stream.
+    fn read(&mut self) -> Option<JsonValue> {
+        loop {
+            match lsp::read_message(&mut self.input) {
+                Ok(Some(message)) => {
+                    if let Some(JsonValue::String(kind)) = message.get("type") {
+                        if kind == "request" {
+                            return Some(message);
+                        }
+                    }
+                }
+                Ok(None) => return None,
+                Err(_) => {}
+            }
+        }
+    }
+
+    fn send(&mut self, kind: &str, mut fields: Vec<(&str, JsonValue)>) {
+        self.seq += 1;
+        fields.insert(0, ("seq", JsonValue::Number(self.seq as f64)));
+        fields.insert(1, ("type", JsonValue::String(kind.to_string())));
+        let body = JsonValue::object(fields).write(None).unwrap_or_default();
+        let _ = write!(
+            self.output,
+            "Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = self.output.flush();
+    }
+
+    fn respond(&mut self, request: &JsonValue, result: Result<JsonValue, String>) {
+        let mut fields = Vec::from([
+            (
+                "request_seq",
+                request.get("seq").cloned().unwrap_or(JsonValue::Null),
+            ),
+            ("success", JsonValue::Bool(result.is_ok())),
+            ("command", JsonValue::String(command(request))),
+        ]);
+        match result {
+            Ok(JsonValue::Null) => {}
+            Ok(body) => fields.push(("body", body)),
+            Err(message) => fields.push(("message", JsonValue::String(message))),
+        }
+        self.send("response", fields);
+    }
+
+    fn event(&mut self, event: &str, body: JsonValue) {
+        let mut fields = Vec::from([("event", JsonValue::String(event.to_string()))]);
+        if body != JsonValue::Null {
+            fields.push(("body", body));
+        }
+        self.send("event", fields);
+    }
+
+    fn output(&mut self, category: &str, text: &str) {
+        self.event(
+            "output",
+            JsonValue::object(Vec::from([
+                ("category", JsonValue::String(category.to_string())),
+                ("output", JsonValue::String(text.to_string())),
+            ])),
+        );
+    }
+}
+
+/// Sends what the script prints as `output` events, a line at a time.
+struct OutputEvents<R: BufRead, W: Write> {
+    connection: Rc<RefCell<Connection<R, W>>>,
+    buffer: Vec<u8>,
+}
+
+impl<R: BufRead, W: Write> Write for OutputEvents<R, W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(bytes);
+        if let Some(end) = self.buffer.iter().rposition(|&byte| byte == b'\n') {
+            let lines: Vec<u8> = self.buffer.drain(..=end).collect();
+            self.connection
+                .borrow_mut()
+                .output("stdout", &String::from_utf8_lossy(&lines));
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let text = String::from_utf8_lossy(&self.buffer).to_string();
+            self.buffer.clear();
+            self.connection.borrow_mut().output("stdout", &text);
+        }
+        Ok(())
+    }
+}
+
+struct DapFrontend<R: BufRead, W: Write> {
+    connection: Rc<RefCell<Connection<R, W>>>,
+    program: PathBuf,
+    // The children behind each `variablesReference`, which is an index
+    // into this plus one. Cleared whenever the program stops.
+    handles: Vec<Vec<(String, BindableValue)>>,
+}
+
+impl<R: BufRead, W: Write> Frontend for DapFrontend<R, W> {
+    fn stopped(
+        &mut self,
+        breakpoints: &mut BTreeSet<u32>,
+        interpreter: &mut Interpreter,
+        reason: StopReason,
+    ) -> Result<Resume, RuntimeError> {
+        let (reason, description) = match reason {
+            StopReason::Entry => ("entry", "Paused on entry"),
+            StopReason::Step => ("step", "Paused after step"),
+            StopReason::Breakpoint => ("breakpoint", "Paused on breakpoint"),
+            StopReason::DebuggerStatement => ("pause", "Paused on 'debugger;'"),
+        };
+        self.connection.borrow_mut().event(
+            "stopped",
+            JsonValue::object(Vec::from([
+                ("reason", JsonValue::String(reason.to_string())),
+                ("description", JsonValue::String(description.to_string())),
+                ("threadId", JsonValue::Number(THREAD_ID)),
+                ("allThreadsStopped", JsonValue::Bool(true)),
+            ])),
+        );
+        self.handles.clear();
+        let stack = debugger::stack(interpreter);
+
+        loop {
+            let Some(request) = self.connection.borrow_mut().read() else {
+                self.connection.borrow_mut().disconnected = true;
+                return Err(stop(interpreter));
+            };
+            let command = command(&request);
+            let arguments = request.get("arguments").unwrap_or(&JsonValue::Null);
+            let resume = match command.as_str() {
+                "continue" => Some(Resume::Continue),
+                "next" => Some(Resume::StepOver),
+                "stepIn" => Some(Resume::StepInto),
+                "stepOut" => Some(Resume::StepOut),
+                _ => None,
+            };
+            let result = match command.as_str() {
+                _ if resume.is_some() => Ok(JsonValue::Null),
+                "threads" => Ok(threads()),
+                "stackTrace" => Ok(self.stack_trace(&stack)),
+                "scopes" => frame(arguments, &stack).map(|frame| self.scopes(frame)),
+                "variables" => self.variables(arguments),
+                "evaluate" => self.evaluate(arguments, &stack),
+                "setBreakpoints" => breakpoint_lines(&request).map(|(path, lines)| {
+                    if path != self.program {
+                        return breakpoints_body(&lines, false);
+                    }
+                    *breakpoints = lines.iter().copied().collect();
+                    breakpoints_body(&lines, true)
+                }),
+                "setExceptionBreakpoints" => Ok(JsonValue::Null),
+                "disconnect" | "terminate" => {
+                    self.connection.borrow_mut().disconnected = command == "disconnect";
+                    self.connection
+                        .borrow_mut()
+                        .respond(&request, Ok(JsonValue::Null));
+                    return Err(stop(interpreter));
+                }
+                _ => Err(format!("Unsupported request '{}'.", command)),
+            };
+            self.connection.borrow_mut().respond(&request, result);
+            if let Some(resume) = resume {
+                return Ok(resume);
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> DapFrontend<R, W> {
+    fn stack_trace(&self, stack: &[StackFrame]) -> JsonValue {
+        let name = self
+            .program
+            .file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().to_string());
+        let source = JsonValue::object(Vec::from([
+            ("name", JsonValue::String(name)),
+            (
+                "path",
+                JsonValue::String(self.program.to_string_lossy().to_string()),
+            ),
+        ]));
+        let frames = stack
+            .iter()
+            .enumerate()
+            .map(|(id, frame)| {
+                JsonValue::object(Vec::from([
+                    ("id", JsonValue::Number(id as f64)),
+                    ("name", JsonValue::String(frame.function.clone())),
+                    ("source", source.clone()),
+                    ("line", JsonValue::Number(frame.line as f64)),
+                    ("column", JsonValue::Number(1.0)),
+                ]))
+            })
+            .collect();
+        JsonValue::object(Vec::from([
+            ("stackFrames", JsonValue::Array(frames)),
+            ("totalFrames", JsonValue::Number(stack.len() as f64)),
+        ]))
+    }
+
+    fn scopes(&mut self, frame: &StackFrame) -> JsonValue {
+        let mut scopes = debugger::scopes(&frame.environment);
+        let globals = scopes.pop().unwrap_or_default();
+
+        let mut result = Vec::new();
+        if !scopes.is_empty() {
+            let mut locals: Vec<(String, BindableValue)> = Vec::new();
+            for (name, value) in scopes.into_iter().flatten() {
+                if !locals.iter().any(|(local, _)| *local == name) {
+                    locals.push((name, value));
+                }
+            }
+            result.push(self.scope("Locals", locals));
+        }
+        result.push(self.scope("Globals", globals));
+        JsonValue::object(Vec::from([("scopes", JsonValue::Array(result))]))
+    }
+
+    fn scope(&mut self, name: &str, variables: Vec<(String, BindableValue)>) -> JsonValue {
+        self.handles.push(variables);
+        JsonValue::object(Vec::from([
+            ("name", JsonValue::String(name.to_string())),
+            (
+                "variablesReference",
+                JsonValue::Number(self.handles.len() as f64),
+            ),
+            ("expensive", JsonValue::Bool(false)),
+        ]))
+    }
+
+    fn variables(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let reference = match arguments.get("variablesReference") {
+            Some(JsonValue::Number(reference)) => *reference as usize,
+            _ => 0,
+        };
+        let Some(variables) = reference
+            .checked_sub(1)
+            .and_then(|index| self.handles.get(index))
+        else {
+            return Err("Unknown 'variablesReference'.".to_string());
+        };
+
+        let variables = variables.clone();
+        let variables = variables
+            .iter()
+            .map(|(name, value)| {
+                JsonValue::object(Vec::from([
+                    ("name", JsonValue::String(name.clone())),
+                    ("value", JsonValue::String(debugger::describe(value))),
+                    (
+                        "variablesReference",
+                        JsonValue::Number(self.children(value) as f64),
+                    ),
+                ]))
+            })
+            .collect();
+        Ok(JsonValue::object(Vec::from([(
+            "variables",
+            JsonValue::Array(variables),
+        )])))
+    }
+
+    fn evaluate(
+        &mut self,
+        arguments: &JsonValue,
+        stack: &[StackFrame],
+    ) -> Result<JsonValue, String> {
+        let Some(JsonValue::String(expression)) = arguments.get("expression") else {
+            return Err("'evaluate' needs an 'expression'.".to_string());
+        };
+        let frame = match arguments.get("frameId") {
+            Some(_) => frame(arguments, stack)?,
+            None => &stack[0],
+        };
+        let name = expression.trim();
+        let Some(value) = debugger::lookup(&frame.environment, name) else {
+            return Err(format!("'{}' is not a variable in this frame.", name));
+        };
+
+        Ok(JsonValue::object(Vec::from([
+            ("result", JsonValue::String(debugger::describe(&value))),
+            (
+                "variablesReference",
+                JsonValue::Number(self.children(&value) as f64),
+            ),
+        ])))
+    }
+
+    /// The `variablesReference` for a value's elements, or 0 if it has none.
+    fn children(&mut self, value: &BindableValue) -> usize {
+        let children = match value {
+            BindableValue::List(list) => list
+                .borrow()
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (index.to_string(), value.clone()))
+                .collect(),
+            BindableValue::Map(map) => map
+                .borrow()
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            _ => return 0,
+        };
+        self.handles.push(children);
+        self.handles.len()
+    }
+}
+
+fn frame<'a>(arguments: &JsonValue, stack: &'a [StackFrame]) -> Result<&'a StackFrame, String> {
+    match arguments.get("frameId") {
+        Some(JsonValue::Number(id)) if (*id as usize) < stack.len() => Ok(&stack[*id as usize]),
+        _ => Err("Unknown 'frameId'.".to_string()),
+    }
+}
+
+fn command(request: &JsonValue) -> String {
+    match request.get("command") {
+        Some(JsonValue::String(command)) => command.clone(),
+        _ => String::new(),
+    }
+}
+
+fn capabilities() -> JsonValue {
+    JsonValue::object(Vec::from([
+        ("supportsConfigurationDoneRequest", JsonValue::Bool(true)),
+        ("supportsTerminateRequest", JsonValue::Bool(true)),
+        ("supportsEvaluateForHovers", JsonValue::Bool(true)),
+    ]))
+}
+
+fn threads() -> JsonValue {
+    let thread = JsonValue::object(Vec::from([
+        ("id", JsonValue::Number(THREAD_ID)),
+        ("name", JsonValue::String("main".to_string())),
+    ]));
+    JsonValue::object(Vec::from([(
+        "threads",
+        JsonValue::Array(Vec::from([thread])),
+    )]))
+}
+
+/// The source path and lines of a `setBreakpoints` request.
+fn breakpoint_lines(request: &JsonValue) -> Result<(PathBuf, Vec<u32>), String> {
+    let arguments = request.get("arguments").unwrap_or(&JsonValue::Null);
+    let Some(JsonValue::String(path)) = arguments
+        .get("source")
+        .and_then(|source| source.get("path"))
+    else {
+        return Err("'setBreakpoints' needs a 'source' with a 'path'.".to_string());
+    };
+
+    let mut lines = Vec::new();
+    if let Some(JsonValue::Array(breakpoints)) = arguments.get("breakpoints") {
+        for breakpoint in breakpoints {
+            if let Some(JsonValue::Number(line)) = breakpoint.get("line") {
+                lines.push(*line as u32);
+            }
+        }
+    }
+    let path = fs::canonicalize(path).unwrap_or_else(|_| Path::new(path).to_path_buf());
+    Ok((path, lines))
+}
+
+fn breakpoints_body(lines: &[u32], verified: bool) -> JsonValue {
+    let breakpoints = lines
+        .iter()
+        .map(|line| {
+            let mut fields = Vec::from([
+                ("verified", JsonValue::Bool(verified)),
+                ("line", JsonValue::Number(*line as f64)),
+            ]);
+            if !verified {
+                let message = "Breakpoints can only be set in the launched program.";
+                fields.push(("message", JsonValue::String(message.to_string())));
+            }
+            JsonValue::object(fields)
+        })
+        .collect();
+    JsonValue::object(Vec::from([("breakpoints", JsonValue::Array(breakpoints))]))
+}
+
+fn stop(interpreter: &Interpreter) -> RuntimeError {
+    RuntimeError {
+        token: Token {
+            ttype: TokenType::Eof,
+            lexeme: String::new(),
+            literal: None,
+            line: interpreter.line,
+            column: 0,
+        },
+        message: "Stopped by the debugger.".to_string(),
+        kind: ErrorKind::Exit(0),
+    }
+}
@@ -0,0 +1,417 @@
+//! The line-stepping debugger behind `jlox debug`.
+//!
+//! `Debugger` is a hook that decides where to stop: at line breakpoints, at
+//! `debugger;` statements and after steps. While the program is stopped a
+//! `Frontend` talks to the user and says how to go on; `Console` is the
+//! command prompt.
+//!
+//! Stops happen per line: a statement only stops the program if it starts
+//! a new line, meaning the previous one was on another line, in another
+//! frame, or to its right (as when a loop on one line comes round again).
+//! Breakpoints are lines of the script being debugged; statements run
+//! while importing a module never hit them.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    io::{BufRead, Write},
+    rc::Rc,
+};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    hook::{self, Hook},
+    interpreter::{ErrorKind, Interpreter, RuntimeError},
+    stmt::StmtType,
+    token_type::{LiteralType, Token, TokenType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Entry,
+    Step,
+    Breakpoint,
+    DebuggerStatement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resume {
+    Continue,
+    StepInto,
+    StepOver,
+    StepOut,
+}
+
+pub trait Frontend {
+    /// Called when the program stops, with the breakpoints to show and
+    /// change. Returns how to go on, or an error to end the program with.
+    fn stopped(
+        &mut self,
+        breakpoints: &mut BTreeSet<u32>,
+        interpreter: &mut Interpreter,
+        reason: StopReason,
+    ) -> Result<Resume, RuntimeError>;
+}
+
+pub struct Debugger<F: Frontend> {
+    pub breakpoints: BTreeSet<u32>,
+    frontend: F,
+    resume: Resume,
+    // The call depth when the program was last resumed.
+    resume_depth: usize,
+    at_entry: bool,
+    // The depth, line and column of the last statement seen.
+    previous: Option<(usize, u32, u32)>,
+    // How many files were being imported when the script started.
+    script_imports: Option<usize>,
+}
+
+impl<F: Frontend> Debugger<F> {
+    pub fn new(frontend: F, breakpoints: BTreeSet<u32>, stop_on_entry: bool) -> Debugger<F> {
+        Debugger {
+            breakpoints,
+            frontend,
+            resume: if stop_on_entry { Resume::StepInto } else { Resume::Continue },
+            resume_depth: 0,
+            at_entry: stop_on_entry,
+            previous: None,
+            script_imports: None,
+        }
+    }
+}
+
+impl<F: Frontend> Hook for Debugger<F> {
+    fn statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        stmt: &StmtType,
+        line: u32,
+    ) -> Result<(), RuntimeError> {
+        let script_imports = *self.script_imports.get_or_insert(interpreter.importing.len());
+        if interpreter.importing.len() > script_imports {
+            return Ok(());
+        }
+
+        let depth = interpreter.frames.len();
+        let column = hook::position(stmt).map_or(0, |(_, column)| column);
+        let new_line = match self.previous {
+            Some((previous_depth, previous_line, previous_column)) => {
+                depth != previous_depth || line != previous_line || column <= previous_column
+            }
+            None => true,
+        };
+        self.previous = Some((depth, line, column));
+
+        let stepped = new_line
+            && match self.resume {
+                Resume::Continue => false,
+                Resume::StepInto => true,
+                Resume::StepOver => depth <= self.resume_depth,
+                Resume::StepOut => depth < self.resume_depth,
+            };
+        let reason = if let StmtType::Debugger(_) = stmt {
+            StopReason::DebuggerStatement
+        } else if stepped && self.at_entry {
+            StopReason::Entry
+        } else if stepped {
+            StopReason::Step
+        } else if new_line && self.breakpoints.contains(&line) {
+            StopReason::Breakpoint
+        } else {
+            return Ok(());
+        };
+
+        self.at_entry = false;
+        self.resume = self.frontend.stopped(&mut self.breakpoints, interpreter, reason)?;
+        self.resume_depth = depth;
+        Ok(())
+    }
+}
+
+/// A frame of the call stack as a debugger shows it.
+pub struct StackFrame {
+    pub function: String,
+    /// The line being run in this frame: the current line for the innermost
+    /// frame, the line of the pending call for the others.
+    pub line: u32,
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+/// The call stack, innermost first, ending with the script as `<script>`.
+pub fn stack(interpreter: &Interpreter) -> Vec<StackFrame> {
+    let frames = &interpreter.frames;
+    let function = |index: usize| match index {
+        0 => "<script>".to_string(),
+        _ => frames[index - 1].function.clone(),
+    };
+
+    let mut stack = Vec::from([StackFrame {
+        function: function(frames.len()),
+        line: interpreter.line,
+        environment: interpreter.environment.clone().unwrap(),
+    }]);
+    for index in (0..frames.len()).rev() {
+        stack.push(StackFrame {
+            function: function(index),
+            line: frames[index].line,
+            environment: frames[index].environment.clone(),
+        });
+    }
+    stack
+}
+
+/// The scopes visible from `environment`, innermost first, each with its
+/// variables sorted by name. The last is the global scope, without the
+/// native functions.
+pub fn scopes(environment: &Rc<RefCell<Environment>>) -> Vec<Vec<(String, BindableValue)>> {
+    let mut scopes = Vec::new();
+    let mut current = Some(environment.clone());
+    while let Some(environment) = current {
+        let environment = environment.borrow();
+        let mut variables: Vec<(String, BindableValue)> = environment
+            .values
+            .iter()
+            .filter(|(_, value)| !matches!(value, BindableValue::NativeFunction(_)))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        variables.sort_by(|(left, _), (right, _)| left.cmp(right));
+        scopes.push(variables);
+        current = environment.enclosing.clone();
+    }
+    scopes
+}
+
+/// The value of the variable `name` as seen from `environment`.
+pub fn lookup(environment: &Rc<RefCell<Environment>>, name: &str) -> Option<BindableValue> {
+    let token = Token {
+        ttype: TokenType::Identifier,
+        lexeme: name.to_string(),
+        literal: None,
+        line: 0,
+        column: 0,
+    };
+    environment.borrow().get(&token).ok()
+}
+
+/// A value as a debugger shows it: like `print`, but strings are quoted.
+pub fn describe(value: &BindableValue) -> String {
+    match value {
+        BindableValue::Literal(LiteralType::String(value)) => format!("\"{}\"", value),
+        _ => Interpreter::stringify(value),
+    }
+}
+
+const HELP: &str = "\
+Commands:
+  continue, c          run to the next breakpoint or 'debugger;'
+  next, n              step over calls to the next line
+  step, s              step into calls
+  out, o               run until the current function returns
+  break, b <line>      set a breakpoint
+  delete, d <line>     remove a breakpoint
+  breakpoints          list breakpoints
+  backtrace, bt        show the call stack
+  up, down, frame <n>  select a frame for print and list
+  print, p [name]      show a variable, or all visible ones
+  list, l              show the source around the selected frame
+  quit, q              stop the program
+  help, h              show this help
+An empty line repeats the last command.";
+
+/// The `jlox debug` prompt. At the end of input it clears the breakpoints
+/// and lets the program run to completion.
+pub struct Console<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    source: Vec<String>,
+    selected: usize,
+    last_command: String,
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    pub fn new(input: R, output: W, source: &str) -> Console<R, W> {
+        Console {
+            input,
+            output,
+            source: source.lines().map(str::to_string).collect(),
+            selected: 0,
+            last_command: String::new(),
+        }
+    }
+
+    fn source_line(&self, line: u32) -> &str {
+        self.source.get((line as usize).wrapping_sub(1)).map_or("", String::as_str)
+    }
+
+    fn show_frame(&mut self, stack: &[StackFrame]) {
+        let frame = &stack[self.selected];
+        let text = format!(
+            "#{} {} at line {}\n{:>5} | {}",
+            self.selected,
+            frame.function,
+            frame.line,
+            frame.line,
+            self.source_line(frame.line)
+        );
+        let _ = writeln!(self.output, "{}", text);
+    }
+
+    /// Runs one command, returning how to resume if it resumes the program.
+    fn command(
+        &mut self,
+        line: &str,
+        breakpoints: &mut BTreeSet<u32>,
+        interpreter: &Interpreter,
+    ) -> Result<Option<Resume>, RuntimeError> {
+        let stack = stack(interpreter);
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let argument = words.next();
+        let line_argument = argument.and_then(|argument| argument.parse::<u32>().ok());
+
+        let text = match (command, line_argument) {
+            ("continue" | "c", _) => return Ok(Some(Resume::Continue)),
+            ("next" | "n", _) => return Ok(Some(Resume::StepOver)),
+            ("step" | "s", _) => return Ok(Some(Resume::StepInto)),
+            ("out" | "o", _) => return Ok(Some(Resume::StepOut)),
+            ("quit" | "q", _) => {
+                return Err(RuntimeError {
+                    token: Token {
+                        ttype: TokenType::Eof,
+                        lexeme: String::new(),
+                        literal: None,
+                        line: interpreter.line,
+                        column: 0,
+                    },
+                    message: "Stopped by the debugger.".to_string(),
+                    kind: ErrorKind::Exit(0),
+                })
+            }
+            ("break" | "b", Some(line)) => {
+                breakpoints.insert(line);
+                format!("Breakpoint set at line {}.", line)
+            }
+            ("delete" | "d", Some(line)) => match breakpoints.remove(&line) {
+                true => format!("Breakpoint at line {} deleted.", line),
+                false => format!("No breakpoint at line {}.", line),
+            },
+            ("break" | "b" | "delete" | "d", None) => format!("Usage: {} <line>", command),
+            ("breakpoints", _) if breakpoints.is_empty() => "No breakpoints.".to_string(),
+            ("breakpoints", _) => {
+                let lines: Vec<String> = breakpoints
+                    .iter()
+                    .map(|line| format!("line {}: {}", line, self.source_line(*line).trim()))
+                    .collect();
+                lines.join("\n")
+            }
+            ("backtrace" | "bt", _) => {
+                let frames: Vec<String> = stack
+                    .iter()
+                    .enumerate()
+                    .map(|(index, frame)| {
+                        let marker = if index == self.selected { "*" } else { " " };
+                        format!("{}#{} {} at line {}", marker, index, frame.function, frame.line)
+                    })
+                    .collect();
+                frames.join("\n")
+            }
+            ("up" | "down" | "frame" | "f", _) => {
+                let selected = match command {
+                    "up" => Some(self.selected + 1),
+                    "down" => self.selected.checked_sub(1),
+                    _ => line_argument.map(|index| index as usize),
+                };
+                match selected {
+                    Some(selected) if selected < stack.len() => {
+                        self.selected = selected;
+                        self.show_frame(&stack);
+                        return Ok(None);
+                    }
+                    _ => "No such frame.".to_string(),
+                }
+            }
+            ("print" | "p", _) => {
+                let environment = &stack[self.selected].environment;
+                match argument {
+                    Some(name) => match lookup(environment, name) {
+                        Some(value) => format!("{} = {}", name, describe(&value)),
+                        None => format!("No variable '{}' in scope.", name),
+                    },
+                    None => {
+                        let scopes = scopes(environment);
+                        let last = scopes.len() - 1;
+                        let mut lines = Vec::new();
+                        for (index, scope) in scopes.iter().enumerate() {
+                            if scope.is_empty() {
+                                continue;
+                            }
+                            lines.push(if index == last { "globals:" } else { "locals:" }.to_string());
+                            for (name, value) in scope {
+                                lines.push(format!("  {} = {}", name, describe(value)));
+                            }
+                        }
+                        lines.join("\n")
+                    }
+                }
+            }
+            ("list" | "l", _) => {
+                let current = stack[self.selected].line;
+                let first = current.saturating_sub(3).max(1);
+                let last = (current + 3).min(self.source.len() as u32);
+                let lines: Vec<String> = (first..=last)
+                    .map(|line| {
+                        let marker = if line == current { ">" } else { " " };
+                        format!("{}{:>4} | {}", marker, line, self.source_line(line))
+                    })
+                    .collect();
+                lines.join("\n")
+            }
+            ("help" | "h", _) => HELP.to_string(),
+            _ => format!("Unknown command '{}'. Type 'help' for a list.", command),
+        };
+
+        let _ = writeln!(self.output, "{}", text);
+        Ok(None)
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for Console<R, W> {
+    fn stopped(
+        &mut self,
+        breakpoints: &mut BTreeSet<u32>,
+        interpreter: &mut Interpreter,
+        reason: StopReason,
+    ) -> Result<Resume, RuntimeError> {
+        let heading = match reason {
+            StopReason::Entry | StopReason::Step => None,
+            StopReason::Breakpoint => Some("Breakpoint hit."),
+            StopReason::DebuggerStatement => Some("Paused at 'debugger'."),
+        };
+        if let Some(heading) = heading {
+            let _ = writeln!(self.output, "{}", heading);
+        }
+        self.selected = 0;
+        self.show_frame(&stack(interpreter));
+
+        loop {
+            let _ = write!(self.output, "(jlox) ");
+            let _ = self.output.flush();
+
+            let mut line = String::new();
+            if !matches!(self.input.read_line(&mut line), Ok(read) if read > 0) {
+                let _ = writeln!(self.output);
+                breakpoints.clear();
+                return Ok(Resume::Continue);
+            }
+            let line = match line.trim() {
+                "" => self.last_command.clone(),
+                line => line.to_string(),
+            };
+            self.last_command = line.clone();
+
+            if let Some(resume) = self.command(&line, breakpoints, interpreter)? {
+                return Ok(resume);
+            }
+        }
+    }
+}
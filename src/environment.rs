@@ -1,9 +1,15 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    mem,
+    rc::Rc,
+};
 
 use crate::{
-    interpreter::RuntimeError,
+    interpreter::{ErrorKind, LoxError, RuntimeError},
     lox_function::LoxFunction,
-    lox_std::NativeFunction,
+    lox_module::LoxModule,
+    lox_std::{map::LoxMap, NativeFunction},
     token_type::{LiteralType, Token},
 };
 
@@ -12,6 +18,10 @@ pub enum BindableValue {
     Literal(LiteralType),
     Function(LoxFunction),
     NativeFunction(NativeFunction),
+    Error(LoxError),
+    Module(LoxModule),
+    List(Rc<RefCell<Vec<BindableValue>>>),
+    Map(Rc<RefCell<LoxMap>>),
 }
 
 #[derive(Debug)]
@@ -21,22 +31,23 @@ pub struct Environment {
 }
 
 impl Environment {
-    pub fn define(&mut self, name: String, value: BindableValue) -> () {
+    pub fn define(&mut self, name: String, value: BindableValue) {
         self.values.insert(name.clone(), value);
     }
     pub fn get(&self, name: &Token) -> Result<BindableValue, RuntimeError> {
         let map_value = self.values.get(name.lexeme.as_str());
 
         if let Some(value) = map_value {
-            return Ok(value.clone());
+            Ok(value.clone())
         } else {
             if let Some(enclosing_env) = &self.enclosing {
                 return enclosing_env.borrow_mut().get(name);
             } else {
-                return Err(RuntimeError {
+                Err(RuntimeError {
                     token: name.clone(),
                     message: format!("Undefined variable '{}'.", &name.lexeme),
-                });
+                    kind: ErrorKind::Error,
+                })
             }
         }
     }
@@ -45,22 +56,17 @@ impl Environment {
         name: Token,
         value: BindableValue,
     ) -> Result<BindableValue, RuntimeError> {
-        let value_clone = value.clone();
-
-        if self.values.contains_key(&name.lexeme) {
-            let assignment = self.values.insert(name.lexeme, value);
-            match assignment {
-                Some(literal) => Ok(literal),
-                None => Ok(value_clone),
-            }
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            Ok(mem::replace(slot, value))
         } else {
             if let Some(enclosing_env) = &mut self.enclosing {
                 return enclosing_env.borrow_mut().assign(name, value);
             } else {
-                return Err(RuntimeError {
+                Err(RuntimeError {
                     token: name.clone(),
                     message: format!("Undefined variable '{}'.", &name.lexeme),
-                });
+                    kind: ErrorKind::Error,
+                })
             }
         }
     }
@@ -10,6 +10,10 @@ pub enum ExpressionType {
     Variable(Variable),
     Logical(Logical),
     Unary(Unary),
+    CompoundAssign(CompoundAssign),
+    Update(Update),
+    Conditional(Conditional),
+    Get(Get),
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +32,7 @@ pub struct Call {
     pub callee: Box<ExpressionType>,
     pub paren: Token,
     pub arguments: Vec<ExpressionType>,
+    pub optional: bool,
 }
 #[derive(Debug, Clone)]
 pub struct Grouping {
@@ -52,3 +57,27 @@ pub struct Unary {
     pub operator: Token,
     pub right: Box<ExpressionType>,
 }
+#[derive(Debug, Clone)]
+pub struct CompoundAssign {
+    pub name: Token,
+    pub operator: Token,
+    pub value: Box<ExpressionType>,
+}
+#[derive(Debug, Clone)]
+pub struct Update {
+    pub name: Token,
+    pub operator: Token,
+    pub prefix: bool,
+}
+#[derive(Debug, Clone)]
+pub struct Conditional {
+    pub condition: Box<ExpressionType>,
+    pub then_branch: Box<ExpressionType>,
+    pub else_branch: Box<ExpressionType>,
+}
+#[derive(Debug, Clone)]
+pub struct Get {
+    pub object: Box<ExpressionType>,
+    pub name: Token,
+    pub optional: bool,
+}
@@ -0,0 +1,588 @@
+use std::fs;
+
+use crate::{
+    environment::BindableValue,
+    expr::ExpressionType,
+    interpreter::Interpreter,
+    lox::{self, Lox},
+    parser::Parser,
+    scanner::{Comment, ScanError, Scanner},
+    stmt::StmtType,
+    token_type::{LiteralType, Token, TokenType},
+};
+
+const INDENT: &str = "    ";
+const MAX_WIDTH: usize = 100;
+
+/// `jlox fmt [--check] <files/dirs>`: rewrites each file in the canonical
+/// style, or with `--check` only lists the files that would change.
+pub fn run(args: &[String]) -> i32 {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths: Vec<String> = args.iter().filter(|arg| *arg != "--check").cloned().collect();
+
+    if paths.is_empty() {
+        println!("Usage: jlox fmt [--check] <files/dirs>");
+        return 64;
+    }
+
+    let mut exit_code = 0;
+    for file in lox::source_files(&paths) {
+        let display = file.display();
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {}", display, error);
+                exit_code = 74;
+                continue;
+            }
+        };
+
+        match format(&source) {
+            Ok(formatted) if formatted == source => {}
+            Ok(_) if check => {
+                println!("{} is not formatted", display);
+                exit_code = exit_code.max(1);
+            }
+            Ok(formatted) => {
+                if let Err(error) = fs::write(&file, formatted) {
+                    eprintln!("{}: {}", display, error);
+                    exit_code = 74;
+                }
+            }
+            Err(message) => {
+                eprintln!("{}: {}", display, message);
+                exit_code = exit_code.max(65);
+            }
+        }
+    }
+    exit_code
+}
+
+/// Formats Lox source: four-space indentation, one statement per line,
+/// spaces around binary operators, at most one blank line in a row, and
+/// calls that overflow 100 columns wrapped one argument per line. Comments
+/// are kept; those inside an expression move to the end of its statement.
+pub fn format(source: &str) -> Result<String, String> {
+    let (tokens, comments, scan_errors) = scan(source);
+
+    let mut lox = Lox::default();
+    let mut parser = Parser {
+        tokens: Vec::new(),
+        current: 0,
+    };
+    let statements = parser.parse(tokens.clone(), &mut lox);
+    // The scanner skips what it cannot read, so formatting after a scan
+    // error would drop it from the file.
+    if lox.had_error || !scan_errors.is_empty() {
+        return Err("could not be parsed.".to_string());
+    }
+
+    let mut formatter = Formatter {
+        tokens,
+        comments,
+        cursor: 0,
+        next_comment: 0,
+        output: String::new(),
+        indent: 0,
+        last_line: 0,
+        at_block_start: false,
+    };
+    for statement in &statements {
+        formatter.statement(statement);
+    }
+    formatter.leading_comments();
+    let formatted = formatter.output;
+
+    // The formatter only ever changes whitespace, so anything else is a bug
+    // that must not be written back over the user's file.
+    let (original_tokens, original_comments, _) = scan(source);
+    let (formatted_tokens, formatted_comments, _) = scan(&formatted);
+    let same_comments = original_comments
+        .iter()
+        .map(|comment| &comment.text)
+        .eq(formatted_comments.iter().map(|comment| &comment.text));
+    if !same_tokens(&original_tokens, &formatted_tokens) || !same_comments {
+        return Err("internal error: formatting would change the program.".to_string());
+    }
+
+    Ok(formatted)
+}
+
+fn scan(source: &str) -> (Vec<Token>, Vec<Comment>, Vec<ScanError>) {
+    let mut scanner = Scanner {
+        source: Vec::new(),
+        tokens: Vec::new(),
+        comments: Vec::new(),
+        positions: Vec::new(),
+        errors: Vec::new(),
+        start: 0,
+        current: 0,
+        line: 1,
+    };
+    let tokens = scanner.scan_tokens(source.to_string());
+    (tokens, scanner.comments, scanner.errors)
+}
+
+fn same_tokens(left: &[Token], right: &[Token]) -> bool {
+    left.len() == right.len()
+        && left.iter().zip(right).all(|(left, right)| {
+            left.ttype == right.ttype
+                && match left.ttype {
+                    TokenType::Number => left.literal == right.literal,
+                    _ => left.lexeme == right.lexeme,
+                }
+        })
+}
+
+// The column reached after writing `text` starting at `column`.
+fn end_column(column: usize, text: &str) -> usize {
+    match text.rfind('\n') {
+        Some(newline) => text.len() - newline - 1,
+        None => column + text.len(),
+    }
+}
+
+/// Re-emits the tree token by token, in source order. `cursor` follows
+/// along in the scanned tokens, which tells us where each comment and
+/// blank line of the original belongs.
+struct Formatter {
+    tokens: Vec<Token>,
+    comments: Vec<Comment>,
+    cursor: usize,
+    next_comment: usize,
+    output: String,
+    indent: usize,
+    // Source line of the last token or comment written.
+    last_line: u32,
+    at_block_start: bool,
+}
+
+impl Formatter {
+    fn statement(&mut self, stmt: &StmtType) {
+        self.begin_line();
+        self.statement_inline(stmt);
+        self.end_line();
+    }
+
+    fn statement_inline(&mut self, stmt: &StmtType) {
+        match stmt {
+            StmtType::Expression(expression) => {
+                self.expression(&expression.expression);
+                self.write_token(";");
+            }
+            StmtType::Print(print) => {
+                self.write_token("print");
+                self.write(" ");
+                self.expression(&print.expression);
+                self.write_token(";");
+            }
+            StmtType::Var(var) => {
+                self.write_token("var");
+                self.write(" ");
+                self.write_token(&var.name.lexeme);
+                if let Some(initializer) = &var.initializer {
+                    self.write(" ");
+                    self.write_token("=");
+                    self.write(" ");
+                    self.expression(initializer);
+                }
+                self.write_token(";");
+            }
+            StmtType::Block(block) => self.block(&block.statements),
+            StmtType::Debugger(_) => {
+                self.write_token("debugger");
+                self.write_token(";");
+            }
+            StmtType::If(if_stmt) => {
+                self.write_token("if");
+                self.write(" ");
+                self.write_token("(");
+                self.expression(&if_stmt.condition);
+                self.write_token(")");
+                self.write(" ");
+                self.body(&if_stmt.then_branch);
+
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    if let StmtType::Block(_) = *if_stmt.then_branch {
+                        self.write(" ");
+                    } else {
+                        self.continue_line();
+                    }
+                    self.write_token("else");
+                    self.write(" ");
+                    self.body(else_branch);
+                }
+            }
+            StmtType::While(while_stmt) => {
+                self.write_token("while");
+                self.write(" ");
+                self.write_token("(");
+                self.expression(&while_stmt.condition);
+                self.write_token(")");
+                self.write(" ");
+                self.body(&while_stmt.body);
+            }
+            StmtType::For(for_stmt) => {
+                self.write_token("for");
+                self.write(" ");
+                self.write_token("(");
+                match &for_stmt.initializer {
+                    Some(initializer) => self.statement_inline(initializer),
+                    None => self.write_token(";"),
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.write(" ");
+                    self.expression(condition);
+                }
+                self.write_token(";");
+                if let Some(increment) = &for_stmt.increment {
+                    self.write(" ");
+                    self.expression(increment);
+                }
+                self.write_token(")");
+                self.write(" ");
+                self.body(&for_stmt.body);
+            }
+            StmtType::Function(function) => {
+                self.write_token("fun");
+                self.write(" ");
+                self.write_token(&function.name.lexeme);
+                self.write_token("(");
+                for (index, param) in function.params.iter().enumerate() {
+                    if index > 0 {
+                        self.write_token(",");
+                        self.write(" ");
+                    }
+                    self.write_token(&param.lexeme);
+                }
+                self.write_token(")");
+                self.write(" ");
+                self.block(&function.body);
+            }
+            StmtType::Return(return_stmt) => {
+                self.write_token("return");
+                if let Some(value) = &return_stmt.value {
+                    self.write(" ");
+                    self.expression(value);
+                }
+                self.write_token(";");
+            }
+            StmtType::Throw(throw) => {
+                self.write_token("throw");
+                self.write(" ");
+                self.expression(&throw.value);
+                self.write_token(";");
+            }
+            StmtType::Try(try_stmt) => {
+                self.write_token("try");
+                self.write(" ");
+                self.block(&try_stmt.body);
+                if let (Some(name), Some(catch_body)) = (&try_stmt.catch_name, &try_stmt.catch_body) {
+                    self.write(" ");
+                    self.write_token("catch");
+                    self.write(" ");
+                    self.write_token("(");
+                    self.write_token(&name.lexeme);
+                    self.write_token(")");
+                    self.write(" ");
+                    self.block(catch_body);
+                }
+                if let Some(finally_body) = &try_stmt.finally_body {
+                    self.write(" ");
+                    self.write_token("finally");
+                    self.write(" ");
+                    self.block(finally_body);
+                }
+            }
+            StmtType::Import(import) => match &import.alias {
+                Some(alias) => {
+                    self.write_token("import");
+                    self.write(" ");
+                    self.write_token(&import.path.lexeme);
+                    self.write(" ");
+                    self.write_token("as");
+                    self.write(" ");
+                    self.write_token(&alias.lexeme);
+                    self.write_token(";");
+                }
+                None => {
+                    self.write_token("from");
+                    self.write(" ");
+                    self.write_token(&import.path.lexeme);
+                    self.write(" ");
+                    self.write_token("import");
+                    self.write(" ");
+                    for (index, name) in import.names.iter().enumerate() {
+                        if index > 0 {
+                            self.write_token(",");
+                            self.write(" ");
+                        }
+                        self.write_token(&name.lexeme);
+                    }
+                    self.write_token(";");
+                }
+            },
+            StmtType::Test(test) => {
+                self.write_token("test");
+                self.write(" ");
+                self.write_token(&test.description.lexeme);
+                self.write(" ");
+                self.block(&test.body);
+            }
+        }
+    }
+
+    // The body of an if, while or for stays on the same line.
+    fn body(&mut self, stmt: &StmtType) {
+        match stmt {
+            StmtType::Block(block) => self.block(&block.statements),
+            _ => self.statement_inline(stmt),
+        }
+    }
+
+    fn block(&mut self, statements: &[StmtType]) {
+        self.write_token("{");
+        if statements.is_empty() && self.due_comment().is_none() {
+            self.write_token("}");
+            return;
+        }
+
+        self.end_line();
+        self.indent += 1;
+        self.at_block_start = true;
+        for statement in statements {
+            self.statement(statement);
+        }
+        self.leading_comments();
+        self.indent -= 1;
+
+        self.write_indent();
+        self.write_token("}");
+    }
+
+    fn expression(&mut self, expr: &ExpressionType) {
+        let text = self.expr(expr, self.column());
+        self.write(&text);
+    }
+
+    // Renders an expression that starts at `column`, advancing the cursor
+    // past its tokens.
+    fn expr(&mut self, expr: &ExpressionType, column: usize) -> String {
+        match expr {
+            ExpressionType::Binary(binary) => {
+                let left = self.expr(&binary.left, column);
+                let operator = self.token(&binary.operator.lexeme);
+                let prefix = if let TokenType::Comma = binary.operator.ttype {
+                    format!("{}{} ", left, operator)
+                } else {
+                    format!("{} {} ", left, operator)
+                };
+                let right = self.expr(&binary.right, end_column(column, &prefix));
+                prefix + &right
+            }
+            ExpressionType::Logical(logical) => {
+                let left = self.expr(&logical.left, column);
+                let operator = self.token(&logical.operator.lexeme);
+                let prefix = format!("{} {} ", left, operator);
+                let right = self.expr(&logical.right, end_column(column, &prefix));
+                prefix + &right
+            }
+            ExpressionType::Unary(unary) => {
+                let operator = self.token(&unary.operator.lexeme);
+                let right = self.expr(&unary.right, column + operator.len());
+                // `- -x` must not become the `--` operator.
+                if operator == "-" && right.starts_with('-') {
+                    format!("{} {}", operator, right)
+                } else {
+                    operator + &right
+                }
+            }
+            ExpressionType::Grouping(grouping) => {
+                let open = self.token("(");
+                let inner = self.expr(&grouping.expression, column + 1);
+                let close = self.token(")");
+                open + &inner + &close
+            }
+            ExpressionType::Literal(literal) => {
+                let text = self.literal(&literal.value);
+                self.token(&text)
+            }
+            ExpressionType::Variable(variable) => self.token(&variable.name.lexeme),
+            ExpressionType::Assign(assign) => {
+                let name = self.token(&assign.name.lexeme);
+                let prefix = format!("{} {} ", name, self.token("="));
+                let value = self.expr(&assign.value, end_column(column, &prefix));
+                prefix + &value
+            }
+            ExpressionType::CompoundAssign(compound) => {
+                let name = self.token(&compound.name.lexeme);
+                let prefix = format!("{} {} ", name, self.token(&compound.operator.lexeme));
+                let value = self.expr(&compound.value, end_column(column, &prefix));
+                prefix + &value
+            }
+            ExpressionType::Update(update) => {
+                if update.prefix {
+                    let operator = self.token(&update.operator.lexeme);
+                    operator + &self.token(&update.name.lexeme)
+                } else {
+                    let name = self.token(&update.name.lexeme);
+                    name + &self.token(&update.operator.lexeme)
+                }
+            }
+            ExpressionType::Conditional(conditional) => {
+                let condition = self.expr(&conditional.condition, column);
+                let mut text = format!("{} {} ", condition, self.token("?"));
+                text += &self.expr(&conditional.then_branch, end_column(column, &text));
+                text += &format!(" {} ", self.token(":"));
+                text += &self.expr(&conditional.else_branch, end_column(column, &text));
+                text
+            }
+            ExpressionType::Get(get) => {
+                let object = self.expr(&get.object, column);
+                let dot = self.token(if get.optional { "?." } else { "." });
+                object + &dot + &self.token(&get.name.lexeme)
+            }
+            ExpressionType::Call(call) => {
+                let start = self.cursor;
+                let mut text = self.expr(&call.callee, column);
+                if call.optional {
+                    text += &self.token("?.");
+                }
+                text += &self.token("(");
+                for (index, argument) in call.arguments.iter().enumerate() {
+                    if index > 0 {
+                        text += &self.token(",");
+                        text += " ";
+                    }
+                    text += &self.expr(argument, end_column(column, &text));
+                }
+                text += &self.token(")");
+
+                let fits = !text.contains('\n') && column + text.len() <= MAX_WIDTH;
+                if fits || call.arguments.is_empty() {
+                    return text;
+                }
+
+                // Too wide: start over with one argument per line.
+                self.cursor = start;
+                let mut text = self.expr(&call.callee, column);
+                if call.optional {
+                    text += &self.token("?.");
+                }
+                text += &self.token("(");
+                self.indent += 1;
+                let argument_indent = INDENT.repeat(self.indent);
+                for (index, argument) in call.arguments.iter().enumerate() {
+                    text += "\n";
+                    text += &argument_indent;
+                    text += &self.expr(argument, argument_indent.len());
+                    if index + 1 < call.arguments.len() {
+                        text += &self.token(",");
+                    }
+                }
+                self.indent -= 1;
+                text += "\n";
+                text += &INDENT.repeat(self.indent);
+                text + &self.token(")")
+            }
+        }
+    }
+
+    // Number and string literals keep their source spelling.
+    fn literal(&self, value: &LiteralType) -> String {
+        let source = self.current_token();
+        match value {
+            LiteralType::F64(_) | LiteralType::String(_)
+                if source.literal.as_ref() == Some(value) =>
+            {
+                source.lexeme.clone()
+            }
+            LiteralType::String(string) => format!("\"{}\"", string),
+            _ => Interpreter::stringify(&BindableValue::Literal(value.clone())),
+        }
+    }
+
+    fn begin_line(&mut self) {
+        self.leading_comments();
+        self.separate(self.current_token().line);
+        self.write_indent();
+        self.at_block_start = false;
+    }
+
+    // Finishes the line, keeping a comment that trailed it in the source.
+    fn end_line(&mut self) {
+        if let Some(comment) = self.due_comment() {
+            let previous_line = self.tokens[comment.token_index.max(1) - 1].line;
+            if comment.token_index > 0 && comment.line == previous_line {
+                self.output.push(' ');
+                self.output.push_str(&comment.text);
+                self.last_line = comment.line;
+                self.next_comment += 1;
+            }
+        }
+        self.output.push('\n');
+    }
+
+    // Moves to a new line of the same statement, as for `else`.
+    fn continue_line(&mut self) {
+        self.end_line();
+        self.leading_comments();
+        self.write_indent();
+    }
+
+    // Writes the comments that come before the next token, each on a line
+    // of its own.
+    fn leading_comments(&mut self) {
+        while let Some(comment) = self.due_comment() {
+            self.separate(comment.line);
+            self.write_indent();
+            self.output.push_str(&comment.text);
+            self.output.push('\n');
+            self.last_line = comment.line;
+            self.at_block_start = false;
+            self.next_comment += 1;
+        }
+    }
+
+    fn due_comment(&self) -> Option<Comment> {
+        self.comments
+            .get(self.next_comment)
+            .filter(|comment| comment.token_index <= self.cursor)
+            .cloned()
+    }
+
+    // Keeps one blank line where the source had one or more.
+    fn separate(&mut self, line: u32) {
+        if !self.at_block_start && !self.output.is_empty() && line > self.last_line + 1 {
+            self.output.push('\n');
+        }
+    }
+
+    fn write_indent(&mut self) {
+        self.output.push_str(&INDENT.repeat(self.indent));
+    }
+
+    fn write(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn write_token(&mut self, text: &str) {
+        let text = self.token(text);
+        self.output.push_str(&text);
+    }
+
+    // Steps past the source token that `text` stands for.
+    fn token(&mut self, text: &str) -> String {
+        self.last_line = self.current_token().line;
+        self.cursor = (self.cursor + 1).min(self.tokens.len() - 1);
+        text.to_string()
+    }
+
+    fn current_token(&self) -> &Token {
+        &self.tokens[self.cursor]
+    }
+
+    fn column(&self) -> usize {
+        end_column(0, &self.output)
+    }
+}
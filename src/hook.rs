@@ -0,0 +1,96 @@
+//! Hooks let tools such as the debugger watch a running interpreter. They
+//! are installed in `Interpreter::hooks` and told about every statement
+//! before it runs and every call as it starts and finishes.
+
+use std::fmt;
+
+use crate::{
+    environment::BindableValue,
+    expr::ExpressionType,
+    interpreter::{Interpreter, RuntimeError},
+    stmt::StmtType,
+    token_type::Token,
+};
+
+pub trait Hook {
+    /// Called before each statement that has a position (see `position`),
+    /// with `interpreter.line` already set to `line`. Returning an error
+    /// stops the program with it.
+    fn statement(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        _stmt: &StmtType,
+        _line: u32,
+    ) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+
+    /// Called once a call's arguments are evaluated and its frame is on top
+    /// of `interpreter.frames`, before the callee runs.
+    fn call(&mut self, _interpreter: &mut Interpreter, _arguments: &[BindableValue]) {}
+
+    /// Called when the call on top of `interpreter.frames` finishes, before
+    /// its frame is popped.
+    fn call_finished(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        _result: &Result<BindableValue, RuntimeError>,
+    ) {
+    }
+
+    /// Called when the program takes a branch, with the token naming the
+    /// branch point: for an `if`, branch 0 is the then branch and 1 the
+    /// else branch (taken even if there is none); for a loop, 0 runs the
+    /// body and 1 leaves the loop; for `and`, `or` and `??`, 0 evaluates
+    /// the right operand and 1 short-circuits.
+    fn branch(&mut self, _interpreter: &mut Interpreter, _token: &Token, _branch: usize) {}
+}
+
+impl fmt::Debug for dyn Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<hook>")
+    }
+}
+
+/// The line and column a statement starts at: its keyword or name, or the
+/// first token of its expression. Blocks and `try` statements only group
+/// others and have none, and neither does an expression statement made of
+/// a lone literal.
+pub fn position(stmt: &StmtType) -> Option<(u32, u32)> {
+    let token = match stmt {
+        StmtType::Expression(stmt) => return expr_position(&stmt.expression),
+        StmtType::Function(stmt) => &stmt.name,
+        StmtType::If(stmt) => &stmt.keyword,
+        StmtType::Var(stmt) => &stmt.name,
+        StmtType::Print(stmt) => &stmt.keyword,
+        StmtType::Return(stmt) => &stmt.keyword,
+        StmtType::While(stmt) => &stmt.keyword,
+        StmtType::For(stmt) => &stmt.keyword,
+        StmtType::Throw(stmt) => &stmt.keyword,
+        StmtType::Import(stmt) => &stmt.keyword,
+        StmtType::Test(stmt) => &stmt.keyword,
+        StmtType::Debugger(stmt) => &stmt.keyword,
+        StmtType::Block(_) | StmtType::Try(_) => return None,
+    };
+    Some((token.line, token.column))
+}
+
+/// The position of the leftmost token of an expression, if it has one.
+pub fn expr_position(expr: &ExpressionType) -> Option<(u32, u32)> {
+    let token = match expr {
+        ExpressionType::Assign(expr) => &expr.name,
+        ExpressionType::Binary(expr) => return expr_position(&expr.left),
+        ExpressionType::Call(expr) => return expr_position(&expr.callee),
+        ExpressionType::Grouping(expr) => return expr_position(&expr.expression),
+        ExpressionType::Literal(_) => return None,
+        ExpressionType::Variable(expr) => &expr.name,
+        ExpressionType::Logical(expr) => return expr_position(&expr.left),
+        ExpressionType::Unary(expr) => &expr.operator,
+        ExpressionType::CompoundAssign(expr) => &expr.name,
+        ExpressionType::Update(expr) if expr.prefix => &expr.operator,
+        ExpressionType::Update(expr) => &expr.name,
+        ExpressionType::Conditional(expr) => return expr_position(&expr.condition),
+        ExpressionType::Get(expr) => return expr_position(&expr.object),
+    };
+    Some((token.line, token.column))
+}
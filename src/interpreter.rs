@@ -1,35 +1,213 @@
-use std::{cell::RefCell, collections::HashMap, ops::Neg, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, env, fmt, fs, io::{self, Write}, mem, ops::Neg, path::PathBuf, rc::Rc, time::{Duration, Instant}};
 
 use crate::{
-    environment::{BindableValue, Environment}, expr::{Assign, Binary, Call, ExpressionType, Grouping, Literal, Logical, Unary, Variable}, lox::Lox, lox_function::LoxFunction, lox_std::NativeFunction, stmt::{Block, Function, If, Return, StmtType, Var, While}, token_type::{LiteralType, Token, TokenType}, LoxCallable
+    environment::{BindableValue, Environment}, expr::{Assign, Binary, Call, CompoundAssign, Conditional, ExpressionType, Get, Grouping, Literal, Logical, Unary, Update, Variable}, hook::{self, Hook}, lox::Lox, lox_function::LoxFunction, lox_module::{self, LoxModule}, lox_std::{self, math::Random}, memory, stmt::{Block, Expression, For, Function, If, Import, Return, StmtType, Test, Throw, Try, Var, While}, token_type::{LiteralType, Token, TokenType}, LoxCallable
 };
 
 #[derive(Debug)]
 pub struct Interpreter {
     pub globals: Rc<RefCell<Environment>>,
     pub environment: Option<Rc<RefCell<Environment>>>,
+    /// The file of the code running: the script, a module being loaded,
+    /// or the file a called function was declared in.
+    pub current_file: Option<PathBuf>,
+    pub search_path: Vec<PathBuf>,
+    pub modules: HashMap<PathBuf, LoxModule>,
+    pub importing: Vec<PathBuf>,
+    pub random: Random,
+    pub script_args: Vec<String>,
+    pub hooks: Vec<Box<dyn Hook>>,
+    /// The calls in progress, innermost last.
+    pub frames: Vec<CallFrame>,
+    /// The line of the statement being run. Only kept while hooks are
+    /// installed or steps are limited.
+    pub line: u32,
+    /// Where `print` writes.
+    pub output: Output,
+    pub limits: Limits,
+    /// Statements executed so far, counted while steps or time are limited.
+    pub steps: u64,
+    /// Bytes and elements natives and `+` have allocated since the memory
+    /// in use was last measured, counted while memory is limited.
+    pub allocated: usize,
+    /// The memory in use at the last measurement.
+    pub in_use: usize,
+    pub started: Instant,
+}
+
+/// What a script may use before it is stopped with an `ErrorKind::Limit`
+/// error. Only the call depth is limited by default, so that deep
+/// recursion fails cleanly instead of overflowing the native stack.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Statements that may be executed.
+    pub steps: Option<u64>,
+    /// Calls that may be in progress at once.
+    pub call_depth: Option<usize>,
+    /// The most bytes in strings plus elements in lists and maps that the
+    /// script may hold at once, and so also the most in any one value.
+    /// Natives that build large values check the size before allocating.
+    /// Values are freed by reference counting, so the total is measured by
+    /// walking what the script can reach, once enough has been allocated
+    /// since the last measurement that it might be over.
+    pub memory: Option<usize>,
+    /// How long the script may run, counted from when the interpreter was
+    /// created.
+    pub timeout: Option<Duration>,
+}
+
+pub const DEFAULT_CALL_DEPTH: usize = 1000;
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            steps: None,
+            call_depth: Some(DEFAULT_CALL_DEPTH),
+            memory: None,
+            timeout: None,
+        }
+    }
+}
+
+/// The stream `print` writes to: stdout, unless a tool such as the DAP
+/// server needs stdout for itself.
+pub struct Output(pub Box<dyn Write>);
+
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<output>")
+    }
+}
+
+/// A call in progress.
+#[derive(Debug, Clone)]
+pub struct CallFrame {
+    pub function: String,
+    /// The line the call was made on.
+    pub line: u32,
+    /// The caller's environment at the call.
+    pub environment: Rc<RefCell<Environment>>,
+    /// The file the called function was declared in; `None` for natives.
+    pub file: Option<PathBuf>,
 }
 
 #[derive(Debug)]
 pub struct RuntimeError {
     pub token: Token,
     pub message: String,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+    Error,
+    Throw(BindableValue),
+    Return(BindableValue),
+    Exit(i32),
+    /// A limit in `Interpreter::limits` was hit. Unlike `Error`, `catch`
+    /// does not stop it.
+    Limit,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoxError {
+    pub message: String,
+    pub line: u32,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 type DefaultResult = Result<Option<BindableValue>, RuntimeError>;
 
 impl Interpreter {
-    pub fn interpret(mut self, statements: Vec<StmtType>, lox_strt_instance: &mut Lox) -> () {
+    pub fn new() -> Interpreter {
+        let globals = Rc::new(RefCell::new(Self::global_environment()));
+
+        Interpreter {
+            globals: globals.clone(),
+            environment: Some(globals),
+            current_file: None,
+            search_path: Vec::new(),
+            modules: HashMap::new(),
+            importing: Vec::new(),
+            random: Random::from_time(),
+            script_args: Vec::new(),
+            hooks: Vec::new(),
+            frames: Vec::new(),
+            line: 0,
+            output: Output(Box::new(io::stdout())),
+            limits: Limits::default(),
+            steps: 0,
+            allocated: 0,
+            in_use: 0,
+            started: Instant::now(),
+        }
+    }
+    /// An interpreter set up to run the script at `path`: imports resolve
+    /// relative to it and through `LOX_PATH`.
+    pub fn for_script(path: &str, script_args: Vec<String>) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.current_file = Some(PathBuf::from(path));
+        interpreter.script_args = script_args;
+        if let Ok(script_path) = fs::canonicalize(path) {
+            interpreter.importing.push(script_path);
+        }
+        if let Some(search_path) = env::var_os("LOX_PATH") {
+            interpreter.search_path = env::split_paths(&search_path).collect();
+        }
+        interpreter
+    }
+    /// The root of the current scope chain: the script's globals, or the
+    /// module's while a module's code runs, its functions included.
+    pub fn root_environment(&self) -> Rc<RefCell<Environment>> {
+        let mut root = self.environment.clone().unwrap();
+        loop {
+            let enclosing = root.borrow().enclosing.clone();
+            match enclosing {
+                Some(enclosing) => root = enclosing,
+                None => return root,
+            }
+        }
+    }
+    fn global_environment() -> Environment {
+        let mut environment = Environment {
+            enclosing: None,
+            values: HashMap::new(),
+        };
+        lox_std::define_globals(&mut environment);
+        environment
+    }
+    pub fn interpret(mut self, statements: Vec<StmtType>, lox_strt_instance: &mut Lox) {
         for statement in statements {
             let execute_result = self.execute(statement);
 
             if let Err(runtime_error) = execute_result {
-                lox_strt_instance.runtime_error(runtime_error);
+                if let ErrorKind::Exit(code) = runtime_error.kind {
+                    lox_strt_instance.exit_code = Some(code);
+                } else {
+                    lox_strt_instance.runtime_error(runtime_error);
+                }
                 break;
             }
         }
     }
     pub fn evaluate(&mut self, expr: ExpressionType) -> DefaultResult {
+        if let Some(memory) = self.limits.memory {
+            let line = hook::expr_position(&expr).map_or(self.line, |(line, _)| line);
+            let value = self.evaluate_expr(expr)?;
+            if let Some(value) = &value {
+                Self::check_size(value, memory, line)?;
+            }
+            self.check_in_use(memory, value.as_ref(), line)?;
+            return Ok(value);
+        }
+        self.evaluate_expr(expr)
+    }
+    fn evaluate_expr(&mut self, expr: ExpressionType) -> DefaultResult {
         match expr {
             ExpressionType::Binary(binary) => Self::visit_binary_expr(self, binary),
             ExpressionType::Grouping(grouping) => Self::visit_grouping_expr(self, grouping),
@@ -39,9 +217,23 @@ impl Interpreter {
             ExpressionType::Assign(assignment) => Self::visit_assign_expr(self, assignment),
             ExpressionType::Logical(logical) => Self::visit_logical_expr(self, logical),
             ExpressionType::Call(call) => Self::visit_call_expr(self, call),
+            ExpressionType::CompoundAssign(compound) => Self::visit_compound_assign_expr(self, compound),
+            ExpressionType::Update(update) => Self::visit_update_expr(self, update),
+            ExpressionType::Conditional(conditional) => Self::visit_conditional_expr(self, conditional),
+            ExpressionType::Get(get) => Self::visit_get_expr(self, get),
         }
     }
     fn execute(&mut self, stmt: StmtType) -> DefaultResult {
+        if self.limits.steps.is_some() || self.limits.timeout.is_some() {
+            self.check_step(&stmt)?;
+        }
+        if !self.hooks.is_empty() {
+            if let Some((line, _)) = hook::position(&stmt) {
+                self.line = line;
+                self.run_hooks(|hook, interpreter| hook.statement(interpreter, &stmt, line))?;
+            }
+        }
+
         match stmt {
             StmtType::Expression(expr) => Self::visit_expression_stmt(self, expr.expression),
             StmtType::Print(print) => Self::visit_print_stmt(self, print.expression),
@@ -49,8 +241,139 @@ impl Interpreter {
             StmtType::Block(block) => Self::visit_block_stmt(self, block),
             StmtType::If(if_stmt) => Self::visit_if_stmt(self, if_stmt),
             StmtType::While(while_stmt) => Self::visit_while_stmt(self, while_stmt),
+            StmtType::For(for_stmt) => Self::visit_for_stmt(self, for_stmt),
             StmtType::Function(function) => Self::visit_function_stmt(self, function),
             StmtType::Return(ret_stmt) => Self::visit_return_stmt(self, ret_stmt),
+            StmtType::Throw(throw_stmt) => Self::visit_throw_stmt(self, throw_stmt),
+            StmtType::Try(try_stmt) => Self::visit_try_stmt(self, try_stmt),
+            StmtType::Import(import) => Self::visit_import_stmt(self, import),
+            StmtType::Test(test) => Self::visit_test_stmt(test),
+            StmtType::Debugger(_) => Ok(None),
+        }
+    }
+    fn check_step(&mut self, stmt: &StmtType) -> Result<(), RuntimeError> {
+        if let Some((line, _)) = hook::position(stmt) {
+            self.line = line;
+        }
+        self.steps += 1;
+        if let Some(steps) = self.limits.steps {
+            if self.steps > steps {
+                return Err(Self::limit_error(self.line, format!("Step limit of {} exceeded.", steps)));
+            }
+        }
+        if let Some(timeout) = self.limits.timeout {
+            if self.started.elapsed() > timeout {
+                return Err(self.timeout_error(self.line));
+            }
+        }
+        Ok(())
+    }
+    fn check_size(value: &BindableValue, memory: usize, line: u32) -> Result<(), RuntimeError> {
+        let (kind, size) = match value {
+            BindableValue::Literal(LiteralType::String(string)) => ("string", string.len()),
+            BindableValue::List(list) => ("list", list.borrow().len()),
+            BindableValue::Map(map) => ("map", map.borrow().len()),
+            _ => return Ok(()),
+        };
+        Self::check_memory(memory, kind, size, line)
+    }
+    fn check_memory(memory: usize, kind: &str, size: usize, line: u32) -> Result<(), RuntimeError> {
+        if size > memory {
+            let unit = if kind == "string" { "bytes" } else { "elements" };
+            let message = format!("Memory limit of {} exceeded by a {} of {} {}.", memory, kind, size, unit);
+            return Err(Self::limit_error(line, message));
+        }
+        Ok(())
+    }
+    // `value` has just been evaluated and may not be stored anywhere yet.
+    fn check_in_use(&mut self, memory: usize, value: Option<&BindableValue>, line: u32) -> Result<(), RuntimeError> {
+        if self.in_use.saturating_add(self.allocated) <= memory {
+            return Ok(());
+        }
+        self.in_use = memory::in_use(self, value);
+        self.allocated = 0;
+        if self.in_use > memory {
+            let message = format!("Memory limit of {} exceeded by {} bytes and elements in use.", memory, self.in_use);
+            return Err(Self::limit_error(line, message));
+        }
+        Ok(())
+    }
+    /// Fails with an `ErrorKind::Limit` error if a `kind` ("string", "list"
+    /// or "map") of `size` bytes or elements would be over the memory limit.
+    /// Natives call this before building a value, so that the allocation
+    /// itself cannot abort the process. The size also counts towards the
+    /// total, which is checked once the expression finishes.
+    pub fn check_allocation(&mut self, kind: &str, size: usize, line: u32) -> Result<(), RuntimeError> {
+        self.check_growth(kind, size, size, line)
+    }
+    /// Like `check_allocation`, for a list or map growing to `size` by
+    /// `added` elements: only those count towards the total.
+    pub fn check_growth(&mut self, kind: &str, size: usize, added: usize, line: u32) -> Result<(), RuntimeError> {
+        if let Some(memory) = self.limits.memory {
+            Self::check_memory(memory, kind, size, line)?;
+            self.allocated = self.allocated.saturating_add(added);
+        }
+        Ok(())
+    }
+    // Concatenating two strings is checked before the result is built.
+    fn check_concatenation(
+        &mut self,
+        operator: &Token,
+        left: &BindableValue,
+        right: &BindableValue,
+    ) -> Result<(), RuntimeError> {
+        if let (
+            BindableValue::Literal(LiteralType::String(left)),
+            BindableValue::Literal(LiteralType::String(right)),
+        ) = (left, right)
+        {
+            let size = left.len().saturating_add(right.len());
+            self.check_allocation("string", size, operator.line)?;
+        }
+        Ok(())
+    }
+    /// How long the script may still run, if it has a time limit.
+    /// Natives that block wait at most this long.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.limits
+            .timeout
+            .map(|timeout| timeout.saturating_sub(self.started.elapsed()))
+    }
+    /// The error for a native that was stopped by the time limit at `line`.
+    pub fn timeout_error(&self, line: u32) -> RuntimeError {
+        let timeout = self.limits.timeout.unwrap_or_default();
+        let message = format!("Time limit of {}s exceeded.", timeout.as_secs_f64());
+        Self::limit_error(line, message)
+    }
+    fn limit_error(line: u32, message: String) -> RuntimeError {
+        RuntimeError {
+            token: Token {
+                ttype: TokenType::Eof,
+                lexeme: String::new(),
+                literal: None,
+                line,
+                column: 0,
+            },
+            message,
+            kind: ErrorKind::Limit,
+        }
+    }
+    // Hooks are taken out while they run so they can use the interpreter.
+    fn run_hooks(
+        &mut self,
+        mut run: impl FnMut(&mut dyn Hook, &mut Interpreter) -> Result<(), RuntimeError>,
+    ) -> Result<(), RuntimeError> {
+        let mut hooks = mem::take(&mut self.hooks);
+        let result = hooks.iter_mut().try_for_each(|hook| run(hook.as_mut(), self));
+        self.hooks = hooks;
+        result
+    }
+    fn run_branch_hooks(&mut self, token: &Token, branch: usize) {
+        if !self.hooks.is_empty() {
+            let _ = self.run_hooks(|hook, interpreter| {
+                hook.branch(interpreter, token, branch);
+                Ok(())
+            });
         }
     }
     fn visit_block_stmt(&mut self, stmt: Block) -> DefaultResult {
@@ -88,9 +411,14 @@ impl Interpreter {
     }
     fn visit_function_stmt(&mut self, stmt: Function) -> DefaultResult {
         let lexeme = stmt.name.lexeme.clone();
-        let function = LoxFunction { declaration: stmt };
+        let environment = self.environment.clone().unwrap();
+        let function = LoxFunction {
+            declaration: Rc::new(stmt),
+            closure: environment.clone(),
+            file: self.current_file.clone(),
+        };
 
-        self.environment.clone().unwrap().borrow_mut().define(lexeme, BindableValue::Function(function));
+        environment.borrow_mut().define(lexeme, BindableValue::Function(function));
 
         
         Ok(None)
@@ -99,7 +427,9 @@ impl Interpreter {
         let evaluate_result: Option<BindableValue> = self.evaluate(*stmt.condition)?;
 
         if let Some(value) = evaluate_result {
-            if Self::is_truthy(&value) {
+            let truthy = Self::is_truthy(&value);
+            self.run_branch_hooks(&stmt.keyword, if truthy { 0 } else { 1 });
+            if truthy {
                 self.execute(*stmt.then_branch)
             } else if let Some(else_branch) = stmt.else_branch {
                 self.execute(*else_branch)
@@ -112,27 +442,190 @@ impl Interpreter {
             )
         }
     }
+    // `print` writes the value as `stringify` renders it, the same text
+    // `str()` and `eprint` produce, so strings appear without quotes.
     fn visit_print_stmt(&mut self, expr: ExpressionType) -> DefaultResult {
         let value = self.evaluate(expr);
         match value {
             Ok(value) => {
-                println!("{:?}", Self::stringify(&Option::expect(value, "Interpreter implementation fail - print stmt adjacent expression not evaluated to a valid value")));
+                let _ = writeln!(self.output.0, "{}", Self::stringify(&Option::expect(value, "Interpreter implementation fail - print stmt adjacent expression not evaluated to a valid value")));
                 Ok(None)
             }
             Err(error) => Err(error),
         }
     }
     fn visit_return_stmt(&mut self, stmt: Return) -> DefaultResult {
-        match stmt.value {
-            ExpressionType::Literal(Literal { value: LiteralType::Nil }) => {
-                return Ok(Some(
-                    BindableValue::Literal(LiteralType::Nil)
-                ));
-            },
-            _ => {
-                return Ok(Some(self.evaluate(stmt.value)?.unwrap()));
+        let value = match stmt.value {
+            Some(value) => Option::expect(
+                self.evaluate(value)?,
+                "Interpreter implementation fail - return value not evaluated to a valid value",
+            ),
+            None => BindableValue::Literal(LiteralType::Nil),
+        };
+
+        Err(RuntimeError {
+            token: stmt.keyword,
+            message: String::from("Can't return from top-level code."),
+            kind: ErrorKind::Return(value),
+        })
+    }
+    fn visit_throw_stmt(&mut self, stmt: Throw) -> DefaultResult {
+        let value = Option::expect(
+            self.evaluate(stmt.value)?,
+            "Interpreter implementation fail - thrown value not evaluated to a valid value",
+        );
+
+        Err(RuntimeError {
+            token: stmt.keyword,
+            message: format!("Uncaught exception: {}", Self::stringify(&value)),
+            kind: ErrorKind::Throw(value),
+        })
+    }
+    fn visit_try_stmt(&mut self, stmt: Try) -> DefaultResult {
+        let mut result = self.execute_block(stmt.body, Environment {
+            enclosing: Some(self.environment.clone().unwrap()),
+            values: HashMap::new(),
+        });
+
+        if let (Some(catch_name), Some(catch_body)) = (stmt.catch_name, stmt.catch_body) {
+            if let Err(error) = result {
+                result = match error.kind {
+                    ErrorKind::Error | ErrorKind::Throw(_) => {
+                        let mut environment = Environment {
+                            enclosing: Some(self.environment.clone().unwrap()),
+                            values: HashMap::new(),
+                        };
+                        environment.define(catch_name.lexeme, Self::error_value(error));
+                        self.execute_block(catch_body, environment)
+                    }
+                    _ => Err(error),
+                };
+            }
+        }
+
+        if let Some(finally_body) = stmt.finally_body {
+            let finally_result = self.execute_block(finally_body, Environment {
+                enclosing: Some(self.environment.clone().unwrap()),
+                values: HashMap::new(),
+            });
+            // A limit or exit still stops the script, whatever finally did.
+            if let Err(error) = result {
+                if matches!(error.kind, ErrorKind::Limit | ErrorKind::Exit(_)) {
+                    return Err(error);
+                }
+                result = Err(error);
+            }
+            finally_result?;
+        }
+
+        result.map(|_| None)
+    }
+    fn visit_import_stmt(&mut self, stmt: Import) -> DefaultResult {
+        let module = self.load_module(&stmt.path)?;
+        let environment = self.environment.clone().unwrap();
+
+        if let Some(alias) = stmt.alias {
+            environment
+                .borrow_mut()
+                .define(alias.lexeme, BindableValue::Module(module));
+        } else {
+            for name in stmt.names {
+                let value = module.get(&name)?;
+                environment.borrow_mut().define(name.lexeme, value);
             }
-        } 
+        }
+        Ok(None)
+    }
+    fn load_module(&mut self, path: &Token) -> Result<LoxModule, RuntimeError> {
+        let Some(LiteralType::String(module_path)) = &path.literal else {
+            panic!("Interpreter implementation fail - import path is not a string literal")
+        };
+        let module_error = |message: String| RuntimeError {
+            token: path.clone(),
+            message,
+            kind: ErrorKind::Error,
+        };
+
+        let resolved = lox_module::resolve(module_path, self.current_file.as_deref(), &self.search_path)
+            .ok_or_else(|| module_error(format!("Cannot find module '{}'.", module_path)))?;
+
+        if let Some(module) = self.modules.get(&resolved) {
+            return Ok(module.clone());
+        }
+
+        if let Some(start) = self.importing.iter().position(|importing| *importing == resolved) {
+            let chain: Vec<String> = self.importing[start..]
+                .iter()
+                .chain(std::iter::once(&resolved))
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(module_error(format!("Import cycle detected: {}.", chain.join(" -> "))));
+        }
+
+        let source = fs::read_to_string(&resolved)
+            .map_err(|error| module_error(format!("Cannot read module '{}': {}.", module_path, error)))?;
+
+        let mut module_lox = Lox::default();
+        let statements = module_lox.parse(source);
+
+        if module_lox.had_error {
+            return Err(module_error(format!("Cannot parse module '{}'.", module_path)));
+        }
+
+        let environment = Rc::new(RefCell::new(Self::global_environment()));
+        let previous_environment = self.environment.replace(environment.clone());
+        let previous_file = self.current_file.replace(resolved.clone());
+        self.importing.push(resolved.clone());
+
+        let mut result: DefaultResult = Ok(None);
+        for statement in statements {
+            result = self.execute(statement);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.importing.pop();
+        self.current_file = previous_file;
+        self.environment = previous_environment;
+        result?;
+
+        let module = LoxModule {
+            path: resolved.clone(),
+            environment,
+        };
+        self.modules.insert(resolved, module.clone());
+        Ok(module)
+    }
+    fn error_value(error: RuntimeError) -> BindableValue {
+        match error.kind {
+            ErrorKind::Throw(value) => value,
+            _ => BindableValue::Error(LoxError {
+                message: error.message,
+                line: error.token.line,
+            }),
+        }
+    }
+    // Test blocks only run under `jlox test`, through `run_test`.
+    fn visit_test_stmt(_: Test) -> DefaultResult {
+        Ok(None)
+    }
+    /// Runs `setup`, the top-level statements of a test file other than its
+    /// tests, once before the file's tests.
+    pub fn run_setup(&mut self, setup: Vec<StmtType>) -> Result<(), RuntimeError> {
+        for statement in setup {
+            self.execute(statement)?;
+        }
+        Ok(())
+    }
+    /// Runs a test block in a scope of its own inside the file's globals, so
+    /// it sees the setup's definitions but not another test's variables.
+    pub fn run_test(&mut self, test: Test) -> Result<(), RuntimeError> {
+        self.execute_block(test.body, Environment {
+            enclosing: Some(self.environment.clone().unwrap()),
+            values: HashMap::new(),
+        })?;
+        Ok(())
     }
     fn visit_var_stmt(&mut self, stmt: Var) -> DefaultResult {
         let mut value: BindableValue = BindableValue::Literal(LiteralType::Nil);
@@ -147,21 +640,70 @@ impl Interpreter {
             .borrow_mut()
             .define(stmt.name.lexeme, value);
 
-        return Ok(None);
+        Ok(None)
     }
     fn visit_while_stmt(&mut self, stmt: While) -> DefaultResult {
-        while Self::is_truthy(&Option::expect(
-            self.evaluate(stmt.condition.clone())?,
-            "Interpreter implementation fail - while stmt condition not evaluated to a valid value",
-        )) {
+        loop {
+            let truthy = Self::is_truthy(&Option::expect(
+                self.evaluate(stmt.condition.clone())?,
+                "Interpreter implementation fail - while stmt condition not evaluated to a valid value",
+            ));
+            self.run_branch_hooks(&stmt.keyword, if truthy { 0 } else { 1 });
+            if !truthy {
+                return Ok(None);
+            }
             self.execute(*stmt.body.clone())?;
         }
-        return Ok(None);
+    }
+    // Runs as `{ initializer; while (condition) { body; increment; } }`,
+    // without passing the loop itself through `execute`, so hooks see the
+    // `for` once rather than again as a `while`.
+    fn visit_for_stmt(&mut self, stmt: For) -> DefaultResult {
+        let mut body = *stmt.body;
+
+        if let Some(increment) = stmt.increment {
+            body = StmtType::Block(Block {
+                statements: Vec::from([
+                    body,
+                    StmtType::Expression(Expression {
+                        expression: increment,
+                    }),
+                ]),
+            });
+        }
+
+        let condition = stmt.condition.unwrap_or(ExpressionType::Literal(Literal {
+            value: LiteralType::Bool(true),
+        }));
+
+        let loop_stmt = While {
+            keyword: stmt.keyword,
+            condition,
+            body: Box::new(body),
+        };
+
+        let previous = self.environment.replace(Rc::new(RefCell::new(Environment {
+            enclosing: self.environment.clone(),
+            values: HashMap::new(),
+        })));
+        let result = match stmt.initializer {
+            Some(initializer) => self
+                .execute(*initializer)
+                .and_then(|_| self.visit_while_stmt(loop_stmt)),
+            None => self.visit_while_stmt(loop_stmt),
+        };
+        self.environment = previous;
+        result
     }
     pub fn stringify(value: &BindableValue) -> String {
+        Self::stringify_nested(value, &mut Vec::new())
+    }
+    // `enclosing` holds the lists and maps being printed around `value`, so
+    // one that contains itself prints as `[...]` or `{...}` there.
+    fn stringify_nested(value: &BindableValue, enclosing: &mut Vec<*const ()>) -> String {
         match value {
-            BindableValue::Literal(LiteralType::F32(f32_value)) => {
-                        let mut text = f32_value.to_string();
+            BindableValue::Literal(LiteralType::F64(f64_value)) => {
+                        let mut text = f64_value.to_string();
                         if text.ends_with(".0") {
                             let decimal_offset = text.find(".0").unwrap_or(text.len());
                             text = text.drain(..decimal_offset).collect();
@@ -172,13 +714,39 @@ impl Interpreter {
             BindableValue::Literal(LiteralType::Bool(bool_value)) => bool_value.to_string(),
             BindableValue::Literal(LiteralType::String(string_value)) => string_value.clone(),
             BindableValue::Function(lox_function) => lox_function.to_string(),
-            BindableValue::NativeFunction(native_function) => {
-                match native_function {
-                    NativeFunction::Clock(clock) => {
-                        clock.to_string()
-                    }
+            BindableValue::NativeFunction(native_function) => native_function.to_string(),
+            BindableValue::Error(error) => error.message.clone(),
+            BindableValue::Module(module) => module.to_string(),
+            BindableValue::List(list) => {
+                let pointer = Rc::as_ptr(list) as *const ();
+                if enclosing.contains(&pointer) {
+                    return "[...]".to_string();
                 }
-            },
+                enclosing.push(pointer);
+                let items: Vec<String> = list
+                    .borrow()
+                    .iter()
+                    .map(|item| Self::stringify_nested(item, enclosing))
+                    .collect();
+                enclosing.pop();
+                format!("[{}]", items.join(", "))
+            }
+            BindableValue::Map(map) => {
+                let pointer = Rc::as_ptr(map) as *const ();
+                if enclosing.contains(&pointer) {
+                    return "{...}".to_string();
+                }
+                enclosing.push(pointer);
+                let entries: Vec<String> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| {
+                        format!("{}: {}", key, Self::stringify_nested(value, enclosing))
+                    })
+                    .collect();
+                enclosing.pop();
+                format!("{{{}}}", entries.join(", "))
+            }
         }
     }
     pub fn visit_literal_expr(literal: Literal) -> DefaultResult {
@@ -187,23 +755,80 @@ impl Interpreter {
     pub fn visit_logical_expr(&mut self, logical: Logical) -> DefaultResult {
         let left = self.evaluate(*logical.left)?;
 
-        if let TokenType::Or = logical.operator.ttype {
-            if Self::is_truthy(&Option::expect(
-            left.clone(),
-            "Interpreter implementation fail - while stmt condition not evaluated to a valid value",
-        )) {
-                return Ok(left);
-            }
+        let short_circuits = Self::short_circuits(
+            &logical.operator.ttype,
+            &Option::expect(
+                left.clone(),
+                "Interpreter implementation fail - logical expression left operand not evaluated to a valid value",
+            ),
+        );
+        self.run_branch_hooks(&logical.operator, if short_circuits { 1 } else { 0 });
+        if short_circuits {
+            return Ok(left);
+        }
+
+        self.evaluate(*logical.right)
+    }
+    fn short_circuits(operator: &TokenType, left: &BindableValue) -> bool {
+        match operator {
+            TokenType::Or => Self::is_truthy(left),
+            TokenType::And => !Self::is_truthy(left),
+            TokenType::QuestionQuestion => !matches!(left, BindableValue::Literal(LiteralType::Nil)),
+            TokenType::QuestionDot => matches!(left, BindableValue::Literal(LiteralType::Nil)),
+            _ => false,
+        }
+    }
+    pub fn visit_conditional_expr(&mut self, conditional: Conditional) -> DefaultResult {
+        let condition = Option::expect(
+            self.evaluate(*conditional.condition)?,
+            "Interpreter implementation fail - conditional expression condition not evaluated to a valid value",
+        );
+
+        if Self::is_truthy(&condition) {
+            self.evaluate(*conditional.then_branch)
         } else {
-            if !Self::is_truthy(&Option::expect(
-            left.clone(),
-            "Interpreter implementation fail - while stmt condition not evaluated to a valid value",
-        )) {
-                return Ok(left);
-            }
+            self.evaluate(*conditional.else_branch)
+        }
+    }
+    pub fn visit_get_expr(&mut self, expr: Get) -> DefaultResult {
+        let value = self.get_link(expr)?;
+        Ok(Some(value.unwrap_or(BindableValue::Literal(LiteralType::Nil))))
+    }
+    /// Evaluates the object or callee of a `.`, `?.` or call. `None` means
+    /// a `?.` in it found nil, which ends the whole chain: with `a` nil,
+    /// `a?.b.c()` is nil without looking up `c` or calling anything.
+    fn evaluate_chain(&mut self, expr: ExpressionType) -> DefaultResult {
+        match expr {
+            ExpressionType::Get(get) => self.get_link(get),
+            ExpressionType::Call(call) => self.call_link(call),
+            expr => self.evaluate(expr),
         }
+    }
+    fn get_link(&mut self, expr: Get) -> DefaultResult {
+        let Some(object) = self.evaluate_chain(*expr.object)? else {
+            return Ok(None);
+        };
 
-        return self.evaluate(*logical.right);
+        if expr.optional && Self::short_circuits(&TokenType::QuestionDot, &object) {
+            return Ok(None);
+        }
+
+        match object {
+            BindableValue::Module(module) => module.get(&expr.name).map(Some),
+            BindableValue::Map(map) => match map.borrow().get(&expr.name.lexeme) {
+                Some(value) => Ok(Some(value.clone())),
+                None => Err(RuntimeError {
+                    message: format!("Undefined property '{}'.", expr.name.lexeme),
+                    token: expr.name,
+                    kind: ErrorKind::Error,
+                }),
+            },
+            _ => Err(RuntimeError {
+                token: expr.name,
+                message: String::from("Only modules and maps have properties."),
+                kind: ErrorKind::Error,
+            }),
+        }
     }
     pub fn visit_grouping_expr(&mut self, grouping: Grouping) -> DefaultResult {
         self.evaluate(*grouping.expression)
@@ -212,24 +837,22 @@ impl Interpreter {
         let right_r_value = self.evaluate(*unary.right);
 
         if let Err(right_operand_error) = right_r_value {
-            return Err(RuntimeError {
-                message: right_operand_error.message,
-                token: right_operand_error.token,
-            });
+            return Err(right_operand_error);
         }
 
         let right_value = right_r_value.unwrap();
 
         match unary.operator.ttype {
             TokenType::Minus => {
-                if let Some(BindableValue::Literal(LiteralType::F32(f32_value))) = right_value {
-                    return Ok(Some(BindableValue::Literal(LiteralType::F32(
-                        f32_value.neg(),
+                if let Some(BindableValue::Literal(LiteralType::F64(f64_value))) = right_value {
+                    return Ok(Some(BindableValue::Literal(LiteralType::F64(
+                        f64_value.neg(),
                     ))));
                 } else {
                     return Err(RuntimeError {
                         message: String::from("Operand must be a number"),
                         token: unary.operator,
+                        kind: ErrorKind::Error,
                     });
                 }
             }
@@ -260,29 +883,61 @@ impl Interpreter {
         )?;
         Ok(Some(get_result))
     }
-    pub fn visit_binary_expr(&mut self, binary: Binary) -> DefaultResult {
-        let left = *binary.left;
-        let right = *binary.right;
-        let left_r_value = self.evaluate(left);
-        let right_r_value = self.evaluate(right);
+    pub fn visit_compound_assign_expr(&mut self, expr: CompoundAssign) -> DefaultResult {
+        let environment = self.environment.clone().unwrap();
+        let current = environment.borrow().get(&expr.name)?;
+        let value = Option::expect(
+            self.evaluate(*expr.value)?,
+            "Interpreter implementation fail - compound assignment value not evaluated to a valid value",
+        );
 
+        let (ttype, lexeme) = match expr.operator.ttype {
+            TokenType::PlusEqual => (TokenType::Plus, "+"),
+            TokenType::MinusEqual => (TokenType::Minus, "-"),
+            TokenType::StarEqual => (TokenType::Star, "*"),
+            _ => (TokenType::Slash, "/"),
+        };
+        let operator = Token {
+            ttype,
+            lexeme: lexeme.to_string(),
+            literal: None,
+            line: expr.operator.line,
+            column: expr.operator.column,
+        };
 
-        if let Err(left_operand_error) = left_r_value {
-            return Err(RuntimeError {
-                message: left_operand_error.message,
-                token: left_operand_error.token,
-            });
-        }
+        self.check_concatenation(&operator, &current, &value)?;
+        let result = Self::binary_operation(operator, current, value)?.unwrap();
+        environment.borrow_mut().assign(expr.name, result.clone())?;
+        Ok(Some(result))
+    }
+    pub fn visit_update_expr(&mut self, expr: Update) -> DefaultResult {
+        let environment = self.environment.clone().unwrap();
+        let current = environment.borrow().get(&expr.name)?;
 
-        if let Err(right_operand_error) = right_r_value {
+        let BindableValue::Literal(LiteralType::F64(f64_value)) = current else {
             return Err(RuntimeError {
-                message: right_operand_error.message,
-                token: right_operand_error.token,
+                message: String::from("Operand must be a number"),
+                token: expr.operator,
+                kind: ErrorKind::Error,
             });
-        }
+        };
 
-        let left_value = left_r_value.unwrap();
-        let right_value = right_r_value.unwrap();
+        let updated = match expr.operator.ttype {
+            TokenType::PlusPlus => f64_value + 1.0,
+            _ => f64_value - 1.0,
+        };
+        environment
+            .borrow_mut()
+            .assign(expr.name, BindableValue::Literal(LiteralType::F64(updated)))?;
+
+        let result = if expr.prefix { updated } else { f64_value };
+        Ok(Some(BindableValue::Literal(LiteralType::F64(result))))
+    }
+    pub fn visit_binary_expr(&mut self, binary: Binary) -> DefaultResult {
+        let left = *binary.left;
+        let right = *binary.right;
+        let left_value = self.evaluate(left)?;
+        let right_value = self.evaluate(right)?;
 
         let left_unwrapped_value = Option::expect(left_value, 
                       "Interpreter implementation fail - left operator in binary expression not evaluated to a valid value");
@@ -290,146 +945,191 @@ impl Interpreter {
         let right_unwrapped_value = Option::expect(right_value, 
                       "Interpreter implementation fail - left operator in binary expression not evaluated to a valid value");
 
-        if let TokenType::Minus = binary.operator.ttype {
+        self.check_concatenation(&binary.operator, &left_unwrapped_value, &right_unwrapped_value)?;
+        Self::binary_operation(binary.operator, left_unwrapped_value, right_unwrapped_value)
+    }
+    fn binary_operation(
+        operator: Token,
+        left_unwrapped_value: BindableValue,
+        right_unwrapped_value: BindableValue,
+    ) -> DefaultResult {
+        if let TokenType::Comma = operator.ttype {
+            return Ok(Some(right_unwrapped_value));
+        }
+        if let TokenType::Minus = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(
-                        BindableValue::Literal(LiteralType::F32(f32_left - f32_right))
-                    ));
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(
+                        BindableValue::Literal(LiteralType::F64(f64_left - f64_right))
+                    ))
                 }
                 _ => {
-                    return Err(RuntimeError {
+                    Err(RuntimeError {
                         message: String::from("Operands must be a number"),
-                        token: binary.operator,
+                        token: operator,
+                        kind: ErrorKind::Error,
                     })
                 }
             }
-        } else if let TokenType::Plus = binary.operator.ttype {
+        } else if let TokenType::Plus = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::F32(f32_left + f32_right))));
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::F64(f64_left + f64_right))))
                 }
                 (BindableValue::Literal(LiteralType::String(string_left)) , BindableValue::Literal(LiteralType::String(string_right)) ) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::String(format!(
+                    Ok(Some(BindableValue::Literal(LiteralType::String(format!(
                         "{}{}",
                         string_left, string_right
-                    )))));
+                    )))))
                 }
                 _ => {
-                    return Err(RuntimeError {
+                    Err(RuntimeError {
                         message: String::from("Operands must be two numbers or two strings"),
-                        token: binary.operator,
+                        token: operator,
+                        kind: ErrorKind::Error,
                     })
                 }
             }
-        } else if let TokenType::Slash = binary.operator.ttype {
+        } else if let TokenType::Slash = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::F32(f32_left / f32_right))));
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::F64(f64_left / f64_right))))
                 }
                 _ => {
-                    return Err(RuntimeError {
+                    Err(RuntimeError {
                         message: String::from("Operands must be a number"),
-                        token: binary.operator,
+                        token: operator,
+                        kind: ErrorKind::Error,
                     })
                 }
             }
-        } else if let TokenType::Star = binary.operator.ttype {
+        } else if let TokenType::Star = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::F32(f32_left * f32_right))));
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::F64(f64_left * f64_right))))
                 }
                 _ => {
-                    return Err(RuntimeError {
+                    Err(RuntimeError {
                         message: String::from("Operands must be a number"),
-                        token: binary.operator,
+                        token: operator,
+                        kind: ErrorKind::Error,
                     })
                 }
             }
-        } else if let TokenType::Greater = binary.operator.ttype {
+        } else if let TokenType::Greater = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::Bool(f32_left > f32_right))));
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(f64_left > f64_right))))
                 }
-                _ => {
-                    return Err(RuntimeError {
-                        message: String::from("Operands must be a number"),
-                        token: binary.operator,
-                    })
-                }
-            }
-        } else if let TokenType::GreaterEqual = binary.operator.ttype {
-            match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::Bool(f32_left >= f32_right))));
+                (BindableValue::Literal(LiteralType::String(string_left)), BindableValue::Literal(LiteralType::String(string_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(string_left > string_right))))
                 }
                 _ => {
-                    return Err(RuntimeError {
-                        message: String::from("Operands must be a number"),
-                        token: binary.operator,
+                    Err(RuntimeError {
+                        message: String::from("Operands must be two numbers or two strings"),
+                        token: operator,
+                        kind: ErrorKind::Error,
                     })
                 }
             }
-        } else if let TokenType::Less = binary.operator.ttype {
+        } else if let TokenType::GreaterEqual = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::Bool(f32_left < f32_right))));
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(f64_left >= f64_right))))
+                }
+                (BindableValue::Literal(LiteralType::String(string_left)), BindableValue::Literal(LiteralType::String(string_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(string_left >= string_right))))
                 }
                 _ => {
-                    return Err(RuntimeError {
-                        message: String::from("Operands must be a number"),
-                        token: binary.operator,
+                    Err(RuntimeError {
+                        message: String::from("Operands must be two numbers or two strings"),
+                        token: operator,
+                        kind: ErrorKind::Error,
                     })
                 }
             }
-        } else if let TokenType::LessEqual = binary.operator.ttype {
+        } else if let TokenType::Less = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(LiteralType::F32(f32_left)), BindableValue::Literal(LiteralType::F32(f32_right))) => {
-                    return Ok(Some(BindableValue::Literal(LiteralType::Bool(f32_left <= f32_right))));
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(f64_left < f64_right))))
+                }
+                (BindableValue::Literal(LiteralType::String(string_left)), BindableValue::Literal(LiteralType::String(string_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(string_left < string_right))))
                 }
                 _ => {
-                    return Err(RuntimeError {
-                        message: String::from("Operands must be a number"),
-                        token: binary.operator,
+                    Err(RuntimeError {
+                        message: String::from("Operands must be two numbers or two strings"),
+                        token: operator,
+                        kind: ErrorKind::Error,
                     })
                 }
             }
-        } else if let TokenType::BangEqual = binary.operator.ttype {
+        } else if let TokenType::LessEqual = operator.ttype {
             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(left_literal), BindableValue::Literal(right_literal)) => {
-                  Ok(Some(BindableValue
-                            ::Literal(LiteralType::Bool(left_literal != right_literal))))  
+                (BindableValue::Literal(LiteralType::F64(f64_left)), BindableValue::Literal(LiteralType::F64(f64_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(f64_left <= f64_right))))
                 }
-                _ => {
-            return Err(RuntimeError {
-                message: String::from("Comparisons only allowed between literals"),
-                token: binary.operator,
-            });        
-                }
-            }
-            
-        } else if let TokenType::EqualEqual = binary.operator.ttype {
-             match (left_unwrapped_value, right_unwrapped_value) {
-                (BindableValue::Literal(left_literal), BindableValue::Literal(right_literal)) => {
-                  Ok(Some(BindableValue
-                            ::Literal(LiteralType::Bool(left_literal == right_literal))))  
+                (BindableValue::Literal(LiteralType::String(string_left)), BindableValue::Literal(LiteralType::String(string_right))) => {
+                    Ok(Some(BindableValue::Literal(LiteralType::Bool(string_left <= string_right))))
                 }
                 _ => {
-            return Err(RuntimeError {
-                message: String::from("Comparisons only allowed between literals"),
-                token: binary.operator,
-            });        
+                    Err(RuntimeError {
+                        message: String::from("Operands must be two numbers or two strings"),
+                        token: operator,
+                        kind: ErrorKind::Error,
+                    })
                 }
             }
+        } else if let TokenType::BangEqual = operator.ttype {
+            Ok(Some(BindableValue::Literal(LiteralType::Bool(!Self::is_equal(
+                &left_unwrapped_value,
+                &right_unwrapped_value,
+            )))))
+        } else if let TokenType::EqualEqual = operator.ttype {
+            Ok(Some(BindableValue::Literal(LiteralType::Bool(Self::is_equal(
+                &left_unwrapped_value,
+                &right_unwrapped_value,
+            )))))
         } else {
-            return Err(RuntimeError {
+            Err(RuntimeError {
                 message: String::from("Invalid operator"),
-                token: binary.operator,
-            });
+                token: operator,
+                kind: ErrorKind::Error,
+            })
+        }
+    }
+    /// Literals compare by value; functions, lists, maps and modules
+    /// compare by identity. Values of different kinds are never equal.
+    pub fn is_equal(left: &BindableValue, right: &BindableValue) -> bool {
+        match (left, right) {
+            (BindableValue::Literal(left), BindableValue::Literal(right)) => left == right,
+            (BindableValue::Function(left), BindableValue::Function(right)) => {
+                Rc::ptr_eq(&left.declaration, &right.declaration)
+            }
+            (BindableValue::NativeFunction(left), BindableValue::NativeFunction(right)) => {
+                left.name() == right.name()
+            }
+            (BindableValue::Error(left), BindableValue::Error(right)) => {
+                left.message == right.message && left.line == right.line
+            }
+            (BindableValue::Module(left), BindableValue::Module(right)) => left.path == right.path,
+            (BindableValue::List(left), BindableValue::List(right)) => Rc::ptr_eq(left, right),
+            (BindableValue::Map(left), BindableValue::Map(right)) => Rc::ptr_eq(left, right),
+            _ => false,
         }
     }
      pub fn visit_call_expr(&mut self, expr: Call) -> DefaultResult {
-         let callee = self.evaluate(*expr.callee)?.unwrap();
+         let value = self.call_link(expr)?;
+         Ok(Some(value.unwrap_or(BindableValue::Literal(LiteralType::Nil))))
+     }
+     fn call_link(&mut self, expr: Call) -> DefaultResult {
+         let Some(callee) = self.evaluate_chain(*expr.callee)? else {
+             return Ok(None);
+         };
+
+         if expr.optional && Self::short_circuits(&TokenType::QuestionDot, &callee) {
+             return Ok(None);
+         }
 
          let mut arguments: Vec<BindableValue> = Vec::new();
 
@@ -437,26 +1137,79 @@ impl Interpreter {
              arguments.push(Option::expect(self.evaluate(argument)?, "Bug in visit_call_expr() call"));
          }
 
-         match callee {
-            BindableValue::Function(function) => {
-                    return function.call(Some(self), arguments).map(|function_return| Some(function_return));
-            }
-            BindableValue::NativeFunction(_native_function) => todo!(),
-            BindableValue::Literal(_) => {
-                    Err(RuntimeError { token: expr.paren, message: "Can only call functions and classes.".to_string()})
+         let (callable, name, file): (&dyn LoxCallable, &str, Option<PathBuf>) = match &callee {
+            BindableValue::Function(function) => (function, &function.declaration.name.lexeme, function.file.clone()),
+            BindableValue::NativeFunction(native_function) => (native_function, native_function.name(), None),
+            _ => {
+                    return Err(RuntimeError { token: expr.paren, message: "Can only call functions and classes.".to_string(), kind: ErrorKind::Error })
             },
-        }
+        };
+         Self::check_arity(callable.min_arity(), callable.arity(), arguments.len(), &expr.paren)?;
+         if let Some(call_depth) = self.limits.call_depth {
+             if self.frames.len() >= call_depth {
+                 let message = format!("Call depth limit of {} exceeded.", call_depth);
+                 return Err(Self::limit_error(expr.paren.line, message));
+             }
+         }
+
+         self.frames.push(CallFrame {
+             function: name.to_string(),
+             line: expr.paren.line,
+             environment: self.environment.clone().unwrap(),
+             file: file.clone(),
+         });
+         if !self.hooks.is_empty() {
+             let _ = self.run_hooks(|hook, interpreter| {
+                 hook.call(interpreter, &arguments);
+                 Ok(())
+             });
+         }
+
+         let caller_file = file.map(|file| self.current_file.replace(file));
+         let result = callable.call(Some(self), arguments, &expr.paren);
+         if let Some(caller_file) = caller_file {
+             self.current_file = caller_file;
+         }
+
+         if !self.hooks.is_empty() {
+             let _ = self.run_hooks(|hook, interpreter| {
+                 hook.call_finished(interpreter, &result);
+                 Ok(())
+             });
+         }
+         self.frames.pop();
+         result.map(Some)
      }
+    fn check_arity(
+        min_arity: usize,
+        arity: usize,
+        arguments: usize,
+        paren: &Token,
+    ) -> Result<(), RuntimeError> {
+        if arguments < min_arity || arguments > arity {
+            let expected = if min_arity == arity {
+                arity.to_string()
+            } else {
+                format!("{} to {}", min_arity, arity)
+            };
+            return Err(RuntimeError {
+                token: paren.clone(),
+                message: format!("Expected {} arguments but got {}.", expected, arguments),
+                kind: ErrorKind::Error,
+            });
+        }
+        Ok(())
+    }
     pub fn is_truthy(item: &BindableValue) -> bool {
         match item {
             BindableValue::Literal(LiteralType::Bool(bool)) => {
-                return *bool;
+                *bool
             }
             BindableValue::Literal(LiteralType::Nil) => {
-                return false;
+                false
             }
             _ => {
-                return true;
+                true
             }
         }
     }
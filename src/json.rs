@@ -0,0 +1,436 @@
+/// A JSON document. Objects keep their members in order, so documents we
+/// write (such as `jlox ast --format json`) have a stable layout.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parses a complete JSON document. Errors end with the line and column
+    /// of the offending input.
+    pub fn parse(text: &str) -> Result<JsonValue, String> {
+        JsonParser::new(text).parse()
+    }
+
+    /// Writes the value compactly, or one member per line with `indent`
+    /// repeated per level. Fails on numbers JSON cannot represent.
+    pub fn write(&self, indent: Option<&str>) -> Result<String, String> {
+        let mut output = String::new();
+        self.write_to(&mut output, indent, 0)?;
+        Ok(output)
+    }
+
+    /// The length in bytes of what `write` would return, found without
+    /// building the text, so callers can check it against a limit first.
+    pub fn written_len(&self, indent: Option<&str>) -> Result<usize, String> {
+        let mut length = Length(0);
+        self.write_to(&mut length, indent, 0)?;
+        Ok(length.0)
+    }
+
+    /// An object with the given members, in order.
+    pub fn object(members: Vec<(&str, JsonValue)>) -> JsonValue {
+        JsonValue::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// The member `key` of an object, if this is one and has it.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn write_to(&self, output: &mut impl Output, indent: Option<&str>, depth: usize) -> Result<(), String> {
+        match self {
+            JsonValue::Null => output.push_str("null"),
+            JsonValue::Bool(value) => output.push_str(&value.to_string()),
+            JsonValue::Number(value) => {
+                if !value.is_finite() {
+                    return Err(format!("cannot encode the number {}.", value));
+                }
+                output.push_str(&value.to_string());
+            }
+            JsonValue::String(value) => write_string(output, value),
+            JsonValue::Array(items) => {
+                output.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    newline(output, indent, depth + 1);
+                    item.write_to(output, indent, depth + 1)?;
+                }
+                if !items.is_empty() {
+                    newline(output, indent, depth);
+                }
+                output.push(']');
+            }
+            JsonValue::Object(members) => {
+                output.push('{');
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    newline(output, indent, depth + 1);
+                    write_string(output, key);
+                    output.push(':');
+                    if indent.is_some() {
+                        output.push(' ');
+                    }
+                    value.write_to(output, indent, depth + 1)?;
+                }
+                if !members.is_empty() {
+                    newline(output, indent, depth);
+                }
+                output.push('}');
+            }
+        }
+        Ok(())
+    }
+}
+
+fn newline(output: &mut impl Output, indent: Option<&str>, depth: usize) {
+    if let Some(indent) = indent {
+        output.push('\n');
+        for _ in 0..depth {
+            output.push_str(indent);
+        }
+    }
+}
+
+fn write_string(output: &mut impl Output, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            c if c < ' ' => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Where `write_to` puts its text: a string, or a running length.
+trait Output {
+    fn push(&mut self, c: char);
+    fn push_str(&mut self, text: &str);
+}
+
+impl Output for String {
+    fn push(&mut self, c: char) {
+        String::push(self, c);
+    }
+
+    fn push_str(&mut self, text: &str) {
+        String::push_str(self, text);
+    }
+}
+
+struct Length(usize);
+
+impl Output for Length {
+    fn push(&mut self, c: char) {
+        self.0 += c.len_utf8();
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.0 += text.len();
+    }
+}
+
+/// How deeply arrays and objects may nest before parsing gives up, so deep
+/// input is an error rather than a stack overflow.
+const MAX_DEPTH: usize = 512;
+
+/// A recursive-descent parser over the characters of a JSON document,
+/// tracking the line and column so errors can point at the bad input.
+struct JsonParser {
+    chars: Vec<char>,
+    current: usize,
+    line: usize,
+    column: usize,
+    depth: usize,
+}
+
+impl JsonParser {
+    fn new(text: &str) -> JsonParser {
+        JsonParser {
+            chars: text.chars().collect(),
+            current: 0,
+            line: 1,
+            column: 1,
+            depth: 0,
+        }
+    }
+
+    fn parse(&mut self) -> Result<JsonValue, String> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if !self.is_at_end() {
+            return Err(self.error("expects the end of input"));
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => Ok(JsonValue::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", JsonValue::Bool(true)),
+            Some('f') => self.keyword("false", JsonValue::Bool(false)),
+            Some('n') => self.keyword("null", JsonValue::Null),
+            Some(_) => Err(self.error("found an unexpected character")),
+            None => Err(self.error("expects a value")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut JsonParser) -> Result<JsonValue, String>,
+    ) -> Result<JsonValue, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(&format!(
+                "found arrays or objects nested more than {} deep",
+                MAX_DEPTH
+            )));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<JsonValue, String> {
+        self.advance();
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.match_char('}') {
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expects a string key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if !self.match_char(':') {
+                return Err(self.error("expects ':' after an object key"));
+            }
+            let value = self.value()?;
+            entries.push((key, value));
+
+            self.skip_whitespace();
+            if self.match_char('}') {
+                return Ok(JsonValue::Object(entries));
+            }
+            if !self.match_char(',') {
+                return Err(self.error("expects ',' or '}' after an object member"));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, String> {
+        self.advance();
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.match_char(']') {
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+
+            self.skip_whitespace();
+            if self.match_char(']') {
+                return Ok(JsonValue::Array(items));
+            }
+            if !self.match_char(',') {
+                return Err(self.error("expects ',' or ']' after an array element"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.advance();
+        let mut value = String::new();
+
+        loop {
+            match self.peek() {
+                None => return Err(self.error("found an unterminated string")),
+                Some('"') => {
+                    self.advance();
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.advance();
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.advance();
+                            value.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("found an invalid escape sequence")),
+                    };
+                    self.advance();
+                    value.push(escaped);
+                }
+                Some(c) if c < ' ' => {
+                    return Err(self.error("found a control character in a string"))
+                }
+                Some(c) => {
+                    self.advance();
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    // Called just after "\u"; joins a UTF-16 surrogate pair into one char.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex_digits()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("found an unpaired surrogate"));
+        }
+
+        if !(self.match_char('\\') && self.match_char('u')) {
+            return Err(self.error("expects a low surrogate after a high surrogate"));
+        }
+        let low = self.hex_digits()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("expects a low surrogate after a high surrogate"));
+        }
+        let code_point = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        Ok(char::from_u32(code_point).unwrap())
+    }
+
+    fn hex_digits(&mut self) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => {
+                    self.advance();
+                    code = code * 16 + digit;
+                }
+                None => return Err(self.error("expects four hex digits after '\\u'")),
+            }
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<JsonValue, String> {
+        let start = self.current;
+        let (line, column) = (self.line, self.column);
+
+        self.match_char('-');
+        if !self.match_char('0') {
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(self.error("expects a digit"));
+            }
+            self.digits();
+        }
+        if self.match_char('.') {
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(self.error("expects a digit after '.'"));
+            }
+            self.digits();
+        }
+        if self.match_char('e') || self.match_char('E') {
+            if !self.match_char('+') {
+                self.match_char('-');
+            }
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(self.error("expects a digit in the exponent"));
+            }
+            self.digits();
+        }
+
+        let text: String = self.chars[start..self.current].iter().collect();
+        text.parse::<f64>()
+            .map(JsonValue::Number)
+            .map_err(|_| format!("found an invalid number at line {}, column {}.", line, column))
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, String> {
+        for expected in word.chars() {
+            if !self.match_char(expected) {
+                return Err(self.error(&format!("expects '{}'", word)));
+            }
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            return true;
+        }
+        false
+    }
+
+    fn advance(&mut self) {
+        if self.chars[self.current] == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.current += 1;
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current >= self.chars.len()
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at line {}, column {}.", message, self.line, self.column)
+    }
+}
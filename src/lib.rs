@@ -1,71 +1,53 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+// Every fallible step of the interpreter returns a RuntimeError, which
+// carries its token and, for `throw` and `return`, a Lox value. Boxing it
+// would add an allocation to every error path, `return` included, for a
+// smaller Result on the happy path that the interpreter does not need.
+#![allow(clippy::result_large_err)]
 
 use interpreter::Interpreter;
-use token_type::LiteralType;
+use token_type::Token;
 
-use crate::{environment::BindableValue, expr::ExpressionType, interpreter::RuntimeError};
+use crate::{environment::BindableValue, interpreter::RuntimeError};
 
+pub mod ast_dot;
+pub mod ast_json;
 pub mod ast_printer;
+pub mod coverage;
+pub mod dap;
+pub mod debugger;
 pub mod environment;
 pub mod expr;
+pub mod formatter;
+pub mod hook;
 pub mod interpreter;
+pub mod json;
 pub mod lox;
 pub mod lox_function;
+pub mod lox_module;
+pub mod lox_std;
+pub mod lsp;
+pub mod memory;
 pub mod parser;
+pub mod profiler;
 pub mod scanner;
 pub mod stmt;
+pub mod test_runner;
+pub mod token_dump;
 pub mod token_type;
-
-pub mod lox_std {
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    use crate::{
-        environment::BindableValue,
-        expr::{ExpressionType, Literal},
-        interpreter::RuntimeError,
-        token_type::LiteralType,
-        LoxCallable,
-    };
-
-    #[derive(Debug, Clone)]
-    pub enum NativeFunction {
-        Clock(Clock),
-    }
-
-    #[derive(Debug, Clone)]
-    pub struct Clock;
-
-    impl LoxCallable for Clock {
-        fn call(
-            &self,
-            _: Option<&mut crate::interpreter::Interpreter>,
-            _: Vec<crate::environment::BindableValue>,
-        ) -> Result<BindableValue, RuntimeError> {
-            let now = SystemTime::now();
-            let time_elapsed = now
-                .duration_since(UNIX_EPOCH)
-                .expect("Time went backwards")
-                .as_secs_f32();
-
-            return Ok(BindableValue::Literal(LiteralType::F32(time_elapsed)));
-        }
-
-        fn arity(&self) -> usize {
-            return 0;
-        }
-
-        fn to_string(&self) -> String {
-            return "<native fn>".to_string();
-        }
-    }
-}
+pub mod tracer;
 
 pub trait LoxCallable {
     fn call(
         &self,
         interpreter: Option<&mut Interpreter>,
         arguments: Vec<BindableValue>,
+        paren: &Token,
     ) -> Result<BindableValue, RuntimeError>;
     fn arity(&self) -> usize;
+    /// Natives with optional trailing parameters accept anywhere from
+    /// `min_arity` to `arity` arguments.
+    fn min_arity(&self) -> usize {
+        self.arity()
+    }
     fn to_string(&self) -> String;
 }
@@ -1,83 +1,489 @@
-use crate::environment::{BindableValue, Environment};
+use crate::ast_dot;
+use crate::ast_json;
+use crate::ast_printer::AstPrinter;
+use crate::coverage::{Coverage, CoverageRecorder};
+use crate::dap;
+use crate::debugger::{Console, Debugger};
+use crate::formatter;
 use crate::interpreter;
-use crate::lox_function::LoxFunction;
-use crate::lox_std::{Clock, NativeFunction};
+use crate::json::JsonValue;
 use crate::parser::Parser;
+use crate::profiler::{Profile, Profiler};
 use crate::scanner::Scanner;
-use crate::stmt::Function;
+use crate::stmt::StmtType;
+use crate::test_runner;
+use crate::token_dump;
 use crate::token_type::{Token, TokenType};
-use interpreter::Interpreter;
+use crate::tracer::Tracer;
+use interpreter::{Interpreter, Limits};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use std::{fs, process, str::FromStr};
+
+const SOURCE_EXTENSIONS: [&str; 2] = ["lox", "jlox"];
 #[derive(Default)]
 pub struct Lox {
     pub args: Vec<String>,
     pub had_error: bool,
     pub had_runtime_error: bool,
+    pub exit_code: Option<i32>,
+    pub compile_errors: Vec<CompileError>,
+    /// The report of the runtime error that ended the program, if one did.
+    pub runtime_error_report: Option<String>,
+}
+
+/// A parse error, kept alongside the report on stderr for tools such as
+/// the language server.
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    pub token: Token,
+    pub message: String,
 }
 
 impl Lox {
     pub fn start(&mut self) {
         let args_length = self.args.len();
-        if args_length < 1 || args_length > 2 {
-            println!("Usage: jlox [script]");
+        if args_length < 1 {
+            println!("Usage: jlox [script] [args...]");
             process::exit(64);
-        } else if args_length == 2 {
+        } else if args_length >= 2 && self.args[1] == "test" {
+            process::exit(test_runner::run(&self.args[2..]));
+        } else if args_length >= 2 && self.args[1] == "fmt" {
+            process::exit(formatter::run(&self.args[2..]));
+        } else if args_length >= 2 && self.args[1] == "tokens" {
+            process::exit(token_dump::run(&self.args[2..]));
+        } else if args_length >= 2 && self.args[1] == "dap" {
+            process::exit(dap::serve(io::stdin().lock(), io::stdout()));
+        } else if args_length >= 2 && self.args[1] == "debug" {
+            Self::debug_file(self);
+        } else if args_length >= 2 && self.args[1] == "ast" {
+            Self::ast(self);
+        } else if args_length >= 2 && self.args[1] == "--coverage" {
+            Self::coverage_file(self);
+        } else if args_length >= 2 && self.args[1] == "--profile" {
+            Self::profile_file(self);
+        } else if args_length >= 2 && self.args[1] == "--trace" {
+            Self::trace_file(self);
+        } else if args_length >= 3 && self.args[1] == "--print-ast" {
+            Self::print_ast(self);
+        } else if args_length >= 2 {
             Self::run_file(self);
         }
     }
+    /// `jlox [limits] <file> [args...]` runs a script. The limits are
+    /// `--max-steps N` statements, `--max-depth N` calls in progress
+    /// (1000 by default), `--max-memory N` bytes in strings plus elements
+    /// in lists and maps held at once, and `--timeout SECONDS`.
     pub fn run_file(&mut self) {
-        let file: String =
-            fs::read_to_string(self.args[1].clone()).expect("File reading successful");
+        const USAGE: &str = "Usage: jlox [--max-steps n] [--max-depth n] [--max-memory n] [--timeout seconds] <file> [args...]";
 
-        let mut scanner = Scanner {
-            source: Vec::new(),
-            tokens: Vec::new(),
-            start: 0,
-            current: 0,
-            line: 1,
+        let mut limits = Limits::default();
+        let mut index = 1;
+        loop {
+            match (self.args.get(index).map(String::as_str), self.args.get(index + 1)) {
+                (Some("--max-steps"), Some(value)) => {
+                    limits.steps = Some(Self::limit_value("--max-steps", value))
+                }
+                (Some("--max-depth"), Some(value)) => {
+                    limits.call_depth = Some(Self::limit_value("--max-depth", value))
+                }
+                (Some("--max-memory"), Some(value)) => {
+                    limits.memory = Some(Self::limit_value("--max-memory", value))
+                }
+                (Some("--timeout"), Some(value)) => {
+                    let seconds: f64 = Self::limit_value("--timeout", value);
+                    match Duration::try_from_secs_f64(seconds) {
+                        Ok(timeout) => limits.timeout = Some(timeout),
+                        Err(_) => {
+                            eprintln!("Invalid value '{}' for --timeout.", value);
+                            process::exit(64);
+                        }
+                    }
+                }
+                _ => break,
+            }
+            index += 2;
+        }
+        let Some(path) = self.args.get(index).cloned() else {
+            eprintln!("{}", USAGE);
+            process::exit(64);
         };
 
-        let scanned_tokens = scanner.scan_tokens(file);
+        let file: String = fs::read_to_string(&path).expect("File reading successful");
 
-        let mut parser = Parser {
-            tokens: Vec::new(),
-            current: 0,
+        let statements = self.parse(file);
+
+        if self.had_error {
+            process::exit(65);
+        }
+
+        let mut interpreter = Interpreter::for_script(&path, self.args[index + 1..].to_vec());
+        interpreter.limits = limits;
+        interpreter.interpret(statements, self);
+
+        if let Some(code) = self.exit_code {
+            process::exit(code);
+        }
+        if self.had_runtime_error {
+            process::exit(70);
+        }
+    }
+    /// `jlox debug <file> [args...]` runs a script under the debugger's
+    /// command prompt, stopped before its first statement.
+    pub fn debug_file(&mut self) {
+        let Some(path) = self.args.get(2).cloned() else {
+            eprintln!("Usage: jlox debug <file> [args...]");
+            process::exit(64);
+        };
+        let Ok(file) = fs::read_to_string(&path) else {
+            eprintln!("Could not read '{}'.", path);
+            process::exit(74);
+        };
+
+        let statements = self.parse(file.clone());
+
+        if self.had_error {
+            process::exit(65);
+        }
+
+        let console = Console::new(io::stdin().lock(), io::stdout(), &file);
+        let mut interpreter = Interpreter::for_script(&path, self.args[3..].to_vec());
+        interpreter
+            .hooks
+            .push(Box::new(Debugger::new(console, BTreeSet::new(), true)));
+        interpreter.interpret(statements, self);
+        println!("Program exited.");
+
+        if let Some(code) = self.exit_code {
+            process::exit(code);
+        }
+        if self.had_runtime_error {
+            process::exit(70);
+        }
+    }
+    fn limit_value<T: FromStr>(flag: &str, value: &str) -> T {
+        match value.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                eprintln!("Invalid value '{}' for {}.", value, flag);
+                process::exit(64);
+            }
+        }
+    }
+    /// `jlox --trace [--only name,...] [--output file] <file> [args...]` runs
+    /// a script logging its statements and calls to stderr, or to `file`.
+    /// With `--only`, just what happens inside the named functions is
+    /// logged. See `tracer` for the format.
+    pub fn trace_file(&mut self) {
+        const USAGE: &str = "Usage: jlox --trace [--only name,...] [--output file] <file> [args...]";
+
+        let mut functions = Vec::new();
+        let mut output_path = None;
+        let mut index = 2;
+        loop {
+            match (self.args.get(index).map(String::as_str), self.args.get(index + 1)) {
+                (Some("--only"), Some(names)) => {
+                    let names = names.split(',').filter(|name| !name.is_empty());
+                    functions.extend(names.map(str::to_string));
+                }
+                (Some("--output"), Some(path)) => output_path = Some(path.clone()),
+                _ => break,
+            }
+            index += 2;
+        }
+        let Some(path) = self.args.get(index).cloned() else {
+            eprintln!("{}", USAGE);
+            process::exit(64);
+        };
+        let Ok(file) = fs::read_to_string(&path) else {
+            eprintln!("Could not read '{}'.", path);
+            process::exit(74);
+        };
+
+        let statements = self.parse(file);
+
+        if self.had_error {
+            process::exit(65);
+        }
+
+        let output: Box<dyn Write> = match output_path {
+            Some(output_path) => match fs::File::create(&output_path) {
+                Ok(output) => Box::new(io::BufWriter::new(output)),
+                Err(_) => {
+                    eprintln!("Could not write '{}'.", output_path);
+                    process::exit(74);
+                }
+            },
+            None => Box::new(io::stderr()),
+        };
+        let mut interpreter = Interpreter::for_script(&path, self.args[index + 1..].to_vec());
+        interpreter.hooks.push(Box::new(Tracer::new(output, functions)));
+        interpreter.interpret(statements, self);
+
+        if let Some(code) = self.exit_code {
+            process::exit(code);
+        }
+        if self.had_runtime_error {
+            process::exit(70);
+        }
+    }
+    /// `jlox --profile [--folded file] <file> [args...]` runs a script and
+    /// then reports to stderr how long scanning, parsing and interpreting
+    /// took, with per-function and per-line statistics. `--folded` also
+    /// writes the call stacks for flamegraph tools. See `profiler`.
+    pub fn profile_file(&mut self) {
+        const USAGE: &str = "Usage: jlox --profile [--folded file] <file> [args...]";
+
+        let args = self.args[2..].to_vec();
+        let (folded_path, path, script_args) = match args.as_slice() {
+            [flag, folded_path, path, script_args @ ..] if flag == "--folded" => {
+                (Some(folded_path.clone()), path.clone(), script_args.to_vec())
+            }
+            [path, script_args @ ..] if !path.starts_with("--") => {
+                (None, path.clone(), script_args.to_vec())
+            }
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(64);
+            }
+        };
+        let Ok(file) = fs::read_to_string(&path) else {
+            eprintln!("Could not read '{}'.", path);
+            process::exit(74);
+        };
+
+        let start = Instant::now();
+        let tokens = self.scan(file);
+        let scanned = Instant::now();
+        let statements = self.parse_tokens(tokens);
+        let parsed = Instant::now();
+
+        if self.had_error {
+            process::exit(65);
+        }
+
+        let profile = Rc::new(RefCell::new(Profile::default()));
+        let mut interpreter = Interpreter::for_script(&path, script_args);
+        interpreter.hooks.push(Box::new(Profiler::new(profile.clone())));
+        interpreter.interpret(statements, self);
+        let interpret = parsed.elapsed();
+
+        let phases = [
+            ("scan", scanned - start),
+            ("parse", parsed - scanned),
+            ("interpret", interpret),
+        ];
+        eprint!("\n{}", profile.borrow().report(&phases, &path, interpret));
+        if let Some(folded_path) = folded_path {
+            if fs::write(&folded_path, profile.borrow().folded(interpret)).is_err() {
+                eprintln!("Could not write '{}'.", folded_path);
+                process::exit(74);
+            }
+        }
+
+        if let Some(code) = self.exit_code {
+            process::exit(code);
+        }
+        if self.had_runtime_error {
+            process::exit(70);
+        }
+    }
+    /// `jlox --coverage <out.lcov> [--annotate <out.txt>] <file> [args...]`
+    /// runs a script and writes its line and branch coverage in lcov
+    /// format, and with `--annotate` also as the source with counts in the
+    /// gutter. See `coverage`.
+    pub fn coverage_file(&mut self) {
+        const USAGE: &str =
+            "Usage: jlox --coverage <out.lcov> [--annotate <out.txt>] <file> [args...]";
+
+        let args = self.args[2..].to_vec();
+        let (lcov_path, annotate_path, path, script_args) = match args.as_slice() {
+            [lcov_path, flag, annotate_path, path, script_args @ ..] if flag == "--annotate" => (
+                lcov_path.clone(),
+                Some(annotate_path.clone()),
+                path.clone(),
+                script_args.to_vec(),
+            ),
+            [lcov_path, path, script_args @ ..] if !path.starts_with("--") => {
+                (lcov_path.clone(), None, path.clone(), script_args.to_vec())
+            }
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(64);
+            }
+        };
+        let Ok(file) = fs::read_to_string(&path) else {
+            eprintln!("Could not read '{}'.", path);
+            process::exit(74);
         };
 
-        let statements: Vec<crate::stmt::StmtType> = parser.parse(scanned_tokens, self);
+        let statements = self.parse(file.clone());
 
         if self.had_error {
             process::exit(65);
         }
 
-        let mut interpreter = Interpreter {
-            globals: Rc::new(RefCell::new(Environment {
-                enclosing: None,
-                values: HashMap::new(),
-            })),
-            environment: None,
+        let coverage = Rc::new(RefCell::new(Coverage::new(&statements)));
+        let mut interpreter = Interpreter::for_script(&path, script_args);
+        interpreter
+            .hooks
+            .push(Box::new(CoverageRecorder::new(coverage.clone())));
+        interpreter.interpret(statements, self);
+
+        let source_path = fs::canonicalize(&path).map_or(path.clone(), |source_path| {
+            source_path.to_string_lossy().to_string()
+        });
+        let mut outputs = Vec::from([(lcov_path, coverage.borrow().lcov(&source_path))]);
+        if let Some(annotate_path) = annotate_path {
+            outputs.push((annotate_path, coverage.borrow().annotate(&file)));
+        }
+        for (output_path, text) in outputs {
+            if fs::write(&output_path, text).is_err() {
+                eprintln!("Could not write '{}'.", output_path);
+                process::exit(74);
+            }
+        }
+
+        if let Some(code) = self.exit_code {
+            process::exit(code);
+        }
+        if self.had_runtime_error {
+            process::exit(70);
+        }
+    }
+    pub fn print_ast(&mut self) {
+        let file: String =
+            fs::read_to_string(self.args[2].clone()).expect("File reading successful");
+
+        let statements = self.parse(file);
+
+        if self.had_error {
+            process::exit(65);
+        }
+
+        print!("{}", AstPrinter::print_program(&statements));
+    }
+    /// `jlox ast [--format sexp|json|dot] <file>` prints the syntax tree of
+    /// a script; `jlox ast --run <file.json> [args...]` runs a tree written
+    /// with `--format json`. See `ast_json` for the JSON schema.
+    pub fn ast(&mut self) {
+        const USAGE: &str =
+            "Usage: jlox ast [--format sexp|json|dot] <file>\n       jlox ast --run <file.json> [args...]";
+
+        let args = self.args[2..].to_vec();
+        match args.as_slice() {
+            [flag, path, script_args @ ..] if flag == "--run" => {
+                Self::run_ast_json(self, path, script_args.to_vec())
+            }
+            [path] => Self::print_ast_format(self, "sexp", path),
+            [flag, format, path] if flag == "--format" => {
+                Self::print_ast_format(self, format, path)
+            }
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(64);
+            }
+        }
+    }
+    fn print_ast_format(&mut self, format: &str, path: &str) {
+        if !["sexp", "json", "dot"].contains(&format) {
+            eprintln!("Unknown AST format '{}'; expected sexp, json or dot.", format);
+            process::exit(64);
+        }
+        let Ok(file) = fs::read_to_string(path) else {
+            eprintln!("Could not read '{}'.", path);
+            process::exit(74);
         };
-        interpreter.environment = Some(interpreter.globals.clone());
-        interpreter.globals.borrow_mut().define(
-            "clock".to_string(),
-            BindableValue::NativeFunction(NativeFunction::Clock(Clock)),
-        );
 
+        let statements = self.parse(file);
+
+        if self.had_error {
+            process::exit(65);
+        }
+
+        match format {
+            "json" => match ast_json::to_json(&statements).write(Some("  ")) {
+                Ok(text) => println!("{}", text),
+                Err(message) => {
+                    eprintln!("{}", message);
+                    process::exit(65);
+                }
+            },
+            "dot" => print!("{}", ast_dot::to_dot(&statements)),
+            _ => print!("{}", AstPrinter::print_program(&statements)),
+        }
+    }
+    fn run_ast_json(&mut self, path: &str, script_args: Vec<String>) {
+        let Ok(file) = fs::read_to_string(path) else {
+            eprintln!("Could not read '{}'.", path);
+            process::exit(74);
+        };
+
+        let statements = match JsonValue::parse(&file).and_then(|json| ast_json::from_json(&json)) {
+            Ok(statements) => statements,
+            Err(message) => {
+                eprintln!("{}: {}", path, message);
+                process::exit(65);
+            }
+        };
+
+        let interpreter = Interpreter::for_script(path, script_args);
         interpreter.interpret(statements, self);
 
+        if let Some(code) = self.exit_code {
+            process::exit(code);
+        }
         if self.had_runtime_error {
             process::exit(70);
         }
     }
-    pub fn runtime_error(&mut self, error: interpreter::RuntimeError) -> () {
+    /// Scans and parses `source`, reporting errors and setting `had_error`.
+    pub fn parse(&mut self, source: String) -> Vec<StmtType> {
+        let tokens = self.scan(source);
+        self.parse_tokens(tokens)
+    }
+    /// The first half of `parse`, kept apart so `--profile` can time it.
+    pub fn scan(&mut self, source: String) -> Vec<Token> {
+        let mut scanner = Scanner {
+            source: Vec::new(),
+            tokens: Vec::new(),
+            comments: Vec::new(),
+            positions: Vec::new(),
+            errors: Vec::new(),
+            start: 0,
+            current: 0,
+            line: 1,
+        };
+
+        let scanned_tokens = scanner.scan_tokens(source);
+        if !scanner.errors.is_empty() {
+            self.had_error = true;
+        }
+        scanned_tokens
+    }
+    pub fn parse_tokens(&mut self, scanned_tokens: Vec<Token>) -> Vec<StmtType> {
+        let mut parser = Parser {
+            tokens: Vec::new(),
+            current: 0,
+        };
+
+        parser.parse(scanned_tokens, self)
+    }
+    pub fn runtime_error(&mut self, error: interpreter::RuntimeError) {
         let message = format!("{}\n[line: {:?}]", error.message, error.token.line);
         eprintln!("{}", message);
+        self.runtime_error_report = Some(message);
         self.had_runtime_error = true;
     }
-    pub fn error(&mut self, token: Token, message: &str) -> () {
+    pub fn error(&mut self, token: Token, message: &str) {
+        self.compile_errors.push(CompileError {
+            token: token.clone(),
+            message: message.to_string(),
+        });
         if token.ttype == TokenType::Eof {
             Self::report(
                 self,
@@ -94,9 +500,43 @@ impl Lox {
             )
         }
     }
-    pub fn report(&mut self, line: &u32, location: String, message: &str) -> () {
+    pub fn report(&mut self, line: &u32, location: String, message: &str) {
         let report_message = format!("[line {}] Error {}: {}", line, location, message);
         eprintln!("{}", report_message);
         self.had_error = true;
     }
 }
+
+/// Expands the files and directories given to tools such as `jlox test`
+/// into a sorted list of files, searching directories recursively for
+/// `.lox` and `.jlox` sources.
+pub fn source_files(paths: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_source_files(PathBuf::from(path), &mut files);
+    }
+    files
+}
+
+fn collect_source_files(path: PathBuf, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        files.push(path);
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(&path) else {
+        files.push(path);
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    entries.sort();
+
+    for entry in entries {
+        let is_source = entry
+            .extension()
+            .is_some_and(|extension| SOURCE_EXTENSIONS.iter().any(|known| extension == *known));
+        if entry.is_dir() || is_source {
+            collect_source_files(entry, files);
+        }
+    }
+}
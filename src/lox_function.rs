@@ -1,15 +1,32 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, fmt, path::PathBuf, rc::Rc};
 
 use crate::{
     environment::{BindableValue, Environment},
-    interpreter::{Interpreter, RuntimeError},
+    interpreter::{ErrorKind, Interpreter, RuntimeError},
     stmt::Function,
+    token_type::{LiteralType, Token},
     LoxCallable,
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct LoxFunction {
-    pub declaration: Function,
+    /// Shared by every copy of the function value, so that `==` can tell
+    /// functions apart by identity.
+    pub declaration: Rc<Function>,
+    /// The environment the function was declared in, which its body runs
+    /// inside: a module's functions see the module's globals wherever
+    /// they are called from.
+    pub closure: Rc<RefCell<Environment>>,
+    /// The file the function was declared in, which is the running file
+    /// while its body runs.
+    pub file: Option<PathBuf>,
+}
+
+// The closure usually holds the function itself, so it is left out.
+impl fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LoxFunction({})", self.declaration.name.lexeme)
+    }
 }
 
 impl LoxCallable for LoxFunction {
@@ -20,10 +37,11 @@ impl LoxCallable for LoxFunction {
         &self,
         interpreter: Option<&mut Interpreter>,
         arguments: Vec<BindableValue>,
+        _: &Token,
     ) -> Result<BindableValue, RuntimeError> {
         let interpreter = interpreter.unwrap();
         let mut environment = Environment {
-            enclosing: Some(interpreter.environment.clone().unwrap()),
+            enclosing: Some(self.closure.clone()),
             values: HashMap::new(),
         };
 
@@ -31,9 +49,14 @@ impl LoxCallable for LoxFunction {
             environment.define(param.lexeme.clone(), arguments.get(idx).unwrap().clone());
         }
 
-        return Ok(interpreter
-            .execute_block(self.declaration.body.clone(), environment)?
-            .unwrap());
+        match interpreter.execute_block(self.declaration.body.clone(), environment) {
+            Ok(_) => Ok(BindableValue::Literal(LiteralType::Nil)),
+            Err(RuntimeError {
+                kind: ErrorKind::Return(value),
+                ..
+            }) => Ok(value),
+            Err(error) => Err(error),
+        }
     }
     fn to_string(&self) -> String {
         format!("<fn {}>", self.declaration.name.lexeme)
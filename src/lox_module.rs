@@ -0,0 +1,57 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{ErrorKind, RuntimeError},
+    token_type::Token,
+};
+
+#[derive(Clone, Debug)]
+pub struct LoxModule {
+    pub path: PathBuf,
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+impl LoxModule {
+    pub fn get(&self, name: &Token) -> Result<BindableValue, RuntimeError> {
+        match self.environment.borrow().values.get(&name.lexeme) {
+            Some(value) => Ok(value.clone()),
+            None => Err(RuntimeError {
+                token: name.clone(),
+                message: format!(
+                    "Module '{}' has no member '{}'.",
+                    self.path.display(),
+                    name.lexeme
+                ),
+                kind: ErrorKind::Error,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for LoxModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<module {}>", self.path.display())
+    }
+}
+
+/// Looks `path` up next to the importing file first and then in each
+/// directory of the search path, returning the canonical path of the
+/// first match.
+pub fn resolve(path: &str, importer: Option<&Path>, search_path: &[PathBuf]) -> Option<PathBuf> {
+    let base = importer
+        .and_then(|importer| importer.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    std::iter::once(base)
+        .chain(search_path.iter().cloned())
+        .map(|directory| directory.join(path))
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| candidate.canonicalize().ok())
+}
@@ -0,0 +1,313 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{ErrorKind, Interpreter, RuntimeError},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+pub mod io;
+pub mod json;
+pub mod list;
+pub mod map;
+pub mod math;
+pub mod process;
+pub mod reflect;
+pub mod string;
+pub mod testing;
+pub mod time;
+
+use io::IoFunction;
+use json::JsonFunction;
+use list::ListFunction;
+use map::MapFunction;
+use math::MathFunction;
+use process::ProcessFunction;
+use reflect::ReflectFunction;
+use string::StringFunction;
+use testing::TestingFunction;
+use time::TimeFunction;
+
+pub fn define_globals(environment: &mut Environment) {
+    environment.define(
+        "clock".to_string(),
+        BindableValue::NativeFunction(NativeFunction::Clock(Clock)),
+    );
+    environment.define(
+        "errorMessage".to_string(),
+        BindableValue::NativeFunction(NativeFunction::ErrorMessage(ErrorMessage)),
+    );
+    environment.define(
+        "errorLine".to_string(),
+        BindableValue::NativeFunction(NativeFunction::ErrorLine(ErrorLine)),
+    );
+    math::define_globals(environment);
+    string::define_globals(environment);
+    list::define_globals(environment);
+    io::define_globals(environment);
+    process::define_globals(environment);
+    time::define_globals(environment);
+    map::define_globals(environment);
+    json::define_globals(environment);
+    reflect::define_globals(environment);
+    testing::define_globals(environment);
+}
+
+#[derive(Debug, Clone)]
+pub enum NativeFunction {
+    Clock(Clock),
+    ErrorMessage(ErrorMessage),
+    ErrorLine(ErrorLine),
+    Math(MathFunction),
+    String(StringFunction),
+    List(ListFunction),
+    Io(IoFunction),
+    Process(ProcessFunction),
+    Time(TimeFunction),
+    Map(MapFunction),
+    Json(JsonFunction),
+    Reflect(ReflectFunction),
+    Testing(TestingFunction),
+}
+
+impl NativeFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NativeFunction::Clock(_) => "clock",
+            NativeFunction::ErrorMessage(_) => "errorMessage",
+            NativeFunction::ErrorLine(_) => "errorLine",
+            NativeFunction::Math(function) => function.name(),
+            NativeFunction::String(function) => function.name(),
+            NativeFunction::List(function) => function.name(),
+            NativeFunction::Io(function) => function.name(),
+            NativeFunction::Process(function) => function.name(),
+            NativeFunction::Time(function) => function.name(),
+            NativeFunction::Map(function) => function.name(),
+            NativeFunction::Json(function) => function.name(),
+            NativeFunction::Reflect(function) => function.name(),
+            NativeFunction::Testing(function) => function.name(),
+        }
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        match self {
+            NativeFunction::Clock(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::ErrorMessage(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::ErrorLine(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Math(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::String(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::List(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Io(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Process(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Time(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Map(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Json(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Reflect(function) => function.call(interpreter, arguments, paren),
+            NativeFunction::Testing(function) => function.call(interpreter, arguments, paren),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            NativeFunction::Clock(function) => function.arity(),
+            NativeFunction::ErrorMessage(function) => function.arity(),
+            NativeFunction::ErrorLine(function) => function.arity(),
+            NativeFunction::Math(function) => function.arity(),
+            NativeFunction::String(function) => function.arity(),
+            NativeFunction::List(function) => function.arity(),
+            NativeFunction::Io(function) => function.arity(),
+            NativeFunction::Process(function) => function.arity(),
+            NativeFunction::Time(function) => function.arity(),
+            NativeFunction::Map(function) => function.arity(),
+            NativeFunction::Json(function) => function.arity(),
+            NativeFunction::Reflect(function) => function.arity(),
+            NativeFunction::Testing(function) => function.arity(),
+        }
+    }
+
+    fn min_arity(&self) -> usize {
+        match self {
+            NativeFunction::Json(function) => function.min_arity(),
+            NativeFunction::Testing(function) => function.min_arity(),
+            _ => self.arity(),
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+/// Builds the error a native reports when it is handed an argument it
+/// cannot work with, pointing at the closing paren of the call.
+pub fn argument_error(paren: &Token, name: &str, message: &str) -> RuntimeError {
+    RuntimeError {
+        token: paren.clone(),
+        message: format!("{}() {}", name, message),
+        kind: ErrorKind::Error,
+    }
+}
+
+pub fn number_argument(
+    arguments: &[BindableValue],
+    index: usize,
+    paren: &Token,
+    name: &str,
+) -> Result<f64, RuntimeError> {
+    match &arguments[index] {
+        BindableValue::Literal(LiteralType::F64(value)) => Ok(*value),
+        _ => Err(argument_error(
+            paren,
+            name,
+            &format!("expects argument {} to be a number.", index + 1),
+        )),
+    }
+}
+
+pub fn integer_argument(
+    arguments: &[BindableValue],
+    index: usize,
+    paren: &Token,
+    name: &str,
+) -> Result<usize, RuntimeError> {
+    match &arguments[index] {
+        BindableValue::Literal(LiteralType::F64(value)) if *value >= 0.0 && value.fract() == 0.0 => {
+            Ok(*value as usize)
+        }
+        _ => Err(argument_error(
+            paren,
+            name,
+            &format!("expects argument {} to be a non-negative integer.", index + 1),
+        )),
+    }
+}
+
+pub fn string_argument(
+    arguments: &[BindableValue],
+    index: usize,
+    paren: &Token,
+    name: &str,
+) -> Result<String, RuntimeError> {
+    match &arguments[index] {
+        BindableValue::Literal(LiteralType::String(value)) => Ok(value.clone()),
+        _ => Err(argument_error(
+            paren,
+            name,
+            &format!("expects argument {} to be a string.", index + 1),
+        )),
+    }
+}
+
+pub fn list_argument(
+    arguments: &[BindableValue],
+    index: usize,
+    paren: &Token,
+    name: &str,
+) -> Result<Rc<RefCell<Vec<BindableValue>>>, RuntimeError> {
+    match &arguments[index] {
+        BindableValue::List(list) => Ok(list.clone()),
+        _ => Err(argument_error(
+            paren,
+            name,
+            &format!("expects argument {} to be a list.", index + 1),
+        )),
+    }
+}
+
+pub fn new_list(items: Vec<BindableValue>) -> BindableValue {
+    BindableValue::List(Rc::new(RefCell::new(items)))
+}
+
+#[derive(Debug, Clone)]
+pub struct Clock;
+
+impl LoxCallable for Clock {
+    fn call(
+        &self,
+        _: Option<&mut Interpreter>,
+        _: Vec<BindableValue>,
+        _: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let now = SystemTime::now();
+        let time_elapsed = now
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs_f64();
+
+        Ok(BindableValue::Literal(LiteralType::F64(time_elapsed)))
+    }
+
+    fn arity(&self) -> usize {
+        0
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorMessage;
+
+impl LoxCallable for ErrorMessage {
+    fn call(
+        &self,
+        _: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        _: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        match &arguments[0] {
+            BindableValue::Error(error) => {
+                Ok(BindableValue::Literal(LiteralType::String(error.message.clone())))
+            }
+            _ => Ok(BindableValue::Literal(LiteralType::Nil)),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorLine;
+
+impl LoxCallable for ErrorLine {
+    fn call(
+        &self,
+        _: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        _: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        match &arguments[0] {
+            BindableValue::Error(error) => {
+                Ok(BindableValue::Literal(LiteralType::F64(error.line as f64)))
+            }
+            _ => Ok(BindableValue::Literal(LiteralType::Nil)),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{ErrorKind, Interpreter, RuntimeError},
+    lox_std::{new_list, string_argument, NativeFunction},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [IoFunction; 7] = [
+    IoFunction::ReadLine,
+    IoFunction::ReadFile,
+    IoFunction::WriteFile,
+    IoFunction::AppendFile,
+    IoFunction::FileExists,
+    IoFunction::ListDir,
+    IoFunction::Eprint,
+];
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Io(function)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum IoFunction {
+    ReadLine,
+    ReadFile,
+    WriteFile,
+    AppendFile,
+    FileExists,
+    ListDir,
+    Eprint,
+}
+
+impl IoFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IoFunction::ReadLine => "readLine",
+            IoFunction::ReadFile => "readFile",
+            IoFunction::WriteFile => "writeFile",
+            IoFunction::AppendFile => "appendFile",
+            IoFunction::FileExists => "fileExists",
+            IoFunction::ListDir => "listDir",
+            IoFunction::Eprint => "eprint",
+        }
+    }
+}
+
+impl LoxCallable for IoFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+        let string = |index: usize| string_argument(&arguments, index, paren, name);
+        let io_error = |path: &str, error: io::Error| RuntimeError {
+            token: paren.clone(),
+            message: format!("{}() failed for '{}': {}.", name, path, error),
+            kind: ErrorKind::Error,
+        };
+
+        let result = match self {
+            // Waiting for input past the time limit stops the script when it
+            // runs out.
+            IoFunction::ReadLine => {
+                let interpreter = interpreter.unwrap();
+                let line = match read_line(interpreter.remaining_time()) {
+                    Some(line) => line.map_err(|error| io_error("stdin", error))?,
+                    None => return Err(interpreter.timeout_error(paren.line)),
+                };
+                match line {
+                    None => LiteralType::Nil,
+                    Some(line) => {
+                        let trimmed = line.trim_end_matches(['\n', '\r']);
+                        LiteralType::String(trimmed.to_string())
+                    }
+                }
+            }
+            IoFunction::ReadFile => {
+                let path = string(0)?;
+                let size = fs::metadata(&path).map_err(|error| io_error(&path, error))?.len();
+                let size = usize::try_from(size).unwrap_or(usize::MAX);
+                interpreter.unwrap().check_allocation("string", size, paren.line)?;
+                let contents = fs::read_to_string(&path).map_err(|error| io_error(&path, error))?;
+                LiteralType::String(contents)
+            }
+            IoFunction::WriteFile => {
+                let path = string(0)?;
+                fs::write(&path, string(1)?).map_err(|error| io_error(&path, error))?;
+                LiteralType::Nil
+            }
+            IoFunction::AppendFile => {
+                let path = string(0)?;
+                let contents = string(1)?;
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(contents.as_bytes()))
+                    .map_err(|error| io_error(&path, error))?;
+                LiteralType::Nil
+            }
+            IoFunction::FileExists => LiteralType::Bool(Path::new(&string(0)?).exists()),
+            IoFunction::ListDir => {
+                let path = string(0)?;
+                let mut names: Vec<String> = fs::read_dir(&path)
+                    .and_then(|entries| {
+                        entries
+                            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+                            .collect()
+                    })
+                    .map_err(|error| io_error(&path, error))?;
+                names.sort();
+
+                return Ok(new_list(
+                    names
+                        .into_iter()
+                        .map(|name| BindableValue::Literal(LiteralType::String(name)))
+                        .collect(),
+                ));
+            }
+            IoFunction::Eprint => {
+                eprintln!("{}", Interpreter::stringify(&arguments[0]));
+                LiteralType::Nil
+            }
+        };
+
+        Ok(BindableValue::Literal(result))
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            IoFunction::ReadLine => 0,
+            IoFunction::WriteFile | IoFunction::AppendFile => 2,
+            _ => 1,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+// Reads a line from stdin, or `None` at the end of input. The outer `None`
+// means `limit` passed first.
+fn read_line(limit: Option<Duration>) -> Option<io::Result<Option<String>>> {
+    if limit.is_none() && STDIN_LINES.get().is_none() {
+        return Some(stdin_line());
+    }
+    // A line that arrives too late stays queued for the next call.
+    let lines = STDIN_LINES
+        .get_or_init(read_stdin_lines)
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    let line = match limit {
+        Some(limit) => lines.recv_timeout(limit),
+        None => lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    match line {
+        Ok(line) => Some(line),
+        Err(RecvTimeoutError::Timeout) => None,
+        Err(RecvTimeoutError::Disconnected) => Some(Ok(None)),
+    }
+}
+
+fn stdin_line() -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = io::stdin().read_line(&mut line)?;
+    Ok((read > 0).then_some(line))
+}
+
+type Lines = Receiver<io::Result<Option<String>>>;
+
+// Std has no way to wait on stdin for a while, so once a read has a time
+// limit, lines are read on a thread of their own and handed over.
+static STDIN_LINES: OnceLock<Mutex<Lines>> = OnceLock::new();
+
+fn read_stdin_lines() -> Mutex<Lines> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        let line = stdin_line();
+        let last = !matches!(line, Ok(Some(_)));
+        if sender.send(line).is_err() || last {
+            break;
+        }
+    });
+    Mutex::new(receiver)
+}
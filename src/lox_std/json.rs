@@ -0,0 +1,172 @@
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{Interpreter, RuntimeError},
+    json::JsonValue,
+    lox_std::{
+        argument_error,
+        map::{new_map, LoxMap},
+        new_list, string_argument, NativeFunction,
+    },
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [JsonFunction; 2] = [JsonFunction::Parse, JsonFunction::Stringify];
+
+const MAX_INDENT: usize = 10;
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Json(function)),
+        );
+    }
+}
+
+/// Converts between JSON text and Lox values: arrays become lists,
+/// objects become maps and null becomes nil.
+#[derive(Debug, Clone, Copy)]
+pub enum JsonFunction {
+    Parse,
+    Stringify,
+}
+
+impl JsonFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JsonFunction::Parse => "jsonParse",
+            JsonFunction::Stringify => "jsonStringify",
+        }
+    }
+}
+
+impl LoxCallable for JsonFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+
+        match self {
+            JsonFunction::Parse => {
+                let text = string_argument(&arguments, 0, paren, name)?;
+                JsonValue::parse(&text)
+                    .map(from_json)
+                    .map_err(|message| argument_error(paren, name, &message))
+            }
+            // The optional indent is either a number of spaces or the string
+            // to indent with; without one the output is compact. As in
+            // JavaScript, only the first 10 spaces or characters are used.
+            JsonFunction::Stringify => {
+                let indent = match arguments.get(1) {
+                    None | Some(BindableValue::Literal(LiteralType::Nil)) => None,
+                    Some(BindableValue::Literal(LiteralType::F64(spaces)))
+                        if *spaces >= 0.0 && spaces.fract() == 0.0 =>
+                    {
+                        Some(" ".repeat((*spaces as usize).min(MAX_INDENT)))
+                    }
+                    Some(BindableValue::Literal(LiteralType::String(indent))) => {
+                        Some(indent.chars().take(MAX_INDENT).collect())
+                    }
+                    Some(_) => {
+                        return Err(argument_error(
+                            paren,
+                            name,
+                            "expects the indent to be a non-negative integer or a string.",
+                        ))
+                    }
+                };
+
+                let json = to_json(&arguments[0], &mut Vec::new())
+                    .map_err(|message| argument_error(paren, name, &message))?;
+                let size = json
+                    .written_len(indent.as_deref())
+                    .map_err(|message| argument_error(paren, name, &message))?;
+                interpreter.unwrap().check_allocation("string", size, paren.line)?;
+                let text = json
+                    .write(indent.as_deref())
+                    .map_err(|message| argument_error(paren, name, &message))?;
+                Ok(BindableValue::Literal(LiteralType::String(text)))
+            }
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            JsonFunction::Parse => 1,
+            JsonFunction::Stringify => 2,
+        }
+    }
+
+    fn min_arity(&self) -> usize {
+        1
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+fn from_json(json: JsonValue) -> BindableValue {
+    match json {
+        JsonValue::Null => BindableValue::Literal(LiteralType::Nil),
+        JsonValue::Bool(value) => BindableValue::Literal(LiteralType::Bool(value)),
+        JsonValue::Number(value) => BindableValue::Literal(LiteralType::F64(value)),
+        JsonValue::String(value) => BindableValue::Literal(LiteralType::String(value)),
+        JsonValue::Array(items) => new_list(items.into_iter().map(from_json).collect()),
+        JsonValue::Object(members) => new_map(
+            members
+                .into_iter()
+                .map(|(key, value)| (key, from_json(value)))
+                .collect::<LoxMap>(),
+        ),
+    }
+}
+
+// `visiting` holds the lists and maps being converted, to reject cycles.
+fn to_json(value: &BindableValue, visiting: &mut Vec<*const ()>) -> Result<JsonValue, String> {
+    let json = match value {
+        BindableValue::Literal(LiteralType::Nil) => JsonValue::Null,
+        BindableValue::Literal(LiteralType::Bool(value)) => JsonValue::Bool(*value),
+        BindableValue::Literal(LiteralType::F64(value)) => JsonValue::Number(*value),
+        BindableValue::Literal(LiteralType::String(value)) => JsonValue::String(value.clone()),
+        BindableValue::List(list) => {
+            enter(visiting, list.as_ptr() as *const ())?;
+            let items = list
+                .borrow()
+                .iter()
+                .map(|item| to_json(item, visiting))
+                .collect::<Result<_, _>>()?;
+            visiting.pop();
+            JsonValue::Array(items)
+        }
+        BindableValue::Map(map) => {
+            enter(visiting, map.as_ptr() as *const ())?;
+            let members = map
+                .borrow()
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), to_json(item, visiting)?)))
+                .collect::<Result<_, String>>()?;
+            visiting.pop();
+            JsonValue::Object(members)
+        }
+        _ => {
+            return Err(format!(
+                "cannot encode the value {}.",
+                Interpreter::stringify(value)
+            ))
+        }
+    };
+    Ok(json)
+}
+
+fn enter(visiting: &mut Vec<*const ()>, pointer: *const ()) -> Result<(), String> {
+    if visiting.contains(&pointer) {
+        return Err("cannot encode a value that contains itself.".to_string());
+    }
+    visiting.push(pointer);
+    Ok(())
+}
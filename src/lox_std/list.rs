@@ -0,0 +1,91 @@
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{Interpreter, RuntimeError},
+    lox_std::{
+        argument_error, integer_argument, list_argument, new_list, string_argument, NativeFunction,
+    },
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [ListFunction; 3] = [ListFunction::List, ListFunction::Push, ListFunction::Get];
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::List(function)),
+        );
+    }
+}
+
+/// The few natives needed to build and read the lists returned by
+/// `split` and friends; `len` lives with the string natives. `get` also
+/// looks keys up in maps, yielding nil for missing ones.
+#[derive(Debug, Clone, Copy)]
+pub enum ListFunction {
+    List,
+    Push,
+    Get,
+}
+
+impl ListFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListFunction::List => "list",
+            ListFunction::Push => "push",
+            ListFunction::Get => "get",
+        }
+    }
+}
+
+impl LoxCallable for ListFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+
+        match self {
+            ListFunction::List => Ok(new_list(Vec::new())),
+            ListFunction::Push => {
+                let list = list_argument(&arguments, 0, paren, name)?;
+                let size = list.borrow().len() + 1;
+                interpreter.unwrap().check_growth("list", size, 1, paren.line)?;
+                list.borrow_mut().push(arguments[1].clone());
+                Ok(BindableValue::List(list))
+            }
+            ListFunction::Get => {
+                if let BindableValue::Map(map) = &arguments[0] {
+                    let key = string_argument(&arguments, 1, paren, name)?;
+                    let value = map.borrow().get(&key).cloned();
+                    return Ok(value.unwrap_or(BindableValue::Literal(LiteralType::Nil)));
+                }
+                let list = list_argument(&arguments, 0, paren, name)?;
+                let index = integer_argument(&arguments, 1, paren, name)?;
+                let item = list.borrow().get(index).cloned();
+
+                item.ok_or_else(|| {
+                    argument_error(
+                        paren,
+                        name,
+                        &format!("index {} is out of bounds.", index),
+                    )
+                })
+            }
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            ListFunction::List => 0,
+            _ => 2,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
@@ -0,0 +1,166 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{Interpreter, RuntimeError},
+    lox_std::{argument_error, new_list, string_argument, NativeFunction},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [MapFunction; 3] = [MapFunction::Map, MapFunction::Set, MapFunction::Keys];
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Map(function)),
+        );
+    }
+}
+
+pub fn new_map(entries: LoxMap) -> BindableValue {
+    BindableValue::Map(Rc::new(RefCell::new(entries)))
+}
+
+/// A map's entries in the order their keys were first set. That is the
+/// order they print in, `keys()` lists and `jsonStringify` writes, so a
+/// parsed JSON object is written back with its members where they were.
+#[derive(Debug, Clone, Default)]
+pub struct LoxMap {
+    entries: Vec<(String, BindableValue)>,
+    // Each key's position in `entries`.
+    index: HashMap<String, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> LoxMap {
+        LoxMap::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BindableValue> {
+        self.index.get(key).map(|&position| &self.entries[position].1)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Sets `key` to `value`. A key that is already set keeps its place.
+    pub fn insert(&mut self, key: String, value: BindableValue) {
+        match self.index.get(&key) {
+            Some(&position) => self.entries[position].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &BindableValue)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+}
+
+impl FromIterator<(String, BindableValue)> for LoxMap {
+    fn from_iter<I: IntoIterator<Item = (String, BindableValue)>>(entries: I) -> LoxMap {
+        let mut map = LoxMap::new();
+        for (key, value) in entries {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+pub fn map_argument(
+    arguments: &[BindableValue],
+    index: usize,
+    paren: &Token,
+    name: &str,
+) -> Result<Rc<RefCell<LoxMap>>, RuntimeError> {
+    match &arguments[index] {
+        BindableValue::Map(map) => Ok(map.clone()),
+        _ => Err(argument_error(
+            paren,
+            name,
+            &format!("expects argument {} to be a map.", index + 1),
+        )),
+    }
+}
+
+/// String-keyed maps, iterated in insertion order. `get` and `len` from the
+/// list and string natives also accept maps.
+#[derive(Debug, Clone, Copy)]
+pub enum MapFunction {
+    Map,
+    Set,
+    Keys,
+}
+
+impl MapFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapFunction::Map => "map",
+            MapFunction::Set => "set",
+            MapFunction::Keys => "keys",
+        }
+    }
+}
+
+impl LoxCallable for MapFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+
+        match self {
+            MapFunction::Map => Ok(new_map(LoxMap::new())),
+            MapFunction::Set => {
+                let map = map_argument(&arguments, 0, paren, name)?;
+                let key = string_argument(&arguments, 1, paren, name)?;
+                if !map.borrow().contains_key(&key) {
+                    let size = map.borrow().len() + 1;
+                    interpreter.unwrap().check_growth("map", size, 1, paren.line)?;
+                }
+                map.borrow_mut().insert(key, arguments[2].clone());
+                Ok(BindableValue::Map(map))
+            }
+            MapFunction::Keys => {
+                let map = map_argument(&arguments, 0, paren, name)?;
+                let keys = map
+                    .borrow()
+                    .keys()
+                    .map(|key| BindableValue::Literal(LiteralType::String(key.clone())))
+                    .collect();
+                Ok(new_list(keys))
+            }
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            MapFunction::Map => 0,
+            MapFunction::Set => 3,
+            MapFunction::Keys => 1,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
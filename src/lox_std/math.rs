@@ -0,0 +1,222 @@
+use std::{
+    f64::consts,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{Interpreter, RuntimeError},
+    lox_std::{argument_error, number_argument, NativeFunction},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [MathFunction; 22] = [
+    MathFunction::Sqrt,
+    MathFunction::Pow,
+    MathFunction::Floor,
+    MathFunction::Ceil,
+    MathFunction::Round,
+    MathFunction::Abs,
+    MathFunction::Min,
+    MathFunction::Max,
+    MathFunction::Sin,
+    MathFunction::Cos,
+    MathFunction::Tan,
+    MathFunction::Asin,
+    MathFunction::Acos,
+    MathFunction::Atan,
+    MathFunction::Atan2,
+    MathFunction::Exp,
+    MathFunction::Log,
+    MathFunction::Log2,
+    MathFunction::Log10,
+    MathFunction::Random,
+    MathFunction::RandomInt,
+    MathFunction::Seed,
+];
+
+// 2^53: up to here every integer is exactly a number.
+const EXACT_INTEGERS: f64 = 9_007_199_254_740_992.0;
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Math(function)),
+        );
+    }
+
+    for (name, value) in [
+        ("PI", consts::PI),
+        ("E", consts::E),
+        ("INF", f64::INFINITY),
+        ("NAN", f64::NAN),
+    ] {
+        environment.define(
+            name.to_string(),
+            BindableValue::Literal(LiteralType::F64(value)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MathFunction {
+    Sqrt,
+    Pow,
+    Floor,
+    Ceil,
+    Round,
+    Abs,
+    Min,
+    Max,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Exp,
+    Log,
+    Log2,
+    Log10,
+    Random,
+    RandomInt,
+    Seed,
+}
+
+impl MathFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MathFunction::Sqrt => "sqrt",
+            MathFunction::Pow => "pow",
+            MathFunction::Floor => "floor",
+            MathFunction::Ceil => "ceil",
+            MathFunction::Round => "round",
+            MathFunction::Abs => "abs",
+            MathFunction::Min => "min",
+            MathFunction::Max => "max",
+            MathFunction::Sin => "sin",
+            MathFunction::Cos => "cos",
+            MathFunction::Tan => "tan",
+            MathFunction::Asin => "asin",
+            MathFunction::Acos => "acos",
+            MathFunction::Atan => "atan",
+            MathFunction::Atan2 => "atan2",
+            MathFunction::Exp => "exp",
+            MathFunction::Log => "log",
+            MathFunction::Log2 => "log2",
+            MathFunction::Log10 => "log10",
+            MathFunction::Random => "random",
+            MathFunction::RandomInt => "randomInt",
+            MathFunction::Seed => "seed",
+        }
+    }
+}
+
+impl LoxCallable for MathFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+        let number = |index: usize| number_argument(&arguments, index, paren, name);
+
+        let result = match self {
+            MathFunction::Sqrt => number(0)?.sqrt(),
+            MathFunction::Pow => number(0)?.powf(number(1)?),
+            MathFunction::Floor => number(0)?.floor(),
+            MathFunction::Ceil => number(0)?.ceil(),
+            MathFunction::Round => number(0)?.round(),
+            MathFunction::Abs => number(0)?.abs(),
+            MathFunction::Min => number(0)?.min(number(1)?),
+            MathFunction::Max => number(0)?.max(number(1)?),
+            MathFunction::Sin => number(0)?.sin(),
+            MathFunction::Cos => number(0)?.cos(),
+            MathFunction::Tan => number(0)?.tan(),
+            MathFunction::Asin => number(0)?.asin(),
+            MathFunction::Acos => number(0)?.acos(),
+            MathFunction::Atan => number(0)?.atan(),
+            MathFunction::Atan2 => number(0)?.atan2(number(1)?),
+            MathFunction::Exp => number(0)?.exp(),
+            MathFunction::Log => number(0)?.ln(),
+            MathFunction::Log2 => number(0)?.log2(),
+            MathFunction::Log10 => number(0)?.log10(),
+            MathFunction::Random => interpreter.unwrap().random.next_f64(),
+            MathFunction::RandomInt => {
+                let (lo, hi) = (number(0)?, number(1)?);
+                if lo.fract() != 0.0 || hi.fract() != 0.0 || lo > hi {
+                    return Err(argument_error(
+                        paren,
+                        name,
+                        "expects two integers with lo <= hi.",
+                    ));
+                }
+                if hi - lo >= EXACT_INTEGERS {
+                    return Err(argument_error(
+                        paren,
+                        name,
+                        "expects a range of at most 2^53 integers.",
+                    ));
+                }
+                let span = (hi - lo) as u64 + 1;
+                lo + (interpreter.unwrap().random.next_u64() % span) as f64
+            }
+            MathFunction::Seed => {
+                let seed = number(0)?;
+                interpreter.unwrap().random = Random::new(seed.to_bits());
+                return Ok(BindableValue::Literal(LiteralType::Nil));
+            }
+        };
+
+        Ok(BindableValue::Literal(LiteralType::F64(result)))
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            MathFunction::Random => 0,
+            MathFunction::Pow
+            | MathFunction::Min
+            | MathFunction::Max
+            | MathFunction::Atan2
+            | MathFunction::RandomInt => 2,
+            _ => 1,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+/// SplitMix64 generator backing `random`, `randomInt` and `seed`. The
+/// same seed always yields the same sequence.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+    pub fn from_time() -> Random {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards");
+        Random::new(now.as_nanos() as u64)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
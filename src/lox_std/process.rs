@@ -0,0 +1,185 @@
+use std::{
+    env,
+    io::{self, Read},
+    process::{Command, Output, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{ErrorKind, Interpreter, RuntimeError},
+    lox_std::{argument_error, list_argument, new_list, number_argument, string_argument, NativeFunction},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [ProcessFunction; 4] = [
+    ProcessFunction::Args,
+    ProcessFunction::Getenv,
+    ProcessFunction::Exit,
+    ProcessFunction::Run,
+];
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Process(function)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ProcessFunction {
+    Args,
+    Getenv,
+    Exit,
+    Run,
+}
+
+impl ProcessFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcessFunction::Args => "args",
+            ProcessFunction::Getenv => "getenv",
+            ProcessFunction::Exit => "exit",
+            ProcessFunction::Run => "run",
+        }
+    }
+}
+
+impl LoxCallable for ProcessFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+
+        match self {
+            ProcessFunction::Args => Ok(strings(&interpreter.unwrap().script_args)),
+            ProcessFunction::Getenv => {
+                let variable = string_argument(&arguments, 0, paren, name)?;
+                Ok(BindableValue::Literal(match env::var(variable) {
+                    Ok(value) => LiteralType::String(value),
+                    Err(_) => LiteralType::Nil,
+                }))
+            }
+            ProcessFunction::Exit => {
+                let code = number_argument(&arguments, 0, paren, name)?;
+                if code.fract() != 0.0 {
+                    return Err(argument_error(paren, name, "expects an integer exit code."));
+                }
+                Err(RuntimeError {
+                    token: paren.clone(),
+                    message: format!("Exited with code {}.", code),
+                    kind: ErrorKind::Exit(code as i32),
+                })
+            }
+            // Returns [exit code, stdout, stderr]; the exit code is nil when
+            // the child was killed by a signal.
+            ProcessFunction::Run => {
+                let interpreter = interpreter.unwrap();
+                let command = string_argument(&arguments, 0, paren, name)?;
+                let command_arguments: Vec<String> = list_argument(&arguments, 1, paren, name)?
+                    .borrow()
+                    .iter()
+                    .map(Interpreter::stringify)
+                    .collect();
+
+                let mut child = Command::new(&command);
+                child.args(&command_arguments);
+                let output = match interpreter.remaining_time() {
+                    Some(remaining) => output_within(child, remaining),
+                    None => child.output().map(Some),
+                }
+                .map_err(|error| RuntimeError {
+                    token: paren.clone(),
+                    message: format!("run() failed for '{}': {}.", command, error),
+                    kind: ErrorKind::Error,
+                })?;
+                let Some(output) = output else {
+                    return Err(interpreter.timeout_error(paren.line));
+                };
+
+                let code = match output.status.code() {
+                    Some(code) => LiteralType::F64(code as f64),
+                    None => LiteralType::Nil,
+                };
+                Ok(new_list(Vec::from([
+                    BindableValue::Literal(code),
+                    BindableValue::Literal(LiteralType::String(
+                        String::from_utf8_lossy(&output.stdout).to_string(),
+                    )),
+                    BindableValue::Literal(LiteralType::String(
+                        String::from_utf8_lossy(&output.stderr).to_string(),
+                    )),
+                ])))
+            }
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            ProcessFunction::Args => 0,
+            ProcessFunction::Run => 2,
+            _ => 1,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+fn strings(values: &[String]) -> BindableValue {
+    new_list(
+        values
+            .iter()
+            .map(|value| BindableValue::Literal(LiteralType::String(value.clone())))
+            .collect(),
+    )
+}
+
+// Like `Command::output`, but kills the child and returns `None` if it is
+// still running after `limit`.
+fn output_within(mut command: Command, limit: Duration) -> io::Result<Option<Output>> {
+    let deadline = Instant::now() + limit;
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Read both pipes while waiting, so a chatty child cannot block on them.
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+fn read_to_end(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
@@ -0,0 +1,123 @@
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{Interpreter, RuntimeError},
+    lox_std::{argument_error, new_list, NativeFunction},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [ReflectFunction; 5] = [
+    ReflectFunction::Type,
+    ReflectFunction::Arity,
+    ReflectFunction::NameOf,
+    ReflectFunction::IsCallable,
+    ReflectFunction::Globals,
+];
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Reflect(function)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ReflectFunction {
+    Type,
+    Arity,
+    NameOf,
+    IsCallable,
+    Globals,
+}
+
+impl ReflectFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReflectFunction::Type => "type",
+            ReflectFunction::Arity => "arity",
+            ReflectFunction::NameOf => "nameOf",
+            ReflectFunction::IsCallable => "isCallable",
+            ReflectFunction::Globals => "globals",
+        }
+    }
+}
+
+impl LoxCallable for ReflectFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+        let not_callable = || argument_error(paren, name, "expects argument 1 to be a function.");
+
+        let result = match self {
+            ReflectFunction::Type => LiteralType::String(type_name(&arguments[0]).to_string()),
+            // Natives with optional parameters report their maximum arity.
+            ReflectFunction::Arity => match &arguments[0] {
+                BindableValue::Function(function) => LiteralType::F64(function.arity() as f64),
+                BindableValue::NativeFunction(function) => {
+                    LiteralType::F64(function.arity() as f64)
+                }
+                _ => return Err(not_callable()),
+            },
+            ReflectFunction::NameOf => match &arguments[0] {
+                BindableValue::Function(function) => {
+                    LiteralType::String(function.declaration.name.lexeme.clone())
+                }
+                BindableValue::NativeFunction(function) => {
+                    LiteralType::String(function.name().to_string())
+                }
+                _ => return Err(not_callable()),
+            },
+            ReflectFunction::IsCallable => LiteralType::Bool(matches!(
+                arguments[0],
+                BindableValue::Function(_) | BindableValue::NativeFunction(_)
+            )),
+            ReflectFunction::Globals => {
+                let globals = interpreter.unwrap().root_environment();
+                let mut names: Vec<String> = globals.borrow().values.keys().cloned().collect();
+                names.sort();
+
+                return Ok(new_list(
+                    names
+                        .into_iter()
+                        .map(|name| BindableValue::Literal(LiteralType::String(name)))
+                        .collect(),
+                ));
+            }
+        };
+
+        Ok(BindableValue::Literal(result))
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            ReflectFunction::Globals => 0,
+            _ => 1,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+/// The name `type()` reports for a value.
+pub fn type_name(value: &BindableValue) -> &'static str {
+    match value {
+        BindableValue::Literal(LiteralType::F64(_)) => "number",
+        BindableValue::Literal(LiteralType::String(_)) => "string",
+        BindableValue::Literal(LiteralType::Bool(_)) => "bool",
+        BindableValue::Literal(LiteralType::Nil) => "nil",
+        BindableValue::Function(_) => "function",
+        BindableValue::NativeFunction(_) => "native",
+        BindableValue::Error(_) => "error",
+        BindableValue::Module(_) => "module",
+        BindableValue::List(_) => "list",
+        BindableValue::Map(_) => "map",
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{Interpreter, RuntimeError},
+    lox_std::{
+        argument_error, integer_argument, list_argument, new_list, string_argument, NativeFunction,
+    },
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [StringFunction; 14] = [
+    StringFunction::Len,
+    StringFunction::Substr,
+    StringFunction::IndexOf,
+    StringFunction::Contains,
+    StringFunction::Split,
+    StringFunction::Join,
+    StringFunction::Trim,
+    StringFunction::Upper,
+    StringFunction::Lower,
+    StringFunction::Replace,
+    StringFunction::StartsWith,
+    StringFunction::Repeat,
+    StringFunction::Str,
+    StringFunction::Num,
+];
+
+// The most `repeat` builds, so a huge count fails as a Lox error rather
+// than aborting the process when the allocation fails.
+const MAX_REPEAT_BYTES: usize = 1 << 30;
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::String(function)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StringFunction {
+    Len,
+    Substr,
+    IndexOf,
+    Contains,
+    Split,
+    Join,
+    Trim,
+    Upper,
+    Lower,
+    Replace,
+    StartsWith,
+    Repeat,
+    Str,
+    Num,
+}
+
+impl StringFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StringFunction::Len => "len",
+            StringFunction::Substr => "substr",
+            StringFunction::IndexOf => "indexOf",
+            StringFunction::Contains => "contains",
+            StringFunction::Split => "split",
+            StringFunction::Join => "join",
+            StringFunction::Trim => "trim",
+            StringFunction::Upper => "upper",
+            StringFunction::Lower => "lower",
+            StringFunction::Replace => "replace",
+            StringFunction::StartsWith => "startsWith",
+            StringFunction::Repeat => "repeat",
+            StringFunction::Str => "str",
+            StringFunction::Num => "num",
+        }
+    }
+}
+
+impl LoxCallable for StringFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+        let string = |index: usize| string_argument(&arguments, index, paren, name);
+        let integer = |index: usize| integer_argument(&arguments, index, paren, name);
+
+        let result = match self {
+            StringFunction::Len => match &arguments[0] {
+                BindableValue::Literal(LiteralType::String(value)) => {
+                    number(value.chars().count())
+                }
+                BindableValue::List(list) => number(list.borrow().len()),
+                BindableValue::Map(map) => number(map.borrow().len()),
+                _ => {
+                    return Err(argument_error(
+                        paren,
+                        name,
+                        "expects argument 1 to be a string, a list or a map.",
+                    ))
+                }
+            },
+            StringFunction::Substr => {
+                let value = string(0)?;
+                let substring: String = value.chars().skip(integer(1)?).take(integer(2)?).collect();
+                text(substring)
+            }
+            StringFunction::IndexOf => {
+                let value = string(0)?;
+                match value.find(&string(1)?) {
+                    Some(offset) => number(value[..offset].chars().count()),
+                    None => LiteralType::F64(-1.0),
+                }
+            }
+            StringFunction::Contains => LiteralType::Bool(string(0)?.contains(&string(1)?)),
+            StringFunction::Split => {
+                let value = string(0)?;
+                let separator = string(1)?;
+                let parts: Vec<BindableValue> = if separator.is_empty() {
+                    value
+                        .chars()
+                        .map(|c| BindableValue::Literal(text(c.to_string())))
+                        .collect()
+                } else {
+                    value
+                        .split(separator.as_str())
+                        .map(|part| BindableValue::Literal(text(part.to_string())))
+                        .collect()
+                };
+                return Ok(new_list(parts));
+            }
+            StringFunction::Join => {
+                let list = list_argument(&arguments, 0, paren, name)?;
+                let parts: Vec<String> = list.borrow().iter().map(Interpreter::stringify).collect();
+                let separator = string(1)?;
+                let size = parts
+                    .iter()
+                    .fold(0usize, |size, part| size.saturating_add(part.len()))
+                    .saturating_add(separator.len().saturating_mul(parts.len().saturating_sub(1)));
+                interpreter.unwrap().check_allocation("string", size, paren.line)?;
+                text(parts.join(&separator))
+            }
+            StringFunction::Trim => text(string(0)?.trim().to_string()),
+            StringFunction::Upper => text(string(0)?.to_uppercase()),
+            StringFunction::Lower => text(string(0)?.to_lowercase()),
+            StringFunction::Replace => {
+                let value = string(0)?;
+                let from = string(1)?;
+                if from.is_empty() {
+                    return Err(argument_error(
+                        paren,
+                        name,
+                        "expects a non-empty string to replace.",
+                    ));
+                }
+                let to = string(2)?;
+                let matches = value.matches(from.as_str()).count();
+                let size = (value.len() - matches * from.len())
+                    .saturating_add(matches.saturating_mul(to.len()));
+                interpreter.unwrap().check_allocation("string", size, paren.line)?;
+                text(value.replace(&from, &to))
+            }
+            StringFunction::StartsWith => LiteralType::Bool(string(0)?.starts_with(&string(1)?)),
+            StringFunction::Repeat => {
+                let value = string(0)?;
+                let count = integer(1)?;
+                let size = value.len().saturating_mul(count);
+                interpreter.unwrap().check_allocation("string", size, paren.line)?;
+                if size > MAX_REPEAT_BYTES {
+                    return Err(argument_error(
+                        paren,
+                        name,
+                        "would build a string of more than 1 GiB.",
+                    ));
+                }
+                text(value.repeat(count))
+            }
+            StringFunction::Str => text(Interpreter::stringify(&arguments[0])),
+            StringFunction::Num => match &arguments[0] {
+                BindableValue::Literal(LiteralType::F64(value)) => LiteralType::F64(*value),
+                BindableValue::Literal(LiteralType::String(value)) => {
+                    match value.trim().parse::<f64>() {
+                        Ok(parsed) => LiteralType::F64(parsed),
+                        Err(_) => LiteralType::Nil,
+                    }
+                }
+                _ => LiteralType::Nil,
+            },
+        };
+
+        Ok(BindableValue::Literal(result))
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            StringFunction::Len
+            | StringFunction::Trim
+            | StringFunction::Upper
+            | StringFunction::Lower
+            | StringFunction::Str
+            | StringFunction::Num => 1,
+            StringFunction::Substr | StringFunction::Replace => 3,
+            _ => 2,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+fn text(value: String) -> LiteralType {
+    LiteralType::String(value)
+}
+
+fn number(value: usize) -> LiteralType {
+    LiteralType::F64(value as f64)
+}
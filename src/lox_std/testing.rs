@@ -0,0 +1,94 @@
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{ErrorKind, Interpreter, RuntimeError},
+    lox_std::{string_argument, NativeFunction},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [TestingFunction; 2] = [TestingFunction::Assert, TestingFunction::AssertEq];
+
+pub fn define_globals(environment: &mut Environment) {
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Testing(function)),
+        );
+    }
+}
+
+/// Assertions for `test` blocks. A failed assertion is an ordinary runtime
+/// error, so it can also be used outside tests.
+#[derive(Debug, Clone, Copy)]
+pub enum TestingFunction {
+    Assert,
+    AssertEq,
+}
+
+impl TestingFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestingFunction::Assert => "assert",
+            TestingFunction::AssertEq => "assertEq",
+        }
+    }
+}
+
+impl LoxCallable for TestingFunction {
+    fn call(
+        &self,
+        _: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+
+        let failure = match self {
+            TestingFunction::Assert => {
+                if Interpreter::is_truthy(&arguments[0]) {
+                    None
+                } else if arguments.len() > 1 {
+                    Some(string_argument(&arguments, 1, paren, name)?)
+                } else {
+                    Some("expected a truthy value.".to_string())
+                }
+            }
+            // Called as assertEq(actual, expected).
+            TestingFunction::AssertEq => {
+                if Interpreter::is_equal(&arguments[0], &arguments[1]) {
+                    None
+                } else {
+                    Some(format!(
+                        "expected {} but got {}.",
+                        Interpreter::stringify(&arguments[1]),
+                        Interpreter::stringify(&arguments[0])
+                    ))
+                }
+            }
+        };
+
+        match failure {
+            None => Ok(BindableValue::Literal(LiteralType::Nil)),
+            Some(message) => Err(RuntimeError {
+                token: paren.clone(),
+                message: format!("Assertion failed: {}", message),
+                kind: ErrorKind::Error,
+            }),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn min_arity(&self) -> usize {
+        match self {
+            TestingFunction::Assert => 1,
+            TestingFunction::AssertEq => 2,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
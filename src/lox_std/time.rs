@@ -0,0 +1,280 @@
+use std::{
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::{Interpreter, RuntimeError},
+    lox_std::{argument_error, new_list, number_argument, string_argument, NativeFunction},
+    token_type::{LiteralType, Token},
+    LoxCallable,
+};
+
+const FUNCTIONS: [TimeFunction; 5] = [
+    TimeFunction::Now,
+    TimeFunction::Sleep,
+    TimeFunction::EpochMillis,
+    TimeFunction::UtcDate,
+    TimeFunction::FormatTime,
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+static MONOTONIC_START: OnceLock<Instant> = OnceLock::new();
+
+pub fn define_globals(environment: &mut Environment) {
+    MONOTONIC_START.get_or_init(Instant::now);
+
+    for function in FUNCTIONS {
+        environment.define(
+            function.name().to_string(),
+            BindableValue::NativeFunction(NativeFunction::Time(function)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TimeFunction {
+    Now,
+    Sleep,
+    EpochMillis,
+    UtcDate,
+    FormatTime,
+}
+
+impl TimeFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeFunction::Now => "now",
+            TimeFunction::Sleep => "sleep",
+            TimeFunction::EpochMillis => "epochMillis",
+            TimeFunction::UtcDate => "utcDate",
+            TimeFunction::FormatTime => "formatTime",
+        }
+    }
+}
+
+impl LoxCallable for TimeFunction {
+    fn call(
+        &self,
+        interpreter: Option<&mut Interpreter>,
+        arguments: Vec<BindableValue>,
+        paren: &Token,
+    ) -> Result<BindableValue, RuntimeError> {
+        let name = self.name();
+        let number = |index: usize| number_argument(&arguments, index, paren, name);
+
+        let result = match self {
+            // Seconds on a monotonic clock; only differences are meaningful.
+            TimeFunction::Now => {
+                let start = MONOTONIC_START.get_or_init(Instant::now);
+                LiteralType::F64(start.elapsed().as_secs_f64())
+            }
+            TimeFunction::Sleep => {
+                let millis = number(0)?;
+                let Ok(duration) = Duration::try_from_secs_f64(millis / 1000.0) else {
+                    return Err(argument_error(
+                        paren,
+                        name,
+                        "expects a non-negative number of milliseconds.",
+                    ));
+                };
+                // Sleeping past the time limit stops the script when it runs out.
+                let interpreter = interpreter.unwrap();
+                match interpreter.remaining_time() {
+                    Some(remaining) if remaining < duration => {
+                        thread::sleep(remaining);
+                        return Err(interpreter.timeout_error(paren.line));
+                    }
+                    _ => thread::sleep(duration),
+                }
+                LiteralType::Nil
+            }
+            TimeFunction::EpochMillis => {
+                let since_epoch = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("Time went backwards");
+                LiteralType::F64(since_epoch.as_millis() as f64)
+            }
+            // Returns [year, month, day, hour, minute, second, millisecond,
+            // weekday] with months counted from 1 and Sunday as weekday 0.
+            TimeFunction::UtcDate => {
+                let date = UtcDate::from_epoch_seconds(finite_time(number(0)?, paren, name)?);
+                return Ok(new_list(
+                    [
+                        date.year,
+                        date.month as i64,
+                        date.day as i64,
+                        date.hour as i64,
+                        date.minute as i64,
+                        date.second as i64,
+                        date.millisecond as i64,
+                        date.weekday as i64,
+                    ]
+                    .iter()
+                    .map(|field| BindableValue::Literal(LiteralType::F64(*field as f64)))
+                    .collect(),
+                ));
+            }
+            TimeFunction::FormatTime => {
+                let seconds = finite_time(number(0)?, paren, name)?;
+                let format = string_argument(&arguments, 1, paren, name)?;
+                let date = UtcDate::from_epoch_seconds(seconds);
+                let formatted = date
+                    .format(&format, seconds)
+                    .map_err(|message| argument_error(paren, name, &message))?;
+                LiteralType::String(formatted)
+            }
+        };
+
+        Ok(BindableValue::Literal(result))
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            TimeFunction::Now | TimeFunction::EpochMillis => 0,
+            TimeFunction::FormatTime => 2,
+            _ => 1,
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+fn finite_time(seconds: f64, paren: &Token, name: &str) -> Result<f64, RuntimeError> {
+    if seconds.is_finite() {
+        Ok(seconds)
+    } else {
+        Err(argument_error(paren, name, "expects a finite time in seconds."))
+    }
+}
+
+/// A UTC calendar date, broken down from seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtcDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+    pub weekday: u32,
+    pub day_of_year: u32,
+}
+
+impl UtcDate {
+    pub fn from_epoch_seconds(seconds: f64) -> UtcDate {
+        let total_millis = (seconds * 1000.0).floor() as i64;
+        let days = total_millis.div_euclid(86_400_000);
+        let millis_of_day = total_millis.rem_euclid(86_400_000);
+
+        let (year, month, day) = civil_from_days(days);
+        let day_of_year = (days - days_from_civil(year, 1, 1)) as u32 + 1;
+
+        UtcDate {
+            year,
+            month,
+            day,
+            hour: (millis_of_day / 3_600_000) as u32,
+            minute: (millis_of_day / 60_000 % 60) as u32,
+            second: (millis_of_day / 1000 % 60) as u32,
+            millisecond: (millis_of_day % 1000) as u32,
+            weekday: (days + 4).rem_euclid(7) as u32,
+            day_of_year,
+        }
+    }
+
+    /// Expands the strftime-style directives %Y %y %m %d %H %M %S %L %j
+    /// %a %A %b %B %s and %%.
+    pub fn format(&self, format: &str, seconds: f64) -> Result<String, String> {
+        let mut formatted = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                formatted.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => formatted.push_str(&self.year.to_string()),
+                Some('y') => formatted.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => formatted.push_str(&format!("{:02}", self.month)),
+                Some('d') => formatted.push_str(&format!("{:02}", self.day)),
+                Some('H') => formatted.push_str(&format!("{:02}", self.hour)),
+                Some('M') => formatted.push_str(&format!("{:02}", self.minute)),
+                Some('S') => formatted.push_str(&format!("{:02}", self.second)),
+                Some('L') => formatted.push_str(&format!("{:03}", self.millisecond)),
+                Some('j') => formatted.push_str(&format!("{:03}", self.day_of_year)),
+                Some('a') => formatted.push_str(&WEEKDAYS[self.weekday as usize][..3]),
+                Some('A') => formatted.push_str(WEEKDAYS[self.weekday as usize]),
+                Some('b') => formatted.push_str(&MONTHS[self.month as usize - 1][..3]),
+                Some('B') => formatted.push_str(MONTHS[self.month as usize - 1]),
+                Some('s') => formatted.push_str(&(seconds.floor() as i64).to_string()),
+                Some('%') => formatted.push('%'),
+                Some(other) => return Err(format!("does not support the directive '%{}'.", other)),
+                None => return Err("expects a directive after a trailing '%'.".to_string()),
+            }
+        }
+        Ok(formatted)
+    }
+}
+
+// Howard Hinnant's days-from-civil algorithms, valid for the whole
+// proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
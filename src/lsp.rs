@@ -0,0 +1,693 @@
+//! The `jlox-lsp` language server: LSP over stdio with a small JSON-RPC
+//! layer of its own.
+//!
+//! Documents are synced in full. On every change the server scans, parses
+//! and resolves the document, publishes scanner and parser errors as
+//! diagnostics, and answers go-to-definition, find-references, hover and
+//! document-symbol requests from the result.
+//!
+//! Names are resolved lexically like the interpreter does: a use sees the
+//! innermost declaration before it, and a name not declared in any local
+//! scope refers to the global of that name, wherever in the file that is
+//! declared. Natives have no declaration and so no definition.
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Read, Write},
+};
+
+use crate::{
+    expr::ExpressionType,
+    json::JsonValue,
+    lox::Lox,
+    parser::Parser,
+    scanner::Scanner,
+    stmt::StmtType,
+    token_type::Token,
+};
+
+const PARSE_ERROR: f64 = -32700.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+const INVALID_PARAMS: f64 = -32602.0;
+
+// The largest message body `read_message` reads, so that a bad
+// Content-Length cannot make it allocate without bound.
+const MAX_MESSAGE_BYTES: usize = 16 << 20;
+
+// LSP's SymbolKind values.
+const MODULE_SYMBOL: f64 = 2.0;
+const FUNCTION_SYMBOL: f64 = 12.0;
+const VARIABLE_SYMBOL: f64 = 13.0;
+
+/// Serves LSP messages from `input` until the client sends `exit`, and
+/// returns the process exit code: 0 after a `shutdown` request, 1 if the
+/// client exits without one or closes the stream.
+pub fn serve(mut input: impl BufRead, output: impl Write) -> i32 {
+    let mut server = Server {
+        documents: HashMap::new(),
+        shut_down: false,
+        output,
+    };
+
+    loop {
+        match read_message(&mut input) {
+            Ok(Some(message)) => {
+                if let Some(code) = server.handle(&message) {
+                    return code;
+                }
+            }
+            Ok(None) => return 1,
+            Err(message) => server.send(JsonValue::object(Vec::from([
+                ("jsonrpc", JsonValue::String("2.0".to_string())),
+                ("id", JsonValue::Null),
+                ("error", error_object(PARSE_ERROR, &message)),
+            ]))),
+        }
+    }
+}
+
+/// Reads one `Content-Length`-framed message. Returns `None` at the end of
+/// the stream. A body over 16 MiB is skipped and reported as an error.
+pub(crate) fn read_message(input: &mut impl BufRead) -> Result<Option<JsonValue>, String> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        match input.read_line(&mut header) {
+            Ok(0) | Err(_) => return Ok(None),
+            Ok(_) => {}
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let Some(length) = length else {
+        return Err("Message has no valid Content-Length header.".to_string());
+    };
+    if length > MAX_MESSAGE_BYTES {
+        let skipped = io::copy(&mut input.take(length as u64), &mut io::sink()).unwrap_or(0);
+        if skipped < length as u64 {
+            return Ok(None);
+        }
+        return Err(format!(
+            "Message of {} bytes is over the limit of {} bytes.",
+            length, MAX_MESSAGE_BYTES
+        ));
+    }
+    let mut body = vec![0; length];
+    if input.read_exact(&mut body).is_err() {
+        return Ok(None);
+    }
+    let body = String::from_utf8(body).map_err(|_| "Message is not UTF-8.".to_string())?;
+    JsonValue::parse(&body).map(Some)
+}
+
+fn error_object(code: f64, message: &str) -> JsonValue {
+    JsonValue::object(Vec::from([
+        ("code", JsonValue::Number(code)),
+        ("message", JsonValue::String(message.to_string())),
+    ]))
+}
+
+struct Server<W: Write> {
+    documents: HashMap<String, Document>,
+    shut_down: bool,
+    output: W,
+}
+
+impl<W: Write> Server<W> {
+    fn send(&mut self, message: JsonValue) {
+        let body = message.write(None).unwrap_or_default();
+        let _ = write!(self.output, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let _ = self.output.flush();
+    }
+
+    /// Handles one message, returning the exit code once the client exits.
+    /// Responses to requests are sent; results of notifications dropped.
+    fn handle(&mut self, message: &JsonValue) -> Option<i32> {
+        let Some(JsonValue::String(method)) = message.get("method") else {
+            return None;
+        };
+        if method == "exit" {
+            return Some(if self.shut_down { 0 } else { 1 });
+        }
+
+        let params = message.get("params").unwrap_or(&JsonValue::Null);
+        let result = self.dispatch(method, params);
+
+        if let Some(id) = message.get("id") {
+            let (key, value) = match result {
+                Ok(result) => ("result", result),
+                Err((code, message)) => ("error", error_object(code, &message)),
+            };
+            self.send(JsonValue::object(Vec::from([
+                ("jsonrpc", JsonValue::String("2.0".to_string())),
+                ("id", id.clone()),
+                (key, value),
+            ])));
+        }
+        None
+    }
+
+    fn dispatch(&mut self, method: &str, params: &JsonValue) -> Result<JsonValue, (f64, String)> {
+        match method {
+            "initialize" => Ok(JsonValue::object(Vec::from([
+                (
+                    "capabilities",
+                    JsonValue::object(Vec::from([
+                        ("textDocumentSync", JsonValue::Number(1.0)),
+                        ("definitionProvider", JsonValue::Bool(true)),
+                        ("referencesProvider", JsonValue::Bool(true)),
+                        ("hoverProvider", JsonValue::Bool(true)),
+                        ("documentSymbolProvider", JsonValue::Bool(true)),
+                    ])),
+                ),
+                (
+                    "serverInfo",
+                    JsonValue::object(Vec::from([("name", JsonValue::String("jlox-lsp".to_string()))])),
+                ),
+            ]))),
+            "initialized" => Ok(JsonValue::Null),
+            "shutdown" => {
+                self.shut_down = true;
+                Ok(JsonValue::Null)
+            }
+            "textDocument/didOpen" => {
+                let uri = string_param(params, &["textDocument", "uri"])?;
+                let text = string_param(params, &["textDocument", "text"])?;
+                self.update(uri, text);
+                Ok(JsonValue::Null)
+            }
+            "textDocument/didChange" => {
+                let uri = string_param(params, &["textDocument", "uri"])?;
+                let Some(JsonValue::Array(changes)) = params.get("contentChanges") else {
+                    return Err(invalid_params("contentChanges"));
+                };
+                // Full sync: the last change holds the whole new text.
+                if let Some(JsonValue::String(text)) = changes.last().and_then(|change| change.get("text")) {
+                    self.update(uri, text.clone());
+                }
+                Ok(JsonValue::Null)
+            }
+            "textDocument/didClose" => {
+                let uri = string_param(params, &["textDocument", "uri"])?;
+                self.documents.remove(&uri);
+                self.publish_diagnostics(&uri, JsonValue::Array(Vec::new()));
+                Ok(JsonValue::Null)
+            }
+            "textDocument/definition" => {
+                let (uri, document, symbol) = self.symbol_at(params)?;
+                Ok(symbol.map_or(JsonValue::Null, |symbol| {
+                    document.location(&uri, &document.analysis.symbols[symbol].name)
+                }))
+            }
+            "textDocument/references" => {
+                let (uri, document, symbol) = self.symbol_at(params)?;
+                let Some(symbol) = symbol else {
+                    return Ok(JsonValue::Array(Vec::new()));
+                };
+                let include_declaration = matches!(
+                    path(params, &["context", "includeDeclaration"]),
+                    Some(JsonValue::Bool(true))
+                );
+
+                let analysis = &document.analysis;
+                let mut locations = Vec::new();
+                if include_declaration {
+                    locations.push(document.location(&uri, &analysis.symbols[symbol].name));
+                }
+                for (token, _) in analysis.references.iter().filter(|(_, target)| *target == symbol) {
+                    locations.push(document.location(&uri, token));
+                }
+                Ok(JsonValue::Array(locations))
+            }
+            "textDocument/hover" => {
+                let (_, document, symbol) = self.symbol_at(params)?;
+                Ok(symbol.map_or(JsonValue::Null, |symbol| {
+                    let signature = document.analysis.symbols[symbol].signature();
+                    JsonValue::object(Vec::from([(
+                        "contents",
+                        JsonValue::object(Vec::from([
+                            ("kind", JsonValue::String("markdown".to_string())),
+                            ("value", JsonValue::String(format!("```lox\n{}\n```", signature))),
+                        ])),
+                    )]))
+                }))
+            }
+            "textDocument/documentSymbol" => {
+                let uri = string_param(params, &["textDocument", "uri"])?;
+                let Some(document) = self.documents.get(&uri) else {
+                    return Ok(JsonValue::Array(Vec::new()));
+                };
+                let symbols = document
+                    .analysis
+                    .symbols
+                    .iter()
+                    .filter(|symbol| symbol.top_level)
+                    .filter(|symbol| matches!(symbol.kind, SymbolKind::Function(_) | SymbolKind::Variable))
+                    .map(|symbol| {
+                        let range = document.range(&symbol.name);
+                        JsonValue::object(Vec::from([
+                            ("name", JsonValue::String(symbol.name.lexeme.clone())),
+                            ("detail", JsonValue::String(symbol.signature())),
+                            ("kind", JsonValue::Number(symbol.kind.lsp_kind())),
+                            ("range", range.clone()),
+                            ("selectionRange", range),
+                        ]))
+                    })
+                    .collect();
+                Ok(JsonValue::Array(symbols))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'.", method))),
+        }
+    }
+
+    fn update(&mut self, uri: String, text: String) {
+        let document = Document::new(text);
+        let diagnostics = document
+            .analysis
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                JsonValue::object(Vec::from([
+                    (
+                        "range",
+                        document.span(diagnostic.line, diagnostic.column, &diagnostic.text),
+                    ),
+                    ("severity", JsonValue::Number(1.0)),
+                    ("source", JsonValue::String("jlox".to_string())),
+                    ("message", JsonValue::String(diagnostic.message.clone())),
+                ]))
+            })
+            .collect();
+        self.documents.insert(uri.clone(), document);
+        self.publish_diagnostics(&uri, JsonValue::Array(diagnostics));
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str, diagnostics: JsonValue) {
+        self.send(JsonValue::object(Vec::from([
+            ("jsonrpc", JsonValue::String("2.0".to_string())),
+            ("method", JsonValue::String("textDocument/publishDiagnostics".to_string())),
+            (
+                "params",
+                JsonValue::object(Vec::from([
+                    ("uri", JsonValue::String(uri.to_string())),
+                    ("diagnostics", diagnostics),
+                ])),
+            ),
+        ])));
+    }
+
+    /// The document a text-document-position request is about and the
+    /// symbol declared or used at that position, if any.
+    fn symbol_at(&self, params: &JsonValue) -> Result<(String, &Document, Option<usize>), (f64, String)> {
+        let uri = string_param(params, &["textDocument", "uri"])?;
+        let (Some(JsonValue::Number(line)), Some(JsonValue::Number(character))) = (
+            path(params, &["position", "line"]),
+            path(params, &["position", "character"]),
+        ) else {
+            return Err(invalid_params("position"));
+        };
+        let Some(document) = self.documents.get(&uri) else {
+            return Err((INVALID_PARAMS, format!("Document '{}' is not open.", uri)));
+        };
+
+        let line = (*line as u32).saturating_add(1);
+        let column = document.column(line, *character as usize);
+        let analysis = &document.analysis;
+        let at = |token: &Token| {
+            token.line == line && token.column <= column && column < token.column + token.lexeme.len() as u32
+        };
+
+        let symbol = analysis
+            .symbols
+            .iter()
+            .position(|symbol| at(&symbol.name))
+            .or_else(|| {
+                analysis
+                    .references
+                    .iter()
+                    .find(|(token, _)| at(token))
+                    .map(|(_, symbol)| *symbol)
+            });
+        Ok((uri, document, symbol))
+    }
+}
+
+fn path<'a>(json: &'a JsonValue, keys: &[&str]) -> Option<&'a JsonValue> {
+    keys.iter().try_fold(json, |json, key| json.get(key))
+}
+
+fn string_param(params: &JsonValue, keys: &[&str]) -> Result<String, (f64, String)> {
+    match path(params, keys) {
+        Some(JsonValue::String(value)) => Ok(value.clone()),
+        _ => Err(invalid_params(&keys.join("."))),
+    }
+}
+
+fn invalid_params(name: &str) -> (f64, String) {
+    (INVALID_PARAMS, format!("Missing or invalid '{}'.", name))
+}
+
+/// An open document and what the server knows about it.
+struct Document {
+    text: String,
+    analysis: Analysis,
+}
+
+impl Document {
+    fn new(text: String) -> Document {
+        let analysis = analyze(&text);
+        Document { text, analysis }
+    }
+
+    fn line_text(&self, line: u32) -> &str {
+        self.text.lines().nth(line as usize - 1).unwrap_or("")
+    }
+
+    // Tokens have 1-based byte columns; LSP wants 0-based UTF-16 ones.
+    fn position(&self, line: u32, column: u32) -> JsonValue {
+        let line_text = self.line_text(line);
+        let prefix = line_text.get(..column.saturating_sub(1) as usize).unwrap_or(line_text);
+        JsonValue::object(Vec::from([
+            ("line", JsonValue::Number(line.saturating_sub(1) as f64)),
+            ("character", JsonValue::Number(prefix.encode_utf16().count() as f64)),
+        ]))
+    }
+
+    /// The 1-based byte column of the 0-based UTF-16 `character` on `line`.
+    fn column(&self, line: u32, character: usize) -> u32 {
+        let mut units = 0;
+        for (offset, c) in self.line_text(line).char_indices() {
+            if units >= character {
+                return offset as u32 + 1;
+            }
+            units += c.len_utf16();
+        }
+        self.line_text(line).len() as u32 + 1
+    }
+
+    /// The range covering `text` from `line` and `column`, cut off at the
+    /// end of the line for multi-line text.
+    fn span(&self, line: u32, column: u32, text: &str) -> JsonValue {
+        let length = text.lines().next().unwrap_or("").len() as u32;
+        JsonValue::object(Vec::from([
+            ("start", self.position(line, column)),
+            ("end", self.position(line, column + length)),
+        ]))
+    }
+
+    fn range(&self, token: &Token) -> JsonValue {
+        self.span(token.line, token.column, &token.lexeme)
+    }
+
+    fn location(&self, uri: &str, token: &Token) -> JsonValue {
+        JsonValue::object(Vec::from([
+            ("uri", JsonValue::String(uri.to_string())),
+            ("range", self.range(token)),
+        ]))
+    }
+}
+
+struct Diagnostic {
+    line: u32,
+    column: u32,
+    text: String,
+    message: String,
+}
+
+enum SymbolKind {
+    /// A function, with its parameters.
+    Function(Vec<Token>),
+    Variable,
+    Parameter,
+    Module,
+}
+
+impl SymbolKind {
+    fn lsp_kind(&self) -> f64 {
+        match self {
+            SymbolKind::Function(_) => FUNCTION_SYMBOL,
+            SymbolKind::Module => MODULE_SYMBOL,
+            SymbolKind::Variable | SymbolKind::Parameter => VARIABLE_SYMBOL,
+        }
+    }
+}
+
+struct Symbol {
+    name: Token,
+    kind: SymbolKind,
+    top_level: bool,
+}
+
+impl Symbol {
+    fn signature(&self) -> String {
+        match &self.kind {
+            SymbolKind::Function(params) => {
+                let params: Vec<&str> = params.iter().map(|param| param.lexeme.as_str()).collect();
+                format!("fun {}({})", self.name.lexeme, params.join(", "))
+            }
+            SymbolKind::Variable => format!("var {}", self.name.lexeme),
+            SymbolKind::Parameter => format!("(parameter) {}", self.name.lexeme),
+            SymbolKind::Module => format!("(module) {}", self.name.lexeme),
+        }
+    }
+}
+
+/// Every declaration in a document, every use of a name resolved to the
+/// index of the symbol it refers to, and the errors found on the way.
+struct Analysis {
+    diagnostics: Vec<Diagnostic>,
+    symbols: Vec<Symbol>,
+    references: Vec<(Token, usize)>,
+}
+
+fn analyze(text: &str) -> Analysis {
+    let mut scanner = Scanner {
+        source: Vec::new(),
+        tokens: Vec::new(),
+        comments: Vec::new(),
+        positions: Vec::new(),
+        errors: Vec::new(),
+        start: 0,
+        current: 0,
+        line: 1,
+    };
+    let tokens = scanner.scan_tokens(text.to_string());
+
+    let mut lox = Lox::default();
+    let mut parser = Parser {
+        tokens: Vec::new(),
+        current: 0,
+    };
+    let statements = parser.parse(tokens, &mut lox);
+
+    let mut diagnostics: Vec<Diagnostic> = scanner
+        .errors
+        .iter()
+        .map(|error| Diagnostic {
+            line: error.line,
+            column: error.column,
+            text: " ".to_string(),
+            message: error.message.clone(),
+        })
+        .collect();
+    diagnostics.extend(lox.compile_errors.iter().map(|error| Diagnostic {
+        line: error.token.line,
+        column: error.token.column,
+        text: error.token.lexeme.clone(),
+        message: error.message.clone(),
+    }));
+
+    let mut resolver = Resolver {
+        scopes: Vec::new(),
+        globals: HashMap::new(),
+        unresolved_globals: Vec::new(),
+        symbols: Vec::new(),
+        references: Vec::new(),
+    };
+    resolver.stmts(&statements);
+    resolver.finish();
+
+    Analysis {
+        diagnostics,
+        symbols: resolver.symbols,
+        references: resolver.references,
+    }
+}
+
+struct Resolver {
+    scopes: Vec<HashMap<String, usize>>,
+    globals: HashMap<String, usize>,
+    // Uses outside any local declaration, resolved once all globals are known.
+    unresolved_globals: Vec<Token>,
+    symbols: Vec<Symbol>,
+    references: Vec<(Token, usize)>,
+}
+
+impl Resolver {
+    fn finish(&mut self) {
+        for token in self.unresolved_globals.drain(..) {
+            if let Some(symbol) = self.globals.get(&token.lexeme) {
+                self.references.push((token, *symbol));
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &Token, kind: SymbolKind) {
+        let index = self.symbols.len();
+        self.symbols.push(Symbol {
+            name: name.clone(),
+            kind,
+            top_level: self.scopes.is_empty(),
+        });
+        match self.scopes.last_mut() {
+            Some(scope) => scope.insert(name.lexeme.clone(), index),
+            None => self.globals.insert(name.lexeme.clone(), index),
+        };
+    }
+
+    fn reference(&mut self, name: &Token) {
+        let local = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.lexeme));
+        match local {
+            Some(symbol) => self.references.push((name.clone(), *symbol)),
+            None => self.unresolved_globals.push(name.clone()),
+        }
+    }
+
+    fn scoped(&mut self, resolve: impl FnOnce(&mut Resolver)) {
+        self.scopes.push(HashMap::new());
+        resolve(self);
+        self.scopes.pop();
+    }
+
+    fn stmts(&mut self, statements: &[StmtType]) {
+        for statement in statements {
+            self.stmt(statement);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &StmtType) {
+        match stmt {
+            StmtType::Expression(stmt) => self.expr(&stmt.expression),
+            StmtType::Print(stmt) => self.expr(&stmt.expression),
+            StmtType::Var(stmt) => {
+                if let Some(initializer) = &stmt.initializer {
+                    self.expr(initializer);
+                }
+                self.declare(&stmt.name, SymbolKind::Variable);
+            }
+            StmtType::Block(stmt) => self.scoped(|resolver| resolver.stmts(&stmt.statements)),
+            StmtType::If(stmt) => {
+                self.expr(&stmt.condition);
+                self.stmt(&stmt.then_branch);
+                if let Some(else_branch) = &stmt.else_branch {
+                    self.stmt(else_branch);
+                }
+            }
+            StmtType::While(stmt) => {
+                self.expr(&stmt.condition);
+                self.stmt(&stmt.body);
+            }
+            StmtType::For(stmt) => self.scoped(|resolver| {
+                if let Some(initializer) = &stmt.initializer {
+                    resolver.stmt(initializer);
+                }
+                if let Some(condition) = &stmt.condition {
+                    resolver.expr(condition);
+                }
+                if let Some(increment) = &stmt.increment {
+                    resolver.expr(increment);
+                }
+                resolver.stmt(&stmt.body);
+            }),
+            StmtType::Function(stmt) => {
+                self.declare(&stmt.name, SymbolKind::Function(stmt.params.clone()));
+                self.scoped(|resolver| {
+                    for param in &stmt.params {
+                        resolver.declare(param, SymbolKind::Parameter);
+                    }
+                    resolver.stmts(&stmt.body);
+                });
+            }
+            StmtType::Return(stmt) => {
+                if let Some(value) = &stmt.value {
+                    self.expr(value);
+                }
+            }
+            StmtType::Throw(stmt) => self.expr(&stmt.value),
+            StmtType::Try(stmt) => {
+                self.scoped(|resolver| resolver.stmts(&stmt.body));
+                if let (Some(name), Some(body)) = (&stmt.catch_name, &stmt.catch_body) {
+                    self.scoped(|resolver| {
+                        resolver.declare(name, SymbolKind::Variable);
+                        resolver.stmts(body);
+                    });
+                }
+                if let Some(body) = &stmt.finally_body {
+                    self.scoped(|resolver| resolver.stmts(body));
+                }
+            }
+            StmtType::Import(stmt) => {
+                if let Some(alias) = &stmt.alias {
+                    self.declare(alias, SymbolKind::Module);
+                }
+                for name in &stmt.names {
+                    self.declare(name, SymbolKind::Variable);
+                }
+            }
+            StmtType::Test(stmt) => self.scoped(|resolver| resolver.stmts(&stmt.body)),
+            StmtType::Debugger(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &ExpressionType) {
+        match expr {
+            ExpressionType::Variable(expr) => self.reference(&expr.name),
+            ExpressionType::Assign(expr) => {
+                self.expr(&expr.value);
+                self.reference(&expr.name);
+            }
+            ExpressionType::CompoundAssign(expr) => {
+                self.expr(&expr.value);
+                self.reference(&expr.name);
+            }
+            ExpressionType::Update(expr) => self.reference(&expr.name),
+            ExpressionType::Binary(expr) => {
+                self.expr(&expr.left);
+                self.expr(&expr.right);
+            }
+            ExpressionType::Logical(expr) => {
+                self.expr(&expr.left);
+                self.expr(&expr.right);
+            }
+            ExpressionType::Unary(expr) => self.expr(&expr.right),
+            ExpressionType::Grouping(expr) => self.expr(&expr.expression),
+            ExpressionType::Literal(_) => {}
+            ExpressionType::Call(expr) => {
+                self.expr(&expr.callee);
+                for argument in &expr.arguments {
+                    self.expr(argument);
+                }
+            }
+            ExpressionType::Conditional(expr) => {
+                self.expr(&expr.condition);
+                self.expr(&expr.then_branch);
+                self.expr(&expr.else_branch);
+            }
+            // Only the object is a name; the property belongs to a module.
+            ExpressionType::Get(expr) => self.expr(&expr.object),
+        }
+    }
+}
+
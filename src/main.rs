@@ -1,12 +1,28 @@
-use std::env;
+use std::{env, process, thread};
 
 use jlox_rustport::lox::Lox;
 
+// Lox calls recurse in the interpreter too, and unoptimized builds use a
+// lot of stack per call; this leaves room for the default call depth limit
+// to be hit before the stack runs out.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 pub fn main() {
-    let mut lox = Lox {
-        args: env::args().collect(),
-        had_error: false,
-        had_runtime_error: false,
-    };
-    lox.start();
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            let mut lox = Lox {
+                args: env::args().collect(),
+                had_error: false,
+                had_runtime_error: false,
+                exit_code: None,
+                compile_errors: Vec::new(),
+                runtime_error_report: None,
+            };
+            lox.start();
+        })
+        .expect("Interpreter thread started");
+    if interpreter.join().is_err() {
+        process::exit(101);
+    }
 }
@@ -0,0 +1,112 @@
+//! Measures what a script is holding on to, for the total that
+//! `Limits::memory` bounds.
+
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::Interpreter,
+    lox_std::map::LoxMap,
+    token_type::LiteralType,
+};
+
+/// The bytes in strings plus the elements in lists and maps reachable from
+/// the running code, its callers, the loaded modules and `value`, which is
+/// usually the result of the expression just evaluated. Values shared
+/// through several variables are counted once.
+pub fn in_use(interpreter: &Interpreter, value: Option<&BindableValue>) -> usize {
+    let mut walk = Walk::default();
+
+    if let Some(environment) = &interpreter.environment {
+        walk.pending.push(Shared::Environment(environment.clone()));
+    }
+    walk.pending.push(Shared::Environment(interpreter.globals.clone()));
+    for frame in &interpreter.frames {
+        walk.pending.push(Shared::Environment(frame.environment.clone()));
+    }
+    for module in interpreter.modules.values() {
+        walk.pending.push(Shared::Environment(module.environment.clone()));
+    }
+    if let Some(value) = value {
+        walk.value(value);
+    }
+
+    while let Some(shared) = walk.pending.pop() {
+        walk.shared(shared);
+    }
+    walk.total
+}
+
+/// What values hold by reference. The walk keeps a stack of these rather
+/// than recursing, so deeply nested lists cannot overflow the native stack.
+enum Shared {
+    Environment(Rc<RefCell<Environment>>),
+    List(Rc<RefCell<Vec<BindableValue>>>),
+    Map(Rc<RefCell<LoxMap>>),
+}
+
+// `seen` holds the environments, lists and maps already counted, which
+// also stops the walk going round cycles.
+#[derive(Default)]
+struct Walk {
+    pending: Vec<Shared>,
+    seen: HashSet<*const ()>,
+    total: usize,
+}
+
+impl Walk {
+    fn shared(&mut self, shared: Shared) {
+        match shared {
+            Shared::Environment(environment) => {
+                if self.seen.insert(Rc::as_ptr(&environment) as *const ()) {
+                    let environment = environment.borrow();
+                    for value in environment.values.values() {
+                        self.value(value);
+                    }
+                    if let Some(enclosing) = &environment.enclosing {
+                        self.pending.push(Shared::Environment(enclosing.clone()));
+                    }
+                }
+            }
+            Shared::List(list) => {
+                if self.seen.insert(Rc::as_ptr(&list) as *const ()) {
+                    let list = list.borrow();
+                    self.add(list.len());
+                    for item in list.iter() {
+                        self.value(item);
+                    }
+                }
+            }
+            Shared::Map(map) => {
+                if self.seen.insert(Rc::as_ptr(&map) as *const ()) {
+                    let map = map.borrow();
+                    self.add(map.len());
+                    for (key, value) in map.iter() {
+                        self.add(key.len());
+                        self.value(value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn value(&mut self, value: &BindableValue) {
+        match value {
+            BindableValue::Literal(LiteralType::String(string)) => self.add(string.len()),
+            BindableValue::Error(error) => self.add(error.message.len()),
+            BindableValue::Function(function) => {
+                self.pending.push(Shared::Environment(function.closure.clone()))
+            }
+            BindableValue::Module(module) => {
+                self.pending.push(Shared::Environment(module.environment.clone()))
+            }
+            BindableValue::List(list) => self.pending.push(Shared::List(list.clone())),
+            BindableValue::Map(map) => self.pending.push(Shared::Map(map.clone())),
+            BindableValue::Literal(_) | BindableValue::NativeFunction(_) => {}
+        }
+    }
+
+    fn add(&mut self, size: usize) {
+        self.total = self.total.saturating_add(size);
+    }
+}
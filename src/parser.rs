@@ -1,8 +1,12 @@
 use crate::expr::{
-    Assign, Binary, Call, ExpressionType, Grouping, Literal, Logical, Unary, Variable,
+    Assign, Binary, Call, CompoundAssign, Conditional, ExpressionType, Get, Grouping, Literal, Logical,
+    Unary, Update, Variable,
 };
 use crate::lox::Lox;
-use crate::stmt::{Block, Expression, Function, If, Print, Return, StmtType, Var, While};
+use crate::stmt::{
+    Block, Debugger, Expression, For, Function, If, Import, Print, Return, StmtType, Test, Throw,
+    Try, Var, While,
+};
 use crate::token_type::*;
 
 pub struct Parser {
@@ -11,7 +15,7 @@ pub struct Parser {
 }
 
 #[derive(Debug)]
-pub struct ParseError(String);
+pub struct ParseError;
 
 type DefaultResult = Result<StmtType, ParseError>;
 
@@ -24,8 +28,12 @@ impl Parser {
         self.tokens = scanned_tokens;
         let mut statements: Vec<StmtType> = Vec::new();
 
-        while !Self::is_at_end(&self) {
-            let declaration = Self::declaration(self, lox_strt_instance);
+        while !Self::is_at_end(self) {
+            let declaration = if Self::match_expr(self, &[TokenType::Test]) {
+                Self::top_level_test(self, lox_strt_instance)
+            } else {
+                Self::declaration(self, lox_strt_instance)
+            };
 
             if let Ok(value) = declaration {
                 statements.push(value);
@@ -41,22 +49,31 @@ impl Parser {
                 Ok(value) => return Ok(value),
                 Err(_) => {
                     Self::synchronize(self);
-                    return Err(ParseError("".to_string()));
+                    return Err(ParseError);
                 }
             }
         } else if Self::match_expr(self, &[TokenType::Fun]) {
             return Ok(Self::function(self, "function", lox_strt_instance))?;
+        } else if Self::match_expr(self, &[TokenType::Test]) {
+            // Only top-level tests are run, so a nested one is an error
+            // rather than a test that silently never runs.
+            let keyword = Self::previous(self);
+            Self::error(keyword, "Test blocks must be at the top level.", lox_strt_instance);
+            if Self::test_declaration(self, lox_strt_instance).is_err() {
+                Self::synchronize(self);
+            }
+            return Err(ParseError);
         }
 
         let stmt = Self::statement(self, lox_strt_instance);
 
         match stmt {
             Ok(stmt) => {
-                return Ok(stmt);
+                Ok(stmt)
             }
             Err(_) => {
                 Self::synchronize(self);
-                return Err(ParseError("".to_string()));
+                Err(ParseError)
             }
         }
     }
@@ -83,11 +100,45 @@ impl Parser {
         )?;
 
         Ok(StmtType::Var(Var {
-            name: name,
-            initializer: initializer,
+            name,
+            initializer,
+        }))
+    }
+    fn top_level_test(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let test_declaration = Self::test_declaration(self, lox_strt_instance);
+
+        match test_declaration {
+            Ok(value) => Ok(value),
+            Err(_) => {
+                Self::synchronize(self);
+                Err(ParseError)
+            }
+        }
+    }
+    fn test_declaration(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
+        let description = Self::consume(
+            self,
+            &TokenType::String,
+            "Expect test description string.",
+            lox_strt_instance,
+        )?;
+        Self::consume(
+            self,
+            &TokenType::LeftBrace,
+            "Expect '{' before test body.",
+            lox_strt_instance,
+        )?;
+        let body = Self::block(self, lox_strt_instance)?;
+
+        Ok(StmtType::Test(Test {
+            keyword,
+            description,
+            body,
         }))
     }
     fn while_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
         Self::consume(
             self,
             &TokenType::LeftParen,
@@ -104,13 +155,14 @@ impl Parser {
 
         let body = Self::statement(self, lox_strt_instance)?;
 
-        return Ok(StmtType::While(While {
+        Ok(StmtType::While(While {
+            keyword,
             condition,
             body: Box::new(body),
-        }));
+        }))
     }
     fn statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
-        return if Self::match_expr(self, &[TokenType::Print]) {
+        if Self::match_expr(self, &[TokenType::Print]) {
             Self::print_statement(self, lox_strt_instance)
         } else if Self::match_expr(self, &[TokenType::LeftBrace]) {
             let statements = Self::block(self, lox_strt_instance)?;
@@ -120,16 +172,25 @@ impl Parser {
         } else if Self::match_expr(self, &[TokenType::While]) {
             Self::while_statement(self, lox_strt_instance)
         } else if Self::match_expr(self, &[TokenType::For]) {
-            let stmt = Self::for_statement(self, lox_strt_instance);
-            return stmt;
+            Self::for_statement(self, lox_strt_instance)
         } else if Self::match_expr(self, &[TokenType::Return]) {
-            let stmt = Self::return_statement(self, lox_strt_instance);
-            return stmt;
+            Self::return_statement(self, lox_strt_instance)
+        } else if Self::match_expr(self, &[TokenType::Throw]) {
+            Self::throw_statement(self, lox_strt_instance)
+        } else if Self::match_expr(self, &[TokenType::Try]) {
+            Self::try_statement(self, lox_strt_instance)
+        } else if Self::match_expr(self, &[TokenType::Import]) {
+            Self::import_statement(self, lox_strt_instance)
+        } else if Self::match_expr(self, &[TokenType::From]) {
+            Self::import_from_statement(self, lox_strt_instance)
+        } else if Self::match_expr(self, &[TokenType::Debugger]) {
+            Self::debugger_statement(self, lox_strt_instance)
         } else {
             Self::expression_statement(self, lox_strt_instance)
-        };
+        }
     }
     fn for_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
         Self::consume(
             self,
             &TokenType::LeftParen,
@@ -137,17 +198,15 @@ impl Parser {
             lox_strt_instance,
         )?;
 
-        let mut initializer: Option<StmtType> = None;
-
-        if Self::match_expr(self, &[TokenType::Semicolon]) {
-            initializer = None;
+        let initializer: Option<StmtType> = if Self::match_expr(self, &[TokenType::Semicolon]) {
+            None
         } else if Self::match_expr(self, &[TokenType::Var]) {
             let var_decl = Self::var_declaration(self, lox_strt_instance)?;
-            initializer = Some(var_decl);
+            Some(var_decl)
         } else {
             let expr_stmt = Self::expression_statement(self, lox_strt_instance)?;
-            initializer = Some(expr_stmt);
-        }
+            Some(expr_stmt)
+        };
 
         let mut condition: Option<ExpressionType> = None;
 
@@ -176,39 +235,18 @@ impl Parser {
             lox_strt_instance,
         )?;
 
-        let mut body = Self::statement(self, lox_strt_instance)?;
-
-        if let Some(increment) = increment {
-            body = StmtType::Block(Block {
-                statements: Vec::from([
-                    body,
-                    StmtType::Expression(Expression {
-                        expression: increment,
-                    }),
-                ]),
-            });
-        }
-
-        if let None = condition {
-            condition = Some(ExpressionType::Literal(Literal {
-                value: LiteralType::Bool(true),
-            }));
-        }
+        let body = Self::statement(self, lox_strt_instance)?;
 
-        body = StmtType::While(While {
-            condition: condition.unwrap(),
+        Ok(StmtType::For(For {
+            keyword,
+            initializer: initializer.map(Box::new),
+            condition,
+            increment,
             body: Box::new(body),
-        });
-
-        if let Some(initializer) = initializer {
-            body = StmtType::Block(Block {
-                statements: Vec::from([initializer, body]),
-            })
-        }
-
-        return Ok(body);
+        }))
     }
     fn if_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
         Self::consume(
             self,
             &TokenType::LeftParen,
@@ -230,13 +268,26 @@ impl Parser {
             let stmt_result = Self::statement(self, lox_strt_instance)?;
             else_branch = Some(stmt_result);
         }
-        return Ok(StmtType::If(If {
+        Ok(StmtType::If(If {
+            keyword,
             condition: Box::new(condition),
             then_branch: Box::new(then_branch),
-            else_branch: else_branch.map(|branch| Box::new(branch)),
-        }));
+            else_branch: else_branch.map(Box::new),
+        }))
+    }
+    fn debugger_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
+        Self::consume(
+            self,
+            &TokenType::Semicolon,
+            "Expect ';' after 'debugger'.",
+            lox_strt_instance,
+        )?;
+
+        Ok(StmtType::Debugger(Debugger { keyword }))
     }
     fn print_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
         let value: ExpressionType = Self::expression(self, lox_strt_instance)?;
 
         Self::consume(
@@ -246,16 +297,17 @@ impl Parser {
             lox_strt_instance,
         )?;
 
-        Ok(StmtType::Print(Print { expression: value }))
+        Ok(StmtType::Print(Print {
+            keyword,
+            expression: value,
+        }))
     }
     fn return_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
-        let keyword = Self::previous(&self);
-        let mut value = ExpressionType::Literal(Literal {
-            value: LiteralType::Nil,
-        });
+        let keyword = Self::previous(self);
+        let mut value: Option<ExpressionType> = None;
 
         if !self.check(&TokenType::Semicolon) {
-            value = Self::expression(self, lox_strt_instance)?;
+            value = Some(Self::expression(self, lox_strt_instance)?);
         }
 
         Self::consume(
@@ -265,7 +317,163 @@ impl Parser {
             lox_strt_instance,
         )?;
 
-        return Ok(StmtType::Return(Return { keyword, value }));
+        Ok(StmtType::Return(Return { keyword, value }))
+    }
+    fn throw_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
+        let value = Self::expression(self, lox_strt_instance)?;
+
+        Self::consume(
+            self,
+            &TokenType::Semicolon,
+            "Expect ';' after thrown value.",
+            lox_strt_instance,
+        )?;
+
+        Ok(StmtType::Throw(Throw { keyword, value }))
+    }
+    fn try_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        Self::consume(
+            self,
+            &TokenType::LeftBrace,
+            "Expect '{' after 'try'.",
+            lox_strt_instance,
+        )?;
+        let body = Self::block(self, lox_strt_instance)?;
+
+        let mut catch_name: Option<Token> = None;
+        let mut catch_body: Option<Vec<StmtType>> = None;
+
+        if Self::match_expr(self, &[TokenType::Catch]) {
+            Self::consume(
+                self,
+                &TokenType::LeftParen,
+                "Expect '(' after 'catch'.",
+                lox_strt_instance,
+            )?;
+            catch_name = Some(Self::consume(
+                self,
+                &TokenType::Identifier,
+                "Expect exception variable name.",
+                lox_strt_instance,
+            )?);
+            Self::consume(
+                self,
+                &TokenType::RightParen,
+                "Expect ')' after exception variable name.",
+                lox_strt_instance,
+            )?;
+            Self::consume(
+                self,
+                &TokenType::LeftBrace,
+                "Expect '{' before catch body.",
+                lox_strt_instance,
+            )?;
+            catch_body = Some(Self::block(self, lox_strt_instance)?);
+        }
+
+        let mut finally_body: Option<Vec<StmtType>> = None;
+
+        if Self::match_expr(self, &[TokenType::Finally]) {
+            Self::consume(
+                self,
+                &TokenType::LeftBrace,
+                "Expect '{' after 'finally'.",
+                lox_strt_instance,
+            )?;
+            finally_body = Some(Self::block(self, lox_strt_instance)?);
+        }
+
+        if catch_body.is_none() && finally_body.is_none() {
+            return Err(Self::error(
+                Self::peek(self),
+                "Expect 'catch' or 'finally' after try block.",
+                lox_strt_instance,
+            ));
+        }
+
+        Ok(StmtType::Try(Try {
+            body,
+            catch_name,
+            catch_body,
+            finally_body,
+        }))
+    }
+    fn import_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let keyword = Self::previous(self);
+        let path = Self::consume(
+            self,
+            &TokenType::String,
+            "Expect module path after 'import'.",
+            lox_strt_instance,
+        )?;
+        Self::consume(
+            self,
+            &TokenType::As,
+            "Expect 'as' after module path.",
+            lox_strt_instance,
+        )?;
+        let alias = Self::consume(
+            self,
+            &TokenType::Identifier,
+            "Expect module name after 'as'.",
+            lox_strt_instance,
+        )?;
+        Self::consume(
+            self,
+            &TokenType::Semicolon,
+            "Expect ';' after import.",
+            lox_strt_instance,
+        )?;
+
+        Ok(StmtType::Import(Import {
+            keyword,
+            path,
+            alias: Some(alias),
+            names: Vec::new(),
+        }))
+    }
+    fn import_from_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
+        let path = Self::consume(
+            self,
+            &TokenType::String,
+            "Expect module path after 'from'.",
+            lox_strt_instance,
+        )?;
+        let keyword = Self::consume(
+            self,
+            &TokenType::Import,
+            "Expect 'import' after module path.",
+            lox_strt_instance,
+        )?;
+
+        let mut names = Vec::from([Self::consume(
+            self,
+            &TokenType::Identifier,
+            "Expect imported name.",
+            lox_strt_instance,
+        )?]);
+        while Self::match_expr(self, &[TokenType::Comma]) {
+            names.push(Self::consume(
+                self,
+                &TokenType::Identifier,
+                "Expect imported name.",
+                lox_strt_instance,
+            )?);
+        }
+        Self::consume(
+            self,
+            &TokenType::Semicolon,
+            "Expect ';' after import.",
+            lox_strt_instance,
+        )?;
+
+        Ok(StmtType::Import(Import {
+            keyword,
+            path,
+            alias: None,
+            names,
+        }))
     }
     fn expression_statement(&mut self, lox_strt_instance: &mut Lox) -> DefaultResult {
         let expr: ExpressionType = Self::expression(self, lox_strt_instance)?;
@@ -303,7 +511,7 @@ impl Parser {
         if !self.check(&TokenType::RightParen) {
             if params.len() > 255 {
                 return Err(Self::error(
-                    Self::peek(&self),
+                    Self::peek(self),
                     "Can't have more than 255 characters",
                     lox_strt_instance,
                 ));
@@ -340,12 +548,12 @@ impl Parser {
 
         let body: Vec<StmtType> = Self::block(self, lox_strt_instance)?;
 
-        return Ok(StmtType::Function(Function { name, params, body }));
+        Ok(StmtType::Function(Function { name, params, body }))
     }
     fn block(&mut self, lox_strt_instance: &mut Lox) -> Result<Vec<StmtType>, ParseError> {
         let mut statements = Vec::new();
 
-        while !self.check(&TokenType::RightBrace) && !Self::is_at_end(&self) {
+        while !self.check(&TokenType::RightBrace) && !Self::is_at_end(self) {
             let declaration = Self::declaration(self, lox_strt_instance);
 
             if let Ok(decl) = declaration {
@@ -358,28 +566,108 @@ impl Parser {
             "Expect '}' after block.",
             lox_strt_instance,
         )?;
-        return Ok(statements);
+        Ok(statements)
+    }
+    fn comma(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
+        let mut expr = Self::assigment(self, lox_strt_instance)?;
+
+        while Self::match_expr(self, &[TokenType::Comma]) {
+            let operator = Self::previous(self);
+            let right = Self::assigment(self, lox_strt_instance)?;
+            expr = ExpressionType::Binary(Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        Ok(expr)
     }
     fn assigment(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
-        let expr = Self::or(self, lox_strt_instance)?;
+        let expr = Self::conditional(self, lox_strt_instance)?;
 
-        if Self::match_expr(self, &[TokenType::Equal]) {
-            let equals = Self::previous(&self);
+        if Self::match_expr(
+            self,
+            &[
+                TokenType::Equal,
+                TokenType::PlusEqual,
+                TokenType::MinusEqual,
+                TokenType::StarEqual,
+                TokenType::SlashEqual,
+            ],
+        ) {
+            let equals = Self::previous(self);
             let value = Self::assigment(self, lox_strt_instance)?;
+            let name = Self::assignment_target(expr, &equals, lox_strt_instance)?;
 
-            if let ExpressionType::Variable(variable) = expr {
-                let name = variable.name;
+            if let TokenType::Equal = equals.ttype {
                 return Ok(ExpressionType::Assign(Assign {
-                    name: name,
+                    name,
                     value: Box::new(value),
                 }));
             }
 
-            Err(ParseError(format!("{:?} Invalid assigment target", equals)))
+            Ok(ExpressionType::CompoundAssign(CompoundAssign {
+                name,
+                operator: equals,
+                value: Box::new(value),
+            }))
         } else {
-            return Ok(expr);
+            Ok(expr)
         }
     }
+    fn assignment_target(
+        expr: ExpressionType,
+        equals: &Token,
+        lox_strt_instance: &mut Lox,
+    ) -> Result<Token, ParseError> {
+        if let ExpressionType::Variable(variable) = expr {
+            return Ok(variable.name);
+        }
+
+        Err(Self::error(
+            equals.clone(),
+            "Invalid assignment target.",
+            lox_strt_instance,
+        ))
+    }
+    fn conditional(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
+        let expr = Self::nil_coalescing(self, lox_strt_instance)?;
+
+        if Self::match_expr(self, &[TokenType::Question]) {
+            let then_branch = Self::expression(self, lox_strt_instance)?;
+            Self::consume(
+                self,
+                &TokenType::Colon,
+                "Expect ':' after then branch of conditional expression.",
+                lox_strt_instance,
+            )?;
+            let else_branch = Self::conditional(self, lox_strt_instance)?;
+
+            return Ok(ExpressionType::Conditional(Conditional {
+                condition: Box::new(expr),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            }));
+        }
+        Ok(expr)
+    }
+    fn nil_coalescing(
+        &mut self,
+        lox_strt_instance: &mut Lox,
+    ) -> Result<ExpressionType, ParseError> {
+        let mut expr = Self::or(self, lox_strt_instance)?;
+
+        while Self::match_expr(self, &[TokenType::QuestionQuestion]) {
+            let operator = Self::previous(self);
+            let right = Self::or(self, lox_strt_instance)?;
+            expr = ExpressionType::Logical(Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        Ok(expr)
+    }
     pub fn or(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
         let mut expr = Self::and(self, lox_strt_instance)?;
 
@@ -412,7 +700,7 @@ impl Parser {
         &mut self,
         lox_strt_instance: &mut Lox,
     ) -> Result<ExpressionType, ParseError> {
-        Self::assigment(self, lox_strt_instance)
+        Self::comma(self, lox_strt_instance)
     }
     pub fn equality(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
         let mut expr = Self::comparison(self, lox_strt_instance);
@@ -460,7 +748,7 @@ impl Parser {
         ) {
             let operator = Self::previous(self);
 
-            let right = Self::unary(self, lox_strt_instance)?;
+            let right = Self::term(self, lox_strt_instance)?;
             expr = ExpressionType::Binary(Binary {
                 left: Box::new(expr),
                 operator,
@@ -468,7 +756,7 @@ impl Parser {
             });
         }
 
-        return Ok(expr);
+        Ok(expr)
     }
     pub fn term(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
         let mut expr = Self::factor(self, lox_strt_instance);
@@ -477,7 +765,7 @@ impl Parser {
 
             match expr {
                 Ok(ok_response) => {
-                    let right = Self::unary(self, lox_strt_instance);
+                    let right = Self::factor(self, lox_strt_instance);
                     match right {
                         Ok(right_expr) => {
                             expr = Ok(ExpressionType::Binary(Binary {
@@ -487,6 +775,7 @@ impl Parser {
                                     lexeme: operator.lexeme,
                                     literal: operator.literal,
                                     line: operator.line,
+                                    column: operator.column,
                                 },
                                 right: Box::new(right_expr),
                             }));
@@ -502,13 +791,13 @@ impl Parser {
             }
         }
 
-        return expr;
+        expr
     }
     pub fn factor(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
         let mut expr = Self::unary(self, lox_strt_instance);
 
         while Self::match_expr(self, &[TokenType::Slash, TokenType::Star]) {
-            let operator = Self::previous(&self);
+            let operator = Self::previous(self);
 
             match expr {
                 Ok(ok_response) => {
@@ -522,6 +811,7 @@ impl Parser {
                                     lexeme: operator.lexeme,
                                     literal: operator.literal,
                                     line: operator.line,
+                                    column: operator.column,
                                 },
                                 right: Box::new(right_expr),
                             }));
@@ -537,11 +827,22 @@ impl Parser {
             }
         }
 
-        return expr;
+        expr
     }
     pub fn unary(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
+        if Self::match_expr(self, &[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = Self::previous(self);
+            let right = Self::unary(self, lox_strt_instance)?;
+            let name = Self::assignment_target(right, &operator, lox_strt_instance)?;
+
+            return Ok(ExpressionType::Update(Update {
+                name,
+                operator,
+                prefix: true,
+            }));
+        }
         if Self::match_expr(self, &[TokenType::Bang, TokenType::Minus]) {
-            let operator = Self::previous(&self);
+            let operator = Self::previous(self);
             let right = Self::unary(self, lox_strt_instance);
 
             match right {
@@ -552,6 +853,7 @@ impl Parser {
                             lexeme: operator.lexeme,
                             literal: operator.literal,
                             line: operator.line,
+                            column: operator.column,
                         },
                         right: Box::new(ok_response),
                     }));
@@ -561,23 +863,24 @@ impl Parser {
                 }
             }
         }
-        return Self::call(self, lox_strt_instance);
+        Self::call(self, lox_strt_instance)
     }
     fn finish_call(
         &mut self,
         callee: ExpressionType,
+        optional: bool,
         lox_strt_instance: &mut Lox,
     ) -> Result<ExpressionType, ParseError> {
         let mut arguments: Vec<ExpressionType> = Vec::new();
 
         if !self.check(&TokenType::RightParen) {
             if arguments.len() >= 255 {
-                lox_strt_instance.error(Self::peek(&self), "Can't have more than 255 arguments.");
+                lox_strt_instance.error(Self::peek(self), "Can't have more than 255 arguments.");
             }
 
-            arguments.push(Self::expression(self, lox_strt_instance)?);
+            arguments.push(Self::assigment(self, lox_strt_instance)?);
             while Self::match_expr(self, &[TokenType::Comma]) {
-                arguments.push(Self::expression(self, lox_strt_instance)?);
+                arguments.push(Self::assigment(self, lox_strt_instance)?);
             }
         }
 
@@ -588,24 +891,64 @@ impl Parser {
             lox_strt_instance,
         )?;
 
-        return Ok(ExpressionType::Call(Call {
+        Ok(ExpressionType::Call(Call {
             callee: Box::new(callee),
             arguments,
             paren,
-        }));
+            optional,
+        }))
     }
     pub fn call(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
         let mut expr = Self::primary(self, lox_strt_instance)?;
 
         loop {
             if Self::match_expr(self, &[TokenType::LeftParen]) {
-                expr = Self::finish_call(self, expr, lox_strt_instance)?;
+                expr = Self::finish_call(self, expr, false, lox_strt_instance)?;
+            } else if Self::match_expr(self, &[TokenType::Dot]) {
+                let name = Self::consume(
+                    self,
+                    &TokenType::Identifier,
+                    "Expect property name after '.'.",
+                    lox_strt_instance,
+                )?;
+                expr = ExpressionType::Get(Get {
+                    object: Box::new(expr),
+                    name,
+                    optional: false,
+                });
+            } else if Self::match_expr(self, &[TokenType::QuestionDot]) {
+                if Self::match_expr(self, &[TokenType::LeftParen]) {
+                    expr = Self::finish_call(self, expr, true, lox_strt_instance)?;
+                } else {
+                    let name = Self::consume(
+                        self,
+                        &TokenType::Identifier,
+                        "Expect property name or '(' after '?.'.",
+                        lox_strt_instance,
+                    )?;
+                    expr = ExpressionType::Get(Get {
+                        object: Box::new(expr),
+                        name,
+                        optional: true,
+                    });
+                }
             } else {
                 break;
             }
         }
 
-        return Ok(expr);
+        if Self::match_expr(self, &[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = Self::previous(self);
+            let name = Self::assignment_target(expr, &operator, lox_strt_instance)?;
+
+            return Ok(ExpressionType::Update(Update {
+                name,
+                operator,
+                prefix: false,
+            }));
+        }
+
+        Ok(expr)
     }
     pub fn primary(&mut self, lox_strt_instance: &mut Lox) -> Result<ExpressionType, ParseError> {
         if Self::match_expr(self, &[TokenType::False]) {
@@ -626,7 +969,7 @@ impl Parser {
 
         if Self::match_expr(self, &[TokenType::Number, TokenType::String]) {
             return Ok(ExpressionType::Literal(Literal {
-                value: Self::previous(&self).literal.unwrap(),
+                value: Self::previous(self).literal.unwrap(),
             }));
         }
 
@@ -644,14 +987,14 @@ impl Parser {
             }));
         }
         if Self::match_expr(self, &[TokenType::Identifier]) {
-            let prev_token = Self::previous(&self);
+            let prev_token = Self::previous(self);
             return Ok(ExpressionType::Variable(Variable { name: prev_token }));
         }
         Self::error(Self::peek(self), "Expect expression.", lox_strt_instance);
 
-        return Ok(ExpressionType::Literal(Literal {
+        Ok(ExpressionType::Literal(Literal {
             value: LiteralType::Nil,
-        }));
+        }))
     }
     pub fn consume(
         &mut self,
@@ -665,7 +1008,7 @@ impl Parser {
         }
         Ok(Self::advance(self))
     }
-    pub fn synchronize(&mut self) -> () {
+    pub fn synchronize(&mut self) {
         Self::advance(self);
 
         while !Self::is_at_end(self) {
@@ -681,6 +1024,12 @@ impl Parser {
                 TokenType::Return => return,
                 TokenType::Var => return,
                 TokenType::While => return,
+                TokenType::Throw => return,
+                TokenType::Try => return,
+                TokenType::Import => return,
+                TokenType::From => return,
+                TokenType::Test => return,
+                TokenType::Debugger => return,
                 _ => {}
             }
             Self::advance(self);
@@ -690,9 +1039,9 @@ impl Parser {
         types.iter().any(|t| {
             if self.check(t) {
                 Self::advance(self);
-                return true;
+                true
             } else {
-                return false;
+                false
             }
         })
     }
@@ -720,6 +1069,6 @@ impl Parser {
     }
     pub fn error(token: Token, message: &str, lox_strt_instance: &mut Lox) -> ParseError {
         lox_strt_instance.error(token, message);
-        ParseError("".to_string())
+        ParseError
     }
 }
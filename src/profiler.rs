@@ -0,0 +1,223 @@
+//! The profiler behind `jlox --profile`.
+//!
+//! `Profiler` is a hook that times every call, so nothing is sampled: each
+//! function gets its call count, inclusive time (from call to return,
+//! counted once for recursive calls) and exclusive time (inclusive less
+//! the calls it made), and each line the number of statements that started
+//! on it. The report lists the script itself as `<script>`, called once,
+//! whose exclusive time is the time spent at its top level. The hook
+//! itself costs time, which lands mostly in the exclusive time of whatever
+//! makes many short calls.
+//!
+//! Functions and lines are kept per file, so a module's `helper` or line 3
+//! is not mixed up with the script's.
+
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::BTreeMap,
+    path::Path,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    environment::BindableValue,
+    hook::Hook,
+    interpreter::{Interpreter, RuntimeError},
+    stmt::StmtType,
+};
+
+const SCRIPT: &str = "<script>";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FunctionStats {
+    pub calls: u64,
+    pub inclusive: Duration,
+    pub exclusive: Duration,
+}
+
+/// What a `Profiler` recorded, shared with whoever reports it once the
+/// interpreter, and with it the hook, is gone.
+#[derive(Debug, Default)]
+pub struct Profile {
+    /// Keyed by the file the function was declared in, empty for natives,
+    /// and its name.
+    pub functions: BTreeMap<(String, String), FunctionStats>,
+    /// Statements started per file and line.
+    pub lines: BTreeMap<(String, u32), u64>,
+    /// Exclusive time per call stack, as `<script>;outer;inner`.
+    pub stacks: BTreeMap<String, Duration>,
+    /// Time spent in calls made from the top level.
+    pub top_level: Duration,
+}
+
+pub struct Profiler {
+    profile: Rc<RefCell<Profile>>,
+    calls: Vec<ActiveCall>,
+}
+
+struct ActiveCall {
+    file: String,
+    function: String,
+    start: Instant,
+    // Time spent in the calls this one made.
+    children: Duration,
+}
+
+impl Profiler {
+    pub fn new(profile: Rc<RefCell<Profile>>) -> Profiler {
+        Profiler {
+            profile,
+            calls: Vec::new(),
+        }
+    }
+}
+
+impl Hook for Profiler {
+    fn statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        _stmt: &StmtType,
+        line: u32,
+    ) -> Result<(), RuntimeError> {
+        let file = display(interpreter.current_file.as_deref());
+        *self.profile.borrow_mut().lines.entry((file, line)).or_insert(0) += 1;
+        Ok(())
+    }
+
+    fn call(&mut self, interpreter: &mut Interpreter, _arguments: &[BindableValue]) {
+        let frame = interpreter.frames.last().unwrap();
+        self.calls.push(ActiveCall {
+            file: display(frame.file.as_deref()),
+            function: frame.function.clone(),
+            start: Instant::now(),
+            children: Duration::ZERO,
+        });
+    }
+
+    fn call_finished(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        _result: &Result<BindableValue, RuntimeError>,
+    ) {
+        let elapsed = match self.calls.last() {
+            Some(call) => call.start.elapsed(),
+            None => return,
+        };
+        let mut stack = String::from(SCRIPT);
+        for call in &self.calls {
+            stack.push(';');
+            stack.push_str(&call.function);
+        }
+        let call = self.calls.pop().unwrap();
+        let exclusive = elapsed.saturating_sub(call.children);
+        let recursive = self
+            .calls
+            .iter()
+            .any(|outer| outer.file == call.file && outer.function == call.function);
+
+        let mut profile = self.profile.borrow_mut();
+        let stats = profile.functions.entry((call.file, call.function)).or_default();
+        stats.calls += 1;
+        stats.exclusive += exclusive;
+        if !recursive {
+            stats.inclusive += elapsed;
+        }
+        *profile.stacks.entry(stack).or_default() += exclusive;
+        match self.calls.last_mut() {
+            Some(caller) => caller.children += elapsed,
+            None => profile.top_level += elapsed,
+        }
+    }
+}
+
+impl Profile {
+    /// The report `jlox --profile` prints: how long each phase took, then
+    /// the functions by inclusive time and the lines by hits, both
+    /// busiest first. `script` is the file that ran, taking `interpret`.
+    pub fn report(
+        &self,
+        phases: &[(&str, Duration)],
+        script: &str,
+        interpret: Duration,
+    ) -> String {
+        let mut report = String::from("Phases:\n");
+        for (phase, duration) in phases {
+            report.push_str(&format!("  {:<12}{:>12} ms\n", phase, millis(*duration)));
+        }
+
+        let script_stats = FunctionStats {
+            calls: 1,
+            inclusive: interpret,
+            exclusive: interpret.saturating_sub(self.top_level),
+        };
+        let script_key = (script.to_string(), SCRIPT.to_string());
+        let mut functions: Vec<(&(String, String), &FunctionStats)> =
+            self.functions.iter().collect();
+        functions.push((&script_key, &script_stats));
+        functions.sort_by_key(|(_, stats)| Reverse(stats.inclusive));
+        let width = functions
+            .iter()
+            .map(|((_, name), _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("function".len());
+        report.push_str("\nFunctions:\n");
+        report.push_str(&format!(
+            "  {:<width$}  {:>8}  {:>14}  {:>14}  file\n",
+            "function",
+            "calls",
+            "inclusive ms",
+            "exclusive ms",
+            width = width
+        ));
+        for ((file, name), stats) in functions {
+            let row = format!(
+                "  {:<width$}  {:>8}  {:>14}  {:>14}  {}",
+                name,
+                stats.calls,
+                millis(stats.inclusive),
+                millis(stats.exclusive),
+                file,
+                width = width
+            );
+            report.push_str(row.trim_end());
+            report.push('\n');
+        }
+
+        let mut lines: Vec<(&(String, u32), &u64)> = self.lines.iter().collect();
+        lines.sort_by_key(|(_, hits)| Reverse(**hits));
+        report.push_str("\nLines:\n");
+        report.push_str(&format!("  {:>6}  {:>10}  file\n", "line", "hits"));
+        for ((file, line), hits) in lines {
+            let row = format!("  {:>6}  {:>10}  {}", line, hits, file);
+            report.push_str(row.trim_end());
+            report.push('\n');
+        }
+        report
+    }
+
+    /// The call stacks in the folded format flamegraph tools read, one
+    /// `<script>;outer;inner microseconds` line per stack with exclusive
+    /// time. `interpret` is how long the whole script ran.
+    pub fn folded(&self, interpret: Duration) -> String {
+        let mut folded = format!(
+            "{} {}\n",
+            SCRIPT,
+            interpret.saturating_sub(self.top_level).as_micros()
+        );
+        for (stack, duration) in &self.stacks {
+            folded.push_str(&format!("{} {}\n", stack, duration.as_micros()));
+        }
+        folded
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+fn display(file: Option<&Path>) -> String {
+    file.map(|file| file.display().to_string()).unwrap_or_default()
+}
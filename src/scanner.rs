@@ -8,11 +8,34 @@ use crate::token_type::TokenType;
 pub struct Scanner {
     pub source: Vec<u8>,
     pub tokens: Vec<Token>,
+    pub comments: Vec<Comment>,
+    pub positions: Vec<(u32, u32)>,
+    pub errors: Vec<ScanError>,
     pub start: usize,
     pub current: usize,
     pub line: u32,
 }
 
+/// A `//` comment, kept aside from the tokens for tools such as the
+/// formatter. `token_index` is the number of tokens scanned before it.
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub text: String,
+    pub line: u32,
+    pub token_index: usize,
+}
+
+/// An error found while scanning at `line` and `column`, reported on stderr and also kept for
+/// tools such as `jlox tokens`. `token_index` is the number of tokens
+/// scanned before it.
+#[derive(Debug, Clone)]
+pub struct ScanError {
+    pub message: String,
+    pub line: u32,
+    pub column: u32,
+    pub token_index: usize,
+}
+
 impl Scanner {
     pub fn scan_tokens(&mut self, source_file: String) -> Vec<Token> {
         self.source = source_file.as_bytes().to_vec();
@@ -24,24 +47,50 @@ impl Scanner {
 
         let line = self.line;
 
+        let position = self.position(self.current);
+        self.positions.push(position);
         self.tokens.push(Token {
             ttype: TokenType::Eof,
             lexeme: String::new(),
             literal: Some(LiteralType::Nil),
-            line: line,
+            line,
+            column: position.1,
         });
-        let tokens = self.tokens.clone();
-        tokens
+        self.tokens.clone()
     }
-    pub fn report(line: &u32, location: String, message: &str) -> () {
-        let err_msg = format!("[line {}] Error {}: {}", line, location, message);
+    pub fn report(line: &u32, location: String, message: &str) {
+        let err_msg = format!("[line {}] Error {}: {}\n", line, location, message);
         let mut err_out_handler = io::stderr();
         let _ = err_out_handler.write_all(err_msg.as_bytes());
     }
-    pub fn error(line: &u32, message: &str) {
-        Self::report(line, String::new(), message);
+    pub fn error(&mut self, message: &str) {
+        Self::report(&self.line, String::new(), message);
+        let (line, column) = self.position(self.start);
+        self.errors.push(ScanError {
+            message: message.to_string(),
+            line,
+            column,
+            token_index: self.tokens.len(),
+        });
+    }
+    /// The 1-based line and column of the byte at `offset`, which must not
+    /// be past `current`. Unlike a token's `line`, which is where it ends,
+    /// this is where a multi-line string starts.
+    pub fn position(&self, offset: usize) -> (u32, u32) {
+        let newlines_after = self.source[offset..self.current]
+            .iter()
+            .filter(|c| **c == b'\n')
+            .count();
+        let line_start = self.source[..offset]
+            .iter()
+            .rposition(|c| *c == b'\n')
+            .map_or(0, |newline| newline + 1);
+        (
+            self.line - newlines_after as u32,
+            (offset - line_start + 1) as u32,
+        )
     }
-    pub fn scan_token(&mut self) -> () {
+    pub fn scan_token(&mut self) {
         let c: u8 = self.advance();
 
         if c != b'\0' {
@@ -52,10 +101,33 @@ impl Scanner {
                 b'}' => self.add_token(TokenType::RightBrace, None),
                 b',' => self.add_token(TokenType::Comma, None),
                 b'.' => self.add_token(TokenType::Dot, None),
-                b'-' => self.add_token(TokenType::Minus, None),
-                b'+' => self.add_token(TokenType::Plus, None),
+                b'-' => {
+                    if self.match_token(b'-') {
+                        self.add_token(TokenType::MinusMinus, None)
+                    } else {
+                        self.match_token_sequence(b'-', b'=')
+                    }
+                }
+                b'+' => {
+                    if self.match_token(b'+') {
+                        self.add_token(TokenType::PlusPlus, None)
+                    } else {
+                        self.match_token_sequence(b'+', b'=')
+                    }
+                }
                 b';' => self.add_token(TokenType::Semicolon, None),
-                b'*' => self.add_token(TokenType::Star, None),
+                b'?' => {
+                    if self.match_token(b'?') {
+                        self.add_token(TokenType::QuestionQuestion, None)
+                    } else if self.peek() == b'.' && !Self::is_digit(self.peek_next()) {
+                        self.advance();
+                        self.add_token(TokenType::QuestionDot, None)
+                    } else {
+                        self.add_token(TokenType::Question, None)
+                    }
+                }
+                b':' => self.add_token(TokenType::Colon, None),
+                b'*' => self.match_token_sequence(b'*', b'='),
                 b'!' => self.match_token_sequence(b'!', b'='),
                 b'=' => self.match_token_sequence(b'=', b'='),
                 b'<' => self.match_token_sequence(b'<', b'='),
@@ -72,18 +144,18 @@ impl Scanner {
                     } else if Self::is_alpha(c) {
                         self.identifier();
                     } else {
-                        Scanner::error(&self.line, "Unexpected character.")
+                        self.error("Unexpected character.")
                     }
                 }
             }
         }
     }
     pub fn advance(&mut self) -> u8 {
-        self.current = self.current + 1;
+        self.current += 1;
         self.source[self.current - 1]
     }
     pub fn is_alpha(c: u8) -> bool {
-        (c >= b'a' && c <= b'z') || (c >= b'A' && c <= b'Z') || c == b'_'
+        c.is_ascii_lowercase() || c.is_ascii_uppercase() || c == b'_'
     }
     pub fn identifier(&mut self) {
         while Self::is_alphanumeric(self.peek()) {
@@ -109,25 +181,32 @@ impl Scanner {
             b"true" => TokenType::True,
             b"var" => TokenType::Var,
             b"while" => TokenType::While,
+            b"throw" => TokenType::Throw,
+            b"try" => TokenType::Try,
+            b"catch" => TokenType::Catch,
+            b"finally" => TokenType::Finally,
+            b"import" => TokenType::Import,
+            b"from" => TokenType::From,
+            b"as" => TokenType::As,
+            b"test" => TokenType::Test,
+            b"debugger" => TokenType::Debugger,
             _ => TokenType::Identifier,
         };
 
         self.add_token(ttype, Some(LiteralType::Nil))
     }
     pub fn is_digit(c: u8) -> bool {
-        c >= b'0' && c <= b'9'
+        c.is_ascii_digit()
     }
     pub fn is_alphanumeric(peeked_c: u8) -> bool {
         Self::is_alpha(peeked_c) || Self::is_digit(peeked_c)
     }
     pub fn match_token(&mut self, expected: u8) -> bool {
-        if Self::is_at_end(&self) {
-            return false;
-        } else if self.source[self.current] != expected {
-            return false;
+        if Self::is_at_end(self) || self.source[self.current] != expected {
+            false
         } else {
             self.current += 1;
-            return true;
+            true
         }
     }
     pub fn match_token_sequence(&mut self, case: u8, expected: u8) {
@@ -161,11 +240,40 @@ impl Scanner {
                 TokenType::Greater
             };
             self.add_token(ttype, Some(LiteralType::Nil))
+        } else if case == b'+' {
+            let ttype = if match_sequence {
+                TokenType::PlusEqual
+            } else {
+                TokenType::Plus
+            };
+            self.add_token(ttype, None)
+        } else if case == b'-' {
+            let ttype = if match_sequence {
+                TokenType::MinusEqual
+            } else {
+                TokenType::Minus
+            };
+            self.add_token(ttype, None)
+        } else if case == b'*' {
+            let ttype = if match_sequence {
+                TokenType::StarEqual
+            } else {
+                TokenType::Star
+            };
+            self.add_token(ttype, None)
         } else if case == b'/' {
             if match_sequence {
                 while self.peek() != b'\n' && !self.is_at_end() {
                     let _ = self.advance();
                 }
+                let text = &self.source[self.start..self.current];
+                self.comments.push(Comment {
+                    text: String::from_utf8_lossy(text).trim_end().to_string(),
+                    line: self.line,
+                    token_index: self.tokens.len(),
+                });
+            } else if self.match_token(b'=') {
+                self.add_token(TokenType::SlashEqual, Some(LiteralType::Nil));
             } else {
                 self.add_token(TokenType::Slash, Some(LiteralType::Nil));
             };
@@ -173,19 +281,22 @@ impl Scanner {
     }
     pub fn add_token(&mut self, ttype: TokenType, literal: Option<LiteralType>) {
         if let Some(text) = self.source.get(self.start..self.current) {
+            let position = self.position(self.start);
+            self.positions.push(position);
             self.tokens.push(Token {
                 ttype,
-                lexeme: text.to_vec(),
+                lexeme: String::from_utf8(text.to_vec()).unwrap(),
                 literal,
                 line: self.line,
+                column: position.1,
             });
         }
     }
     pub fn peek(&self) -> u8 {
         if self.is_at_end() {
-            return b'\0';
+            b'\0'
         } else {
-            return self.source[self.current];
+            self.source[self.current]
         }
     }
     pub fn number(&mut self) {
@@ -216,7 +327,8 @@ impl Scanner {
             self.advance();
         }
         if self.is_at_end() {
-            Scanner::error(&self.line, "Unterminated string");
+            self.error("Unterminated string");
+            return;
         }
 
         self.advance();
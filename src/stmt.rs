@@ -10,6 +10,12 @@ pub enum StmtType {
     Print(Print),
     Return(Return),
     While(While),
+    For(For),
+    Throw(Throw),
+    Try(Try),
+    Import(Import),
+    Test(Test),
+    Debugger(Debugger),
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +30,7 @@ pub struct Function {
 }
 #[derive(Debug, Clone)]
 pub struct If {
+    pub keyword: Token,
     pub condition: Box<ExpressionType>,
     pub then_branch: Box<StmtType>,
     pub else_branch: Option<Box<StmtType>>,
@@ -39,15 +46,54 @@ pub struct Var {
 }
 #[derive(Debug, Clone)]
 pub struct Print {
+    pub keyword: Token,
     pub expression: ExpressionType,
 }
 #[derive(Debug, Clone)]
 pub struct Return {
     pub keyword: Token,
-    pub value: ExpressionType,
+    pub value: Option<ExpressionType>,
 }
 #[derive(Debug, Clone)]
 pub struct While {
+    pub keyword: Token,
     pub condition: ExpressionType,
     pub body: Box<StmtType>,
 }
+#[derive(Debug, Clone)]
+pub struct For {
+    pub keyword: Token,
+    pub initializer: Option<Box<StmtType>>,
+    pub condition: Option<ExpressionType>,
+    pub increment: Option<ExpressionType>,
+    pub body: Box<StmtType>,
+}
+#[derive(Debug, Clone)]
+pub struct Throw {
+    pub keyword: Token,
+    pub value: ExpressionType,
+}
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub body: Vec<StmtType>,
+    pub catch_name: Option<Token>,
+    pub catch_body: Option<Vec<StmtType>>,
+    pub finally_body: Option<Vec<StmtType>>,
+}
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub keyword: Token,
+    pub path: Token,
+    pub alias: Option<Token>,
+    pub names: Vec<Token>,
+}
+#[derive(Debug, Clone)]
+pub struct Test {
+    pub keyword: Token,
+    pub description: Token,
+    pub body: Vec<StmtType>,
+}
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    pub keyword: Token,
+}
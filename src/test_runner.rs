@@ -0,0 +1,210 @@
+use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+
+use crate::{
+    environment::{BindableValue, Environment},
+    interpreter::Interpreter,
+    lox::{self, Lox},
+    lox_function::LoxFunction,
+    lox_std::map::LoxMap,
+    stmt::{StmtType, Test},
+    token_type::LiteralType,
+};
+
+/// Runs every top-level `test` block in the given files and directories
+/// and returns the process exit code.
+///
+/// Each file gets a fresh interpreter. Its other top-level statements run
+/// once, before its tests, and the tests then run in source order, each in
+/// a scope of its own. Every test starts from the globals as the setup
+/// left them: what an earlier test assigned, or pushed into a list, is
+/// undone first.
+pub fn run(paths: &[String]) -> i32 {
+    if paths.is_empty() {
+        println!("Usage: jlox test <files/dirs>");
+        return 64;
+    }
+
+    let files = lox::source_files(paths);
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for file in files {
+        let display = file.display().to_string();
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                println!("ERROR {}: {}", display, error);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let mut lox = Lox::default();
+        let statements = lox.parse(source);
+        if lox.had_error {
+            println!("ERROR {}: could not be parsed", display);
+            failed += 1;
+            continue;
+        }
+
+        let (tests, setup): (Vec<StmtType>, Vec<StmtType>) = statements
+            .into_iter()
+            .partition(|statement| matches!(statement, StmtType::Test(_)));
+
+        let mut interpreter = Interpreter::for_script(&display, Vec::new());
+        if let Err(error) = interpreter.run_setup(setup) {
+            println!("ERROR {}: setup failed", display);
+            println!("    {}:{}: {}", display, error.token.line, error.message);
+            failed += 1;
+            continue;
+        }
+        let setup_globals =
+            copy_globals(&interpreter.globals, &interpreter.globals.borrow().values);
+
+        for test in tests {
+            let StmtType::Test(test) = test else {
+                unreachable!()
+            };
+            let description = description(&test);
+            let globals = copy_globals(&interpreter.globals, &setup_globals);
+            interpreter.globals.borrow_mut().values = globals;
+
+            match interpreter.run_test(test) {
+                Ok(()) => {
+                    println!("PASS {}: {}", display, description);
+                    passed += 1;
+                }
+                Err(error) => {
+                    println!("FAIL {}: {}", display, description);
+                    println!("    {}:{}: {}", display, error.token.line, error.message);
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!();
+    println!("{} passed, {} failed", passed, failed);
+
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+fn description(test: &Test) -> String {
+    match &test.description.literal {
+        Some(LiteralType::String(description)) => description.clone(),
+        _ => test.description.lexeme.clone(),
+    }
+}
+
+// Deep copies of the globals' `values`, so a test cannot change what the
+// next one starts from. Lists, maps and the environments that functions
+// close over are copied too, each once, so values that were shared stay
+// shared. Functions declared in `globals` keep running in it, and
+// modules are left as they are.
+fn copy_globals(
+    globals: &Rc<RefCell<Environment>>,
+    values: &HashMap<String, BindableValue>,
+) -> HashMap<String, BindableValue> {
+    let mut copier = Copier {
+        globals: globals.clone(),
+        copies: HashMap::new(),
+        environments: HashMap::new(),
+    };
+    values
+        .iter()
+        .map(|(name, value)| (name.clone(), copier.value(value)))
+        .collect()
+}
+
+struct Copier {
+    globals: Rc<RefCell<Environment>>,
+    // The copy made of each list or map, and of each environment, by
+    // address.
+    copies: HashMap<*const (), BindableValue>,
+    environments: HashMap<*const (), Rc<RefCell<Environment>>>,
+}
+
+impl Copier {
+    fn value(&mut self, value: &BindableValue) -> BindableValue {
+        match value {
+            BindableValue::List(list) => {
+                let key = Rc::as_ptr(list) as *const ();
+                if let Some(copy) = self.copies.get(&key) {
+                    return copy.clone();
+                }
+                let copy = Rc::new(RefCell::new(Vec::new()));
+                self.copies.insert(key, BindableValue::List(copy.clone()));
+                let items: Vec<BindableValue> =
+                    list.borrow().iter().map(|item| self.value(item)).collect();
+                *copy.borrow_mut() = items;
+                BindableValue::List(copy)
+            }
+            BindableValue::Map(map) => {
+                let key = Rc::as_ptr(map) as *const ();
+                if let Some(copy) = self.copies.get(&key) {
+                    return copy.clone();
+                }
+                let copy = Rc::new(RefCell::new(LoxMap::new()));
+                self.copies.insert(key, BindableValue::Map(copy.clone()));
+                let entries: LoxMap = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| (key.clone(), self.value(value)))
+                    .collect();
+                *copy.borrow_mut() = entries;
+                BindableValue::Map(copy)
+            }
+            BindableValue::Function(function) => BindableValue::Function(LoxFunction {
+                declaration: function.declaration.clone(),
+                closure: self.environment(&function.closure),
+                file: function.file.clone(),
+            }),
+            _ => value.clone(),
+        }
+    }
+
+    fn environment(&mut self, environment: &Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+        // A module's environments belong to the module, which stays loaded.
+        if Rc::ptr_eq(environment, &self.globals) || !self.in_globals(environment) {
+            return environment.clone();
+        }
+        let key = Rc::as_ptr(environment) as *const ();
+        if let Some(copy) = self.environments.get(&key) {
+            return copy.clone();
+        }
+        let copy = Rc::new(RefCell::new(Environment {
+            enclosing: None,
+            values: HashMap::new(),
+        }));
+        self.environments.insert(key, copy.clone());
+        let enclosing = environment
+            .borrow()
+            .enclosing
+            .as_ref()
+            .map(|enclosing| self.environment(enclosing));
+        let values = environment
+            .borrow()
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), self.value(value)))
+            .collect();
+        *copy.borrow_mut() = Environment { enclosing, values };
+        copy
+    }
+
+    fn in_globals(&self, environment: &Rc<RefCell<Environment>>) -> bool {
+        let mut current = environment.clone();
+        loop {
+            let enclosing = current.borrow().enclosing.clone();
+            match enclosing {
+                Some(enclosing) => current = enclosing,
+                None => return Rc::ptr_eq(&current, &self.globals),
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+
+use crate::{
+    json::JsonValue,
+    scanner::{ScanError, Scanner},
+    token_type::{LiteralType, Token},
+};
+
+/// `jlox tokens [--json] <file>`: prints every token the scanner produces
+/// with the line and column it starts at, its type, lexeme and literal,
+/// one per line. Scanner errors are printed in place, between the tokens
+/// around them. With `--json` each line is a JSON object instead: tokens
+/// have `type`, `lexeme`, `line` and `column` members and a `literal`
+/// member when the token carries one, errors have `error`, `line` and
+/// `column`.
+pub fn run(args: &[String]) -> i32 {
+    let json = args.iter().any(|arg| arg == "--json");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--json").collect();
+
+    let [path] = paths.as_slice() else {
+        println!("Usage: jlox tokens [--json] <file>");
+        return 64;
+    };
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            return 74;
+        }
+    };
+
+    let mut scanner = Scanner {
+        source: Vec::new(),
+        tokens: Vec::new(),
+        comments: Vec::new(),
+        positions: Vec::new(),
+        errors: Vec::new(),
+        start: 0,
+        current: 0,
+        line: 1,
+    };
+    let tokens = scanner.scan_tokens(source);
+
+    let mut errors = scanner.errors.iter().peekable();
+    for (index, token) in tokens.iter().enumerate() {
+        while let Some(error) = errors.next_if(|error| error.token_index <= index) {
+            println!("{}", if json { error_json(error) } else { error_line(error) });
+        }
+        let position = scanner.positions[index];
+        println!(
+            "{}",
+            if json { token_json(token, position) } else { token_line(token, position) }
+        );
+    }
+
+    if scanner.errors.is_empty() {
+        0
+    } else {
+        65
+    }
+}
+
+// Newlines in multi-line strings are shown as `\n` to keep one token per line.
+fn token_line(token: &Token, (line, column): (u32, u32)) -> String {
+    let position = format!("{}:{}", line, column);
+    let lexeme = token.lexeme.replace('\n', "\\n");
+    let literal = match &token.literal {
+        Some(LiteralType::String(value)) => format!("\"{}\"", value.replace('\n', "\\n")),
+        Some(LiteralType::F64(value)) => value.to_string(),
+        Some(LiteralType::Bool(value)) => value.to_string(),
+        Some(LiteralType::Nil) => "nil".to_string(),
+        None => String::new(),
+    };
+    format!("{:<8} {:<16} {:<16} {}", position, format!("{:?}", token.ttype), lexeme, literal)
+        .trim_end()
+        .to_string()
+}
+
+fn error_line(error: &ScanError) -> String {
+    let position = format!("{}:{}", error.line, error.column);
+    format!("{:<8} error: {}", position, error.message)
+}
+
+fn token_json(token: &Token, (line, column): (u32, u32)) -> String {
+    let mut members = Vec::from([
+        ("type".to_string(), JsonValue::String(format!("{:?}", token.ttype))),
+        ("lexeme".to_string(), JsonValue::String(token.lexeme.clone())),
+    ]);
+    if let Some(literal) = &token.literal {
+        let literal = match literal {
+            LiteralType::String(value) => JsonValue::String(value.clone()),
+            LiteralType::F64(value) => JsonValue::Number(*value),
+            LiteralType::Bool(value) => JsonValue::Bool(*value),
+            LiteralType::Nil => JsonValue::Null,
+        };
+        members.push(("literal".to_string(), literal));
+    }
+    members.push(("line".to_string(), JsonValue::Number(line as f64)));
+    members.push(("column".to_string(), JsonValue::Number(column as f64)));
+    write_line(JsonValue::Object(members))
+}
+
+fn error_json(error: &ScanError) -> String {
+    write_line(JsonValue::Object(Vec::from([
+        ("error".to_string(), JsonValue::String(error.message.clone())),
+        ("line".to_string(), JsonValue::Number(error.line as f64)),
+        ("column".to_string(), JsonValue::Number(error.column as f64)),
+    ])))
+}
+
+// Token values are always finite, so writing cannot fail.
+fn write_line(json: JsonValue) -> String {
+    json.write(None).unwrap_or_default()
+}
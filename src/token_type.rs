@@ -1,5 +1,3 @@
-use crate::interpreter::{Interpreter, RuntimeError};
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     //single-character tokens
@@ -14,6 +12,8 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Question,
+    Colon,
     //one Or Two Character Tokens
     Bang,
     BangEqual,
@@ -23,6 +23,14 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PlusPlus,
+    MinusMinus,
+    QuestionQuestion,
+    QuestionDot,
     //literals
     Identifier,
     String,
@@ -44,6 +52,15 @@ pub enum TokenType {
     True,
     Var,
     While,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Import,
+    From,
+    As,
+    Test,
+    Debugger,
     Eof,
 }
 
@@ -58,7 +75,10 @@ pub enum LiteralType {
 #[derive(Debug, Clone)]
 pub struct Token {
     pub ttype: TokenType,
-    pub lexeme: Vec<u8>,
+    pub lexeme: String,
     pub literal: Option<LiteralType>,
     pub line: u32,
+    /// The 1-based column the token starts at, or 0 for tokens that were
+    /// not scanned from source.
+    pub column: u32,
 }
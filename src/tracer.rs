@@ -0,0 +1,123 @@
+//! The execution trace behind `jlox --trace`.
+//!
+//! `Tracer` is a hook that logs every statement as it starts and every call
+//! as it starts and finishes, one line each, indented by call depth:
+//!
+//! ```text
+//! [line 7] expression
+//! [line 7] call square(1)
+//!   [line 2] var result
+//!   [line 3] return
+//! [line 7] square returned 1
+//! ```
+//!
+//! Given function names, it only logs while one of them is on the call
+//! stack, including their own calls and returns. Lines are those of the
+//! file the statement is in, which for imported modules is the module.
+
+use std::io::Write;
+
+use crate::{
+    debugger,
+    environment::BindableValue,
+    hook::Hook,
+    interpreter::{ErrorKind, Interpreter, RuntimeError},
+    stmt::StmtType,
+};
+
+pub struct Tracer<W: Write> {
+    output: W,
+    functions: Vec<String>,
+}
+
+impl<W: Write> Tracer<W> {
+    /// A tracer writing to `output`, limited to calls of `functions` unless
+    /// that is empty.
+    pub fn new(output: W, functions: Vec<String>) -> Tracer<W> {
+        Tracer { output, functions }
+    }
+
+    fn traced(&self, interpreter: &Interpreter) -> bool {
+        self.functions.is_empty()
+            || interpreter
+                .frames
+                .iter()
+                .any(|frame| self.functions.contains(&frame.function))
+    }
+
+    fn log(&mut self, depth: usize, line: u32, text: &str) {
+        let _ = writeln!(
+            self.output,
+            "{}[line {}] {}",
+            "  ".repeat(depth),
+            line,
+            text
+        );
+    }
+}
+
+impl<W: Write> Hook for Tracer<W> {
+    fn statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        stmt: &StmtType,
+        line: u32,
+    ) -> Result<(), RuntimeError> {
+        if self.traced(interpreter) {
+            self.log(interpreter.frames.len(), line, &describe_stmt(stmt));
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, interpreter: &mut Interpreter, arguments: &[BindableValue]) {
+        if !self.traced(interpreter) {
+            return;
+        }
+        let frame = interpreter.frames.last().unwrap();
+        let arguments: Vec<String> = arguments.iter().map(debugger::describe).collect();
+        let text = format!("call {}({})", frame.function, arguments.join(", "));
+        self.log(interpreter.frames.len() - 1, frame.line, &text);
+    }
+
+    fn call_finished(
+        &mut self,
+        interpreter: &mut Interpreter,
+        result: &Result<BindableValue, RuntimeError>,
+    ) {
+        if !self.traced(interpreter) {
+            return;
+        }
+        let frame = interpreter.frames.last().unwrap();
+        let text = match result {
+            Ok(value) => format!("{} returned {}", frame.function, debugger::describe(value)),
+            Err(error) => match &error.kind {
+                ErrorKind::Throw(value) => {
+                    format!("{} threw {}", frame.function, debugger::describe(value))
+                }
+                ErrorKind::Exit(code) => format!("{} exited with {}", frame.function, code),
+                _ => format!("{} failed: {}", frame.function, error.message),
+            },
+        };
+        self.log(interpreter.frames.len() - 1, frame.line, &text);
+    }
+}
+
+/// A statement's kind, and its name if it declares one.
+fn describe_stmt(stmt: &StmtType) -> String {
+    match stmt {
+        StmtType::Expression(_) => "expression".to_string(),
+        StmtType::Print(_) => "print".to_string(),
+        StmtType::Var(stmt) => format!("var {}", stmt.name.lexeme),
+        StmtType::Block(_) => "block".to_string(),
+        StmtType::If(_) => "if".to_string(),
+        StmtType::While(_) => "while".to_string(),
+        StmtType::For(_) => "for".to_string(),
+        StmtType::Function(stmt) => format!("fun {}", stmt.name.lexeme),
+        StmtType::Return(_) => "return".to_string(),
+        StmtType::Throw(_) => "throw".to_string(),
+        StmtType::Try(_) => "try".to_string(),
+        StmtType::Import(stmt) => format!("import {}", stmt.path.lexeme),
+        StmtType::Test(stmt) => format!("test {}", stmt.description.lexeme),
+        StmtType::Debugger(_) => "debugger".to_string(),
+    }
+}
@@ -0,0 +1,64 @@
+//! Checks the Graphviz rendering of the syntax tree on a precedence example.
+
+use jlox_rustport::{ast_dot, lox::Lox};
+
+#[test]
+fn precedence_graph() {
+    let mut lox = Lox::default();
+    let statements = lox.parse("print 1 + 2 * 3 == \"a\";".to_string());
+    assert!(!lox.had_error);
+
+    let expected = r#"digraph ast {
+  node [shape=box, fontname="monospace"];
+  edge [fontname="monospace", fontsize=10];
+  n0 [label="program"];
+  n1 [label="Print"];
+  n0 -> n1 [label="statements[0]"];
+  n2 [label="Binary =="];
+  n1 -> n2 [label="expression"];
+  n3 [label="Binary +"];
+  n2 -> n3 [label="left"];
+  n4 [label="Literal 1"];
+  n3 -> n4 [label="left"];
+  n5 [label="Binary *"];
+  n3 -> n5 [label="right"];
+  n6 [label="Literal 2"];
+  n5 -> n6 [label="left"];
+  n7 [label="Literal 3"];
+  n5 -> n7 [label="right"];
+  n8 [label="Literal \"a\""];
+  n2 -> n8 [label="right"];
+}
+"#;
+    assert_eq!(ast_dot::to_dot(&statements), expected);
+}
+
+#[test]
+fn branches_and_escaping() {
+    let mut lox = Lox::default();
+    let statements =
+        lox.parse("fun f(a, b) { if (a) return; else print \"x\\y\nz\"; }".to_string());
+    assert!(!lox.had_error);
+
+    // The bare return has no value edge, and the backslash and newline in
+    // the string are escaped inside the label.
+    let expected = r#"digraph ast {
+  node [shape=box, fontname="monospace"];
+  edge [fontname="monospace", fontsize=10];
+  n0 [label="program"];
+  n1 [label="Function f(a, b)"];
+  n0 -> n1 [label="statements[0]"];
+  n2 [label="If"];
+  n1 -> n2 [label="body[0]"];
+  n3 [label="Variable a"];
+  n2 -> n3 [label="condition"];
+  n4 [label="Return"];
+  n2 -> n4 [label="then_branch"];
+  n5 [label="Print"];
+  n2 -> n5 [label="else_branch"];
+  n6 [label="Literal \"x\\y\nz\""];
+  n5 -> n6 [label="expression"];
+}
+"#;
+    assert_eq!(ast_dot::to_dot(&statements), expected);
+}
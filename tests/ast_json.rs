@@ -0,0 +1,108 @@
+//! Round-trip tests for the JSON syntax tree: every snapshot source must
+//! survive `to_json`, writing, parsing and `from_json` unchanged, as seen
+//! through `AstPrinter`.
+
+use std::{fs, path::PathBuf};
+
+use jlox_rustport::{ast_json, ast_printer::AstPrinter, json::JsonValue, lox::Lox};
+
+#[test]
+fn json_round_trip() {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/ast");
+
+    let mut sources: Vec<PathBuf> = fs::read_dir(&directory)
+        .expect("snapshot directory exists")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lox"))
+        .collect();
+    sources.sort();
+    assert!(!sources.is_empty(), "no snapshot sources in {}", directory.display());
+
+    for source in sources {
+        let mut lox = Lox::default();
+        let statements = lox.parse(fs::read_to_string(&source).unwrap());
+        assert!(!lox.had_error, "{} does not parse", source.display());
+
+        let text = ast_json::to_json(&statements).write(Some("  ")).unwrap();
+        let loaded = JsonValue::parse(&text)
+            .and_then(|json| ast_json::from_json(&json))
+            .unwrap_or_else(|message| panic!("{}: {}", source.display(), message));
+
+        assert_eq!(
+            AstPrinter::print_program(&loaded),
+            AstPrinter::print_program(&statements),
+            "{} changed in the round trip",
+            source.display()
+        );
+    }
+}
+
+#[test]
+fn loader_errors() {
+    let load = |text: &str| ast_json::from_json(&JsonValue::parse(text).unwrap()).unwrap_err();
+
+    assert_eq!(load(r#"{"statements": []}"#), "Program is missing 'version'.");
+    assert_eq!(
+        load(r#"{"version": 2, "statements": [{"kind": "Print"}]}"#),
+        "Print node is missing 'keyword'."
+    );
+    assert_eq!(
+        load(r#"{"version": 3, "statements": []}"#),
+        "Unsupported AST version; expected 1 or 2."
+    );
+    assert_eq!(
+        load(r#"{"version": 2, "statements": [{"kind": "Nope"}]}"#),
+        "Unknown statement kind 'Nope'."
+    );
+    assert_eq!(
+        load(
+            r#"{"version": 2, "statements": [{"kind": "Var",
+                "name": {"lexeme": "a b", "line": 1}, "initializer": null}]}"#
+        ),
+        "Field 'name' has 'a b', which is not a single token."
+    );
+}
+
+#[test]
+fn deep_nesting_is_a_parse_error() {
+    let text = format!("{{\"version\": 2,\n\"statements\": {}", "[".repeat(100_000));
+    assert_eq!(
+        JsonValue::parse(&text).unwrap_err(),
+        "found arrays or objects nested more than 512 deep at line 2, column 526."
+    );
+}
+
+#[test]
+fn version_1_loads_without_keywords() {
+    let json = JsonValue::parse(
+        r#"{"version": 1, "statements": [
+            {"kind": "While",
+             "condition": {"kind": "Literal", "value": false},
+             "body": {"kind": "Print",
+                      "expression": {"kind": "Variable",
+                                     "name": {"lexeme": "a", "line": 3}}}}]}"#,
+    )
+    .unwrap();
+    let statements = ast_json::from_json(&json).unwrap();
+
+    let mut lox = Lox::default();
+    let expected = lox.parse("while (false) print a;".to_string());
+    assert_eq!(
+        AstPrinter::print_program(&statements),
+        AstPrinter::print_program(&expected)
+    );
+    let keyword_line = |json: JsonValue| {
+        json.get("statements")
+            .and_then(|statements| match statements {
+                JsonValue::Array(items) => items.first().cloned(),
+                _ => None,
+            })
+            .and_then(|stmt| stmt.get("body").cloned())
+            .and_then(|body| body.get("keyword").cloned())
+            .and_then(|keyword| keyword.get("line").cloned())
+    };
+    assert_eq!(
+        keyword_line(ast_json::to_json(&statements)),
+        Some(JsonValue::Number(3.0))
+    );
+}
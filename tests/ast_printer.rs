@@ -0,0 +1,44 @@
+//! Snapshot tests for `AstPrinter`: every `tests/snapshots/ast/*.lox` file
+//! is parsed and printed, and the result compared with the `.ast` file next
+//! to it. Run with `UPDATE_SNAPSHOTS=1` to rewrite the snapshots after a
+//! deliberate grammar or printer change, then review the diff.
+
+use std::{env, fs, path::PathBuf};
+
+use jlox_rustport::{ast_printer::AstPrinter, lox::Lox};
+
+#[test]
+fn ast_snapshots() {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/ast");
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+
+    let mut sources: Vec<PathBuf> = fs::read_dir(&directory)
+        .expect("snapshot directory exists")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lox"))
+        .collect();
+    sources.sort();
+    assert!(!sources.is_empty(), "no snapshot sources in {}", directory.display());
+
+    let mut mismatches = Vec::new();
+    for source in sources {
+        let mut lox = Lox::default();
+        let statements = lox.parse(fs::read_to_string(&source).unwrap());
+        assert!(!lox.had_error, "{} does not parse", source.display());
+
+        let printed = AstPrinter::print_program(&statements);
+        let snapshot = source.with_extension("ast");
+
+        if update {
+            fs::write(&snapshot, &printed).unwrap();
+        } else if fs::read_to_string(&snapshot).ok().as_deref() != Some(printed.as_str()) {
+            mismatches.push(format!("--- {}\n{}", snapshot.display(), printed));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "AST snapshots differ (rerun with UPDATE_SNAPSHOTS=1 to accept):\n{}",
+        mismatches.join("\n")
+    );
+}
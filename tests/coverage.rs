@@ -0,0 +1,125 @@
+//! Runs `jlox --coverage` on the scripts in `tests/coverage` and checks the
+//! lcov records and the annotated source.
+
+use std::{env, fs, process::Command};
+
+const PROGRAM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/coverage/branches.lox");
+
+#[test]
+fn writes_lcov_and_annotated_source() {
+    let base = env::temp_dir().join(format!("jlox-coverage-{}", std::process::id()));
+    let lcov_path = base.with_extension("lcov");
+    let annotate_path = base.with_extension("txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg("--coverage")
+        .arg(&lcov_path)
+        .arg("--annotate")
+        .arg(&annotate_path)
+        .arg(PROGRAM)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "0\n1\n1\n0\n1\n");
+
+    let lcov = fs::read_to_string(&lcov_path).unwrap();
+    let annotated = fs::read_to_string(&annotate_path).unwrap();
+    fs::remove_file(&lcov_path).unwrap();
+    fs::remove_file(&annotate_path).unwrap();
+
+    let source_path = fs::canonicalize(PROGRAM).unwrap();
+    assert_eq!(
+        lcov,
+        format!(
+            "\
+TN:
+SF:{}
+BRDA:2,0,0,0
+BRDA:2,0,1,3
+BRDA:4,0,0,1
+BRDA:4,0,1,2
+BRDA:8,0,0,3
+BRDA:8,0,1,1
+BRDA:8,1,0,3
+BRDA:8,1,1,1
+BRDA:12,0,0,1
+BRDA:12,0,1,0
+BRDA:13,0,0,2
+BRDA:13,0,1,1
+BRF:12
+BRH:10
+DA:1,1
+DA:2,3
+DA:3,0
+DA:4,3
+DA:5,2
+DA:7,1
+DA:8,1
+DA:9,3
+DA:10,3
+DA:12,1
+DA:13,2
+LF:11
+LH:10
+end_of_record
+",
+            source_path.display()
+        )
+    );
+    assert_eq!(
+        annotated,
+        "        1      1 | fun sign(n) {
+        3!     2 |     if (n < 0) {
+    #####      3 |         return -1;
+        3      4 |     } else if (n == 0) return 0;
+        2      5 |     return 1;
+               6 | }
+        1      7 | var i = 0;
+        1      8 | while (i < 3 and true) {
+        3      9 |     print sign(i);
+        3     10 |     i = i + 1;
+              11 | }
+        1!    12 | var unused = nil ?? \"x\";
+        2     13 | for (var j = 0; j < 2; j++) print j;
+"
+    );
+}
+
+// failing.lox calls a module function whose `if` is at the same line and
+// column as the script's `fun never`, then stops with a runtime error.
+#[test]
+fn ignores_module_code_and_writes_coverage_after_an_error() {
+    let program = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/coverage/failing.lox");
+    let lcov_path =
+        env::temp_dir().join(format!("jlox-coverage-{}-failing.lcov", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg("--coverage")
+        .arg(&lcov_path)
+        .arg(program)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "4\n");
+
+    let lcov = fs::read_to_string(&lcov_path).unwrap();
+    fs::remove_file(&lcov_path).unwrap();
+    let records: Vec<&str> = lcov.lines().skip(2).collect();
+    // A branch point that never ran is "-", not 0, in lcov.
+    assert_eq!(
+        records,
+        [
+            "BRDA:3,0,0,-",
+            "BRDA:3,0,1,-",
+            "BRF:2",
+            "BRH:0",
+            "DA:1,1",
+            "DA:2,1",
+            "DA:3,0",
+            "DA:5,1",
+            "DA:6,1",
+            "DA:7,0",
+            "LF:6",
+            "LH:4",
+            "end_of_record",
+        ]
+    );
+}
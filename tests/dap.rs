@@ -0,0 +1,293 @@
+//! Runs `jlox dap` on `tests/debugger/squares.lox` with a scripted session
+//! on stdin and checks the messages it sends back.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use jlox_rustport::json::JsonValue;
+
+const PROGRAM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/debugger/squares.lox");
+
+fn session(requests: &[(&str, String)]) -> Vec<JsonValue> {
+    let mut input = String::new();
+    for (seq, (command, arguments)) in requests.iter().enumerate() {
+        let body = format!(
+            "{{\"seq\":{},\"type\":\"request\",\"command\":\"{}\",\"arguments\":{}}}",
+            seq + 1,
+            command,
+            arguments
+        );
+        input.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+    }
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg("dap")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = String::from_utf8(output.stdout).unwrap();
+    let mut messages = Vec::new();
+    for part in output.split("Content-Length: ").skip(1) {
+        let (_, body) = part.split_once("\r\n\r\n").unwrap();
+        messages.push(JsonValue::parse(body).unwrap());
+    }
+    messages
+}
+
+fn string(value: Option<&JsonValue>) -> String {
+    match value {
+        Some(JsonValue::String(value)) => value.clone(),
+        other => panic!("expected a string, got {:?}", other),
+    }
+}
+
+// One line per message: responses by command, events by name and reason.
+fn summary(messages: &[JsonValue]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|message| match string(message.get("type")).as_str() {
+            "response" => format!("response {}", string(message.get("command"))),
+            _ => {
+                let event = string(message.get("event"));
+                match message.get("body").and_then(|body| body.get("reason")) {
+                    Some(reason) => format!("event {} {}", event, string(Some(reason))),
+                    None => event,
+                }
+            }
+        })
+        .collect()
+}
+
+fn response<'a>(messages: &'a [JsonValue], command: &str) -> &'a JsonValue {
+    messages
+        .iter()
+        .find(|message| message.get("command") == Some(&JsonValue::String(command.to_string())))
+        .and_then(|message| message.get("body"))
+        .unwrap()
+}
+
+fn launch(stop_on_entry: bool) -> Vec<(&'static str, String)> {
+    Vec::from([
+        ("initialize", "{\"adapterID\":\"jlox\"}".to_string()),
+        (
+            "launch",
+            format!(
+                "{{\"program\":\"{}\",\"stopOnEntry\":{}}}",
+                PROGRAM, stop_on_entry
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn breakpoints_stack_and_variables() {
+    let mut requests = launch(false);
+    requests.extend([
+        (
+            "setBreakpoints",
+            format!(
+                "{{\"source\":{{\"path\":\"{}\"}},\"breakpoints\":[{{\"line\":3}}]}}",
+                PROGRAM
+            ),
+        ),
+        ("configurationDone", "{}".to_string()),
+        ("stackTrace", "{\"threadId\":1}".to_string()),
+        ("scopes", "{\"frameId\":0}".to_string()),
+        ("variables", "{\"variablesReference\":1}".to_string()),
+        (
+            "evaluate",
+            "{\"expression\":\"total\",\"frameId\":1}".to_string(),
+        ),
+        (
+            "setBreakpoints",
+            format!(
+                "{{\"source\":{{\"path\":\"{}\"}},\"breakpoints\":[]}}",
+                PROGRAM
+            ),
+        ),
+        ("continue", "{\"threadId\":1}".to_string()),
+        ("continue", "{\"threadId\":1}".to_string()),
+        ("disconnect", "{}".to_string()),
+    ]);
+    let messages = session(&requests);
+
+    assert_eq!(
+        summary(&messages),
+        [
+            "response initialize",
+            "initialized",
+            "response launch",
+            "response setBreakpoints",
+            "response configurationDone",
+            "event stopped breakpoint",
+            "response stackTrace",
+            "response scopes",
+            "response variables",
+            "response evaluate",
+            "response setBreakpoints",
+            "response continue",
+            "event stopped pause",
+            "response continue",
+            "output",
+            "exited",
+            "terminated",
+            "response disconnect",
+        ]
+    );
+
+    let frames = response(&messages, "stackTrace")
+        .get("stackFrames")
+        .unwrap();
+    let JsonValue::Array(frames) = frames else {
+        panic!()
+    };
+    let frames: Vec<(String, Option<&JsonValue>)> = frames
+        .iter()
+        .map(|frame| (string(frame.get("name")), frame.get("line")))
+        .collect();
+    assert_eq!(
+        frames,
+        [
+            ("square".to_string(), Some(&JsonValue::Number(3.0))),
+            ("<script>".to_string(), Some(&JsonValue::Number(7.0))),
+        ]
+    );
+
+    let JsonValue::Array(variables) = response(&messages, "variables").get("variables").unwrap()
+    else {
+        panic!()
+    };
+    let variables: Vec<String> = variables
+        .iter()
+        .map(|variable| {
+            format!(
+                "{} = {}",
+                string(variable.get("name")),
+                string(variable.get("value"))
+            )
+        })
+        .collect();
+    assert_eq!(variables, ["n = 1", "result = 1"]);
+
+    assert_eq!(string(response(&messages, "evaluate").get("result")), "0");
+    let output = messages
+        .iter()
+        .find(|message| message.get("event") == Some(&JsonValue::String("output".to_string())));
+    assert_eq!(
+        string(output.unwrap().get("body").unwrap().get("output")),
+        "14\n"
+    );
+}
+
+#[test]
+fn steps_from_entry_and_disconnects() {
+    let mut requests = launch(true);
+    requests.extend([
+        ("configurationDone", "{}".to_string()),
+        ("next", "{\"threadId\":1}".to_string()),
+        ("stepIn", "{\"threadId\":1}".to_string()),
+        ("stackTrace", "{\"threadId\":1}".to_string()),
+        ("disconnect", "{}".to_string()),
+    ]);
+    let messages = session(&requests);
+
+    assert_eq!(
+        summary(&messages),
+        [
+            "response initialize",
+            "initialized",
+            "response launch",
+            "response configurationDone",
+            "event stopped entry",
+            "response next",
+            "event stopped step",
+            "response stepIn",
+            "event stopped step",
+            "response stackTrace",
+            "response disconnect",
+        ]
+    );
+    let JsonValue::Array(frames) = response(&messages, "stackTrace")
+        .get("stackFrames")
+        .unwrap()
+    else {
+        panic!()
+    };
+    assert_eq!(frames[0].get("line"), Some(&JsonValue::Number(6.0)));
+}
+
+#[test]
+fn steps_out_and_reports_failed_requests() {
+    let mut requests = launch(false);
+    requests.extend([
+        (
+            "setBreakpoints",
+            format!(
+                "{{\"source\":{{\"path\":\"{}\"}},\"breakpoints\":[{{\"line\":2}}]}}",
+                PROGRAM
+            ),
+        ),
+        ("configurationDone", "{}".to_string()),
+        ("evaluate", "{\"expression\":\"missing\"}".to_string()),
+        ("pause", "{\"threadId\":1}".to_string()),
+        ("stepOut", "{\"threadId\":1}".to_string()),
+        ("stackTrace", "{\"threadId\":1}".to_string()),
+        ("disconnect", "{}".to_string()),
+    ]);
+    let messages = session(&requests);
+
+    assert_eq!(
+        summary(&messages),
+        [
+            "response initialize",
+            "initialized",
+            "response launch",
+            "response setBreakpoints",
+            "response configurationDone",
+            "event stopped breakpoint",
+            "response evaluate",
+            "response pause",
+            "response stepOut",
+            "event stopped step",
+            "response stackTrace",
+            "response disconnect",
+        ]
+    );
+
+    // A failed request is answered with success false and a message.
+    let failures: Vec<String> = messages
+        .iter()
+        .filter(|message| message.get("success") == Some(&JsonValue::Bool(false)))
+        .map(|message| string(message.get("message")))
+        .collect();
+    assert_eq!(
+        failures,
+        [
+            "'missing' is not a variable in this frame.",
+            "Unsupported request 'pause'.",
+        ]
+    );
+
+    // Stepping out of square finishes the call on line 7 and stops at the
+    // next statement of the caller: the loop body's block, on line 6.
+    let JsonValue::Array(frames) = response(&messages, "stackTrace")
+        .get("stackFrames")
+        .unwrap()
+    else {
+        panic!()
+    };
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].get("line"), Some(&JsonValue::Number(6.0)));
+}
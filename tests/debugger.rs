@@ -0,0 +1,72 @@
+//! Runs `jlox debug` on `tests/debugger/squares.lox` with a scripted
+//! session on stdin and checks the transcript.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+fn debug(commands: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .args(["debug", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/debugger/squares.lox")])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(commands.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn steps_into_and_out_of_calls() {
+    let transcript = debug("n\nn\nn\ns\nbt\nout\np total\nq\n");
+    assert_eq!(
+        transcript,
+        "\
+#0 <script> at line 1
+    1 | fun square(n) {
+(jlox) #0 <script> at line 5
+    5 | var total = 0;
+(jlox) #0 <script> at line 6
+    6 | for (var i = 1; i <= 3; i++) {
+(jlox) #0 <script> at line 7
+    7 |     total = total + square(i);
+(jlox) #0 square at line 2
+    2 |     var result = n * n;
+(jlox) *#0 square at line 2
+ #1 <script> at line 7
+(jlox) #0 <script> at line 6
+    6 | for (var i = 1; i <= 3; i++) {
+(jlox) total = 1
+(jlox) Program exited.
+"
+    );
+}
+
+#[test]
+fn breakpoints_and_debugger_statement() {
+    let transcript = debug("b 3\nc\np result\nup\np n\np i\nd 3\nc\n\n");
+    assert_eq!(
+        transcript,
+        "\
+#0 <script> at line 1
+    1 | fun square(n) {
+(jlox) Breakpoint set at line 3.
+(jlox) Breakpoint hit.
+#0 square at line 3
+    3 |     return result;
+(jlox) result = 1
+(jlox) #1 <script> at line 7
+    7 |     total = total + square(i);
+(jlox) No variable 'n' in scope.
+(jlox) i = 1
+(jlox) Breakpoint at line 3 deleted.
+(jlox) Paused at 'debugger'.
+#0 <script> at line 9
+    9 | debugger;
+(jlox) 14
+Program exited.
+"
+    );
+}
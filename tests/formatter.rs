@@ -0,0 +1,118 @@
+//! Formats every script in the test suite and checks the formatter is
+//! idempotent and leaves the program unchanged, then drives `jlox fmt` and
+//! `jlox fmt --check` on a badly formatted file.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use jlox_rustport::{ast_printer::AstPrinter, formatter, lox::Lox};
+
+fn sources(directory: &Path, found: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            sources(&path, found);
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            found.push(path);
+        }
+    }
+}
+
+#[test]
+fn formatting_is_idempotent_and_keeps_the_program() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut paths = Vec::new();
+    sources(&root.join("scripts"), &mut paths);
+    sources(&root.join("snapshots/ast"), &mut paths);
+    paths.sort();
+
+    let mut checked = 0;
+    for path in paths {
+        let source = fs::read_to_string(&path).unwrap();
+        // Scripts that test parse errors have nothing to format.
+        let mut lox = Lox::default();
+        lox.parse(source.clone());
+        if lox.had_error {
+            continue;
+        }
+        let name = path.strip_prefix(&root).unwrap().display();
+        let formatted =
+            formatter::format(&source).unwrap_or_else(|message| panic!("{}: {}", name, message));
+        assert_eq!(
+            formatter::format(&formatted).as_deref(),
+            Ok(formatted.as_str()),
+            "{} changes when formatted twice",
+            name
+        );
+
+        let print = |text: &str| {
+            let mut lox = Lox::default();
+            AstPrinter::print_program(&lox.parse(text.to_string()))
+        };
+        assert_eq!(
+            print(&formatted),
+            print(&source),
+            "{} parses differently",
+            name
+        );
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
+
+fn fmt(args: &[&str], directory: &Path) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg("fmt")
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn check_reports_files_until_they_are_formatted() {
+    let directory = env::temp_dir().join(format!("jlox-fmt-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(
+        directory.join("messy.lox"),
+        "var  a=1;\nif(a>0){print a;}// done\n",
+    )
+    .unwrap();
+    fs::write(directory.join("clean.lox"), "print 1;\n").unwrap();
+
+    let (code, stdout) = fmt(&["--check", "messy.lox", "clean.lox"], &directory);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "messy.lox is not formatted\n");
+    // --check leaves the file alone.
+    assert_eq!(
+        fs::read_to_string(directory.join("messy.lox")).unwrap(),
+        "var  a=1;\nif(a>0){print a;}// done\n"
+    );
+
+    assert_eq!(fmt(&["messy.lox"], &directory), (Some(0), String::new()));
+    assert_eq!(
+        fs::read_to_string(directory.join("messy.lox")).unwrap(),
+        "var a = 1;\nif (a > 0) {\n    print a;\n} // done\n"
+    );
+    assert_eq!(fmt(&["--check", "."], &directory), (Some(0), String::new()));
+
+    fs::write(directory.join("broken.lox"), "print (;\n").unwrap();
+    assert_eq!(fmt(&["--check", "broken.lox"], &directory).0, Some(65));
+
+    // The scanner drops the '@', but the rest would still parse.
+    fs::write(directory.join("unscannable.lox"), "var a = 1 @ ;\n").unwrap();
+    assert_eq!(fmt(&["unscannable.lox"], &directory).0, Some(65));
+    assert_eq!(
+        fs::read_to_string(directory.join("unscannable.lox")).unwrap(),
+        "var a = 1 @ ;\n"
+    );
+
+    let _ = fs::remove_dir_all(&directory);
+}
@@ -0,0 +1,199 @@
+//! Runs the scripts in `tests/limits` with resource limits and checks they
+//! stop cleanly, even inside `try`, with the limit named on stderr.
+
+use std::{
+    env,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+fn run(args: &[&str], script: &str) -> (Option<i32>, String, String) {
+    run_with_arguments(args, script, &[])
+}
+
+// Runs in the temporary directory, where scripts may write files.
+fn run_with_arguments(
+    args: &[&str],
+    script: &str,
+    script_args: &[&str],
+) -> (Option<i32>, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .args(args)
+        .arg(format!(
+            "{}/tests/limits/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            script
+        ))
+        .args(script_args)
+        .current_dir(env::temp_dir())
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn step_limit_is_not_caught() {
+    let (code, stdout, stderr) = run(&["--max-steps", "1000"], "loop.lox");
+    assert_eq!(code, Some(70));
+    assert_eq!(stdout, "");
+    assert_eq!(stderr, "Step limit of 1000 exceeded.\n[line: 1]\n");
+}
+
+#[test]
+fn timeout_stops_an_endless_loop() {
+    let (code, _, stderr) = run(&["--timeout", "0.1"], "loop.lox");
+    assert_eq!(code, Some(70));
+    assert_eq!(stderr, "Time limit of 0.1s exceeded.\n[line: 1]\n");
+}
+
+#[test]
+fn memory_limit_caps_string_size() {
+    let (code, _, stderr) = run(&["--max-memory", "1000"], "memory.lox");
+    assert_eq!(code, Some(70));
+    assert_eq!(
+        stderr,
+        "Memory limit of 1000 exceeded by a string of 1024 bytes.\n[line: 2]\n"
+    );
+}
+
+#[test]
+fn memory_limit_is_checked_before_allocating() {
+    for (native, message) in [
+        (
+            "repeat",
+            "Memory limit of 1000 exceeded by a string of 200000000000 bytes.",
+        ),
+        (
+            "push",
+            "Memory limit of 1000 exceeded by 1001 bytes and elements in use.",
+        ),
+        (
+            "readFile",
+            "Memory limit of 1000 exceeded by a string of 1200 bytes.",
+        ),
+        (
+            "replace",
+            "Memory limit of 1000 exceeded by a string of 6000 bytes.",
+        ),
+        (
+            "join",
+            "Memory limit of 1000 exceeded by a string of 1201 bytes.",
+        ),
+        (
+            "jsonStringify",
+            "Memory limit of 1000 exceeded by a string of 1207 bytes.",
+        ),
+    ] {
+        let (code, stdout, stderr) =
+            run_with_arguments(&["--max-memory", "1000"], "allocations.lox", &[native]);
+        assert_eq!(code, Some(70), "{}", native);
+        assert_eq!(stdout, "", "{}", native);
+        assert_eq!(stderr.lines().next(), Some(message), "{}", native);
+    }
+}
+
+#[test]
+fn memory_limit_caps_the_total_in_use() {
+    for (which, message) in [
+        (
+            "list",
+            "Memory limit of 1000 exceeded by 1012 bytes and elements in use.",
+        ),
+        (
+            "variables",
+            "Memory limit of 1000 exceeded by 1309 bytes and elements in use.",
+        ),
+    ] {
+        let (code, stdout, stderr) =
+            run_with_arguments(&["--max-memory", "1000"], "total.lox", &[which]);
+        assert_eq!(code, Some(70), "{}", which);
+        assert_eq!(stdout, "", "{}", which);
+        assert_eq!(stderr.lines().next(), Some(message), "{}", which);
+    }
+}
+
+#[test]
+fn timeout_interrupts_blocking_natives() {
+    for native in ["sleep", "run"] {
+        let start = Instant::now();
+        let (code, stdout, stderr) =
+            run_with_arguments(&["--timeout", "0.1"], "blocking.lox", &[native]);
+        assert!(start.elapsed() < Duration::from_secs(3), "{}", native);
+        assert_eq!(code, Some(70), "{}", native);
+        assert_eq!(stdout, "", "{}", native);
+        assert_eq!(stderr.lines().next(), Some("Time limit of 0.1s exceeded."), "{}", native);
+    }
+}
+
+#[test]
+fn timeout_interrupts_read_line() {
+    let start = Instant::now();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .args(["--timeout", "0.1"])
+        .arg(format!(
+            "{}/tests/limits/blocking.lox",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .arg("readLine")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Held open, so the script waits for input that never comes.
+    let _stdin = child.stdin.take();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(3));
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(output.stdout, b"");
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().lines().next(),
+        Some("Time limit of 0.1s exceeded.")
+    );
+}
+
+#[test]
+fn deep_recursion_hits_the_default_call_depth() {
+    let (code, stdout, stderr) = run(&[], "recursion.lox");
+    assert_eq!(code, Some(70));
+    assert_eq!(stdout, "900\n");
+    assert_eq!(stderr, "Call depth limit of 1000 exceeded.\n[line: 1]\n");
+
+    let (code, stdout, stderr) = run(&["--max-depth", "10"], "recursion.lox");
+    assert_eq!(code, Some(70));
+    assert_eq!(stdout, "");
+    assert_eq!(stderr, "Call depth limit of 10 exceeded.\n[line: 1]\n");
+}
+
+#[test]
+fn return_in_finally_does_not_swallow_a_limit() {
+    let (code, stdout, stderr) =
+        run_with_arguments(&["--max-depth", "10"], "finally.lox", &["depth"]);
+    assert_eq!(code, Some(70));
+    assert_eq!(stdout, "");
+    assert_eq!(
+        stderr.lines().next(),
+        Some("Call depth limit of 10 exceeded.")
+    );
+
+    let (code, stdout, stderr) =
+        run_with_arguments(&["--max-memory", "1000"], "finally.lox", &["memory"]);
+    assert_eq!(code, Some(70));
+    assert_eq!(stdout, "");
+    assert_eq!(
+        stderr.lines().next(),
+        Some("Memory limit of 1000 exceeded by a string of 1024 bytes.")
+    );
+}
+
+#[test]
+fn invalid_limit_is_a_usage_error() {
+    let (code, _, stderr) = run(&["--max-steps", "many"], "loop.lox");
+    assert_eq!(code, Some(64));
+    assert_eq!(stderr, "Invalid value 'many' for --max-steps.\n");
+}
@@ -0,0 +1,162 @@
+//! Drives the language server through a whole session over in-memory
+//! streams and checks its responses.
+
+use jlox_rustport::{json::JsonValue, lsp};
+
+const SOURCE: &str = "fun add(a, b) {\n    return a + b;\n}\nvar total = add(1, 2);\n{\n    var total = 3;\n    print total;\n}\nprint add(total, 1);\n";
+
+fn frame(message: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", message.len(), message)
+}
+
+fn request(id: u32, method: &str, params: &str) -> String {
+    frame(&format!(
+        r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{}}}"#,
+        id, method, params
+    ))
+}
+
+fn notification(method: &str, params: &str) -> String {
+    frame(&format!(r#"{{"jsonrpc":"2.0","method":"{}","params":{}}}"#, method, params))
+}
+
+fn position(line: u32, character: u32) -> String {
+    format!(
+        r#"{{"textDocument":{{"uri":"file:///a.lox"}},"position":{{"line":{},"character":{}}},"context":{{"includeDeclaration":true}}}}"#,
+        line, character
+    )
+}
+
+fn session(input: &str) -> (i32, Vec<JsonValue>) {
+    let mut output = Vec::new();
+    let code = lsp::serve(input.as_bytes(), &mut output);
+
+    let output = String::from_utf8(output).unwrap();
+    let mut messages = Vec::new();
+    let mut rest = output.as_str();
+    while let Some((header, body)) = rest.split_once("\r\n\r\n") {
+        let length: usize = header.trim_start_matches("Content-Length: ").parse().unwrap();
+        messages.push(JsonValue::parse(&body[..length]).unwrap());
+        rest = &body[length..];
+    }
+    (code, messages)
+}
+
+fn result(messages: &[JsonValue], id: f64) -> String {
+    let response = messages
+        .iter()
+        .find(|message| message.get("id") == Some(&JsonValue::Number(id)))
+        .expect("a response");
+    response.get("result").unwrap().write(None).unwrap()
+}
+
+fn range(line: u32, start: u32, end: u32) -> String {
+    format!(
+        r#"{{"start":{{"line":{},"character":{}}},"end":{{"line":{},"character":{}}}}}"#,
+        line, start, line, end
+    )
+}
+
+#[test]
+fn language_features() {
+    let open = format!(
+        r#"{{"textDocument":{{"uri":"file:///a.lox","languageId":"lox","version":1,"text":{}}}}}"#,
+        JsonValue::String(SOURCE.to_string()).write(None).unwrap()
+    );
+    let input = [
+        request(1, "initialize", "{}"),
+        notification("initialized", "{}"),
+        notification("textDocument/didOpen", &open),
+        request(2, "textDocument/definition", &position(8, 11)),
+        request(3, "textDocument/references", &position(1, 11)),
+        request(4, "textDocument/hover", &position(3, 13)),
+        request(5, "textDocument/documentSymbol", r#"{"textDocument":{"uri":"file:///a.lox"}}"#),
+        request(6, "textDocument/definition", &position(6, 11)),
+        request(7, "textDocument/formatting", "{}"),
+        request(8, "shutdown", "null"),
+        notification("exit", "null"),
+    ]
+    .concat();
+
+    let (code, messages) = session(&input);
+    assert_eq!(code, 0);
+
+    let diagnostics = messages
+        .iter()
+        .find(|message| message.get("method").is_some())
+        .unwrap();
+    assert_eq!(
+        diagnostics.get("params").unwrap().get("diagnostics"),
+        Some(&JsonValue::Array(Vec::new()))
+    );
+
+    // `total` in the last line is the global, not the shadowing local.
+    let location = |range: String| format!(r#"{{"uri":"file:///a.lox","range":{}}}"#, range);
+    assert_eq!(result(&messages, 2.0), location(range(3, 4, 9)));
+    assert_eq!(
+        result(&messages, 3.0),
+        format!("[{},{}]", location(range(0, 8, 9)), location(range(1, 11, 12)))
+    );
+    assert_eq!(
+        result(&messages, 4.0),
+        r#"{"contents":{"kind":"markdown","value":"```lox\nfun add(a, b)\n```"}}"#
+    );
+    assert_eq!(
+        result(&messages, 5.0),
+        format!(
+            r#"[{{"name":"add","detail":"fun add(a, b)","kind":12,"range":{0},"selectionRange":{0}}},{{"name":"total","detail":"var total","kind":13,"range":{1},"selectionRange":{1}}}]"#,
+            range(0, 4, 7),
+            range(3, 4, 9)
+        )
+    );
+    assert_eq!(result(&messages, 6.0), location(range(5, 8, 13)));
+
+    let unknown = messages
+        .iter()
+        .find(|message| message.get("id") == Some(&JsonValue::Number(7.0)))
+        .unwrap();
+    assert!(unknown.get("error").is_some());
+}
+
+#[test]
+fn diagnostics() {
+    let open = r#"{"textDocument":{"uri":"file:///b.lox","text":"var x = @1;\nprint (x;\n"}}"#;
+    let input = [notification("textDocument/didOpen", open), notification("exit", "null")].concat();
+
+    let (code, messages) = session(&input);
+    assert_eq!(code, 1);
+    assert_eq!(
+        messages[0].get("params").unwrap().get("diagnostics").unwrap().write(None).unwrap(),
+        format!(
+            r#"[{{"range":{},"severity":1,"source":"jlox","message":"Unexpected character."}},{{"range":{},"severity":1,"source":"jlox","message":"Expect ')' after expression"}}]"#,
+            range(0, 8, 9),
+            range(1, 8, 9)
+        )
+    );
+}
+
+#[test]
+fn rejects_oversized_messages_and_out_of_range_positions() {
+    let open = format!(
+        r#"{{"textDocument":{{"uri":"file:///a.lox","text":{}}}}}"#,
+        JsonValue::String(SOURCE.to_string()).write(None).unwrap()
+    );
+    let oversized = 16 * 1024 * 1024 + 1;
+    let input = [
+        frame(&" ".repeat(oversized)),
+        notification("textDocument/didOpen", &open),
+        request(1, "textDocument/definition", &position(u32::MAX, 0)),
+        notification("exit", "null"),
+    ]
+    .concat();
+
+    let (code, messages) = session(&input);
+    assert_eq!(code, 1);
+    assert_eq!(
+        messages[0].get("error").unwrap().get("message"),
+        Some(&JsonValue::String(
+            "Message of 16777217 bytes is over the limit of 16777216 bytes.".to_string()
+        ))
+    );
+    assert_eq!(result(&messages, 1.0), "null");
+}
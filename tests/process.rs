@@ -0,0 +1,33 @@
+//! Checks what the script harness cannot: the arguments a script is given
+//! and the exit code it leaves with.
+
+use std::process::Command;
+
+fn run(script_args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg(format!(
+            "{}/tests/process/exit.lox",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .args(script_args)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn exit_sets_the_exit_code_after_finally() {
+    let (code, stdout) = run(&["3", "extra"]);
+    assert_eq!(code, Some(3));
+    assert_eq!(stdout, "[3, extra]\nfinally\n");
+}
+
+#[test]
+fn exit_with_zero_succeeds() {
+    let (code, stdout) = run(&["0"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "[0]\nfinally\n");
+}
@@ -0,0 +1,170 @@
+//! Runs `jlox --profile` on `tests/debugger/squares.lox` and checks the
+//! parts of the report that do not depend on timing, then checks that
+//! functions and lines are kept per file and how the profiler accounts for
+//! recursive calls.
+
+use std::{cell::RefCell, env, fs, process::Command, rc::Rc, time::Duration};
+
+use jlox_rustport::{
+    interpreter::Interpreter,
+    lox::Lox,
+    profiler::{Profile, Profiler},
+};
+
+const PROGRAM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/debugger/squares.lox");
+
+#[test]
+fn reports_phases_functions_lines_and_folded_stacks() {
+    let folded_path = env::temp_dir().join(format!("jlox-profile-{}.folded", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .args(["--profile", "--folded"])
+        .arg(&folded_path)
+        .arg(PROGRAM)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "14\n");
+
+    let report = String::from_utf8(output.stderr).unwrap();
+    let rows: Vec<Vec<&str>> = report
+        .lines()
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    for phase in ["scan", "parse", "interpret"] {
+        assert!(rows
+            .iter()
+            .any(|row| row.len() == 3 && row[0] == phase && row[2] == "ms"));
+    }
+    let square = rows
+        .iter()
+        .find(|row| row.first() == Some(&"square"))
+        .unwrap();
+    assert_eq!(square[1], "3");
+    assert_eq!(square[4], PROGRAM);
+    let script = rows
+        .iter()
+        .find(|row| row.first() == Some(&"<script>"))
+        .unwrap();
+    assert_eq!(script[1], "1");
+
+    let lines_at = rows
+        .iter()
+        .position(|row| row == &["line", "hits", "file"])
+        .unwrap();
+    assert_eq!(
+        rows[lines_at + 1..lines_at + 5],
+        [
+            ["6", "5", PROGRAM],
+            ["2", "3", PROGRAM],
+            ["3", "3", PROGRAM],
+            ["7", "3", PROGRAM]
+        ]
+    );
+
+    let folded = fs::read_to_string(&folded_path).unwrap();
+    fs::remove_file(&folded_path).unwrap();
+    let stacks: Vec<&str> = folded
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0)
+        .collect();
+    assert_eq!(stacks, ["<script>", "<script>;square"]);
+}
+
+#[test]
+fn keeps_functions_and_lines_per_file() {
+    let main = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/profile/main.lox");
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg("--profile")
+        .arg(main)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "6\n");
+
+    let report = String::from_utf8(output.stderr).unwrap();
+    let rows: Vec<Vec<&str>> = report
+        .lines()
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    let file_of = |row: &Vec<&str>| match *row.last().unwrap() {
+        file if file == main => "main",
+        file if file.ends_with("modules/helper.lox") => "helper",
+        _ => "other",
+    };
+
+    let mut doubles: Vec<(&str, &str)> = rows
+        .iter()
+        .filter(|row| row.first() == Some(&"double"))
+        .map(|row| (file_of(row), row[1]))
+        .collect();
+    doubles.sort();
+    assert_eq!(doubles, [("helper", "1"), ("main", "1")]);
+
+    let lines_at = rows
+        .iter()
+        .position(|row| row == &["line", "hits", "file"])
+        .unwrap();
+    let mut lines: Vec<(&str, &str, &str)> = rows[lines_at + 1..]
+        .iter()
+        .map(|row| (file_of(row), row[0], row[1]))
+        .collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            ("helper", "1", "1"),
+            ("helper", "2", "1"),
+            ("main", "1", "1"),
+            ("main", "2", "1"),
+            ("main", "3", "1"),
+            ("main", "5", "1")
+        ]
+    );
+}
+
+#[test]
+fn recursive_calls_count_inclusive_time_once() {
+    let mut lox = Lox::default();
+    let statements = lox.parse(
+        "fun countdown(n) { sleep(1); if (n > 1) countdown(n - 1); }\ncountdown(4);".to_string(),
+    );
+    assert!(!lox.had_error);
+
+    let profile = Rc::new(RefCell::new(Profile::default()));
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .hooks
+        .push(Box::new(Profiler::new(profile.clone())));
+    interpreter.interpret(statements, &mut lox);
+
+    let profile = profile.borrow();
+    let countdown = profile.functions[&(String::new(), "countdown".to_string())];
+    assert_eq!(countdown.calls, 4);
+    // Only the outermost call adds to the inclusive time, so it is the time
+    // of that one call rather than the sum of four nested ones.
+    assert_eq!(countdown.inclusive, profile.top_level);
+    assert!(countdown.exclusive <= countdown.inclusive);
+    assert!(countdown.inclusive >= Duration::from_millis(4));
+
+    let stacks: Vec<&str> = profile.stacks.keys().map(String::as_str).collect();
+    assert_eq!(
+        stacks,
+        [
+            "<script>;countdown",
+            "<script>;countdown;countdown",
+            "<script>;countdown;countdown;countdown",
+            "<script>;countdown;countdown;countdown;countdown",
+            "<script>;countdown;countdown;countdown;countdown;sleep",
+            "<script>;countdown;countdown;countdown;sleep",
+            "<script>;countdown;countdown;sleep",
+            "<script>;countdown;sleep",
+        ]
+    );
+    let stack_time: Duration = profile
+        .stacks
+        .iter()
+        .filter(|(stack, _)| !stack.ends_with("sleep"))
+        .map(|(_, time)| *time)
+        .sum();
+    assert_eq!(stack_time, countdown.exclusive);
+}
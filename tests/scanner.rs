@@ -0,0 +1,41 @@
+//! Checks the start positions and errors the scanner records for tools
+//! such as `jlox tokens`.
+
+use jlox_rustport::scanner::Scanner;
+
+fn scan(source: &str) -> Scanner {
+    let mut scanner = Scanner {
+        source: Vec::new(),
+        tokens: Vec::new(),
+        comments: Vec::new(),
+        positions: Vec::new(),
+        errors: Vec::new(),
+        start: 0,
+        current: 0,
+        line: 1,
+    };
+    scanner.scan_tokens(source.to_string());
+    scanner
+}
+
+#[test]
+fn positions_and_errors() {
+    let scanner = scan("var s = \"a\nb\";\n  print @ s;\n\"open");
+
+    let lexemes: Vec<&str> = scanner.tokens.iter().map(|token| token.lexeme.as_str()).collect();
+    assert_eq!(lexemes, ["var", "s", "=", "\"a\nb\"", ";", "print", "s", ";", ""]);
+    assert_eq!(
+        scanner.positions,
+        [(1, 1), (1, 5), (1, 7), (1, 9), (2, 3), (3, 3), (3, 11), (3, 12), (4, 6)]
+    );
+
+    let errors: Vec<(&str, u32, u32, usize)> = scanner
+        .errors
+        .iter()
+        .map(|error| (error.message.as_str(), error.line, error.column, error.token_index))
+        .collect();
+    assert_eq!(
+        errors,
+        [("Unexpected character.", 3, 9, 6), ("Unterminated string", 4, 1, 8)]
+    );
+}
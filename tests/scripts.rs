@@ -0,0 +1,130 @@
+//! Runs every script under `tests/scripts` and checks it against the
+//! expectations written in it as comments:
+//!
+//! - `// expect: text` is the next line the script prints;
+//! - `// expect runtime error: message` means the script stops with this
+//!   runtime error and exit code 70;
+//! - `// expect parse error: message` means stderr has this message and the
+//!   script exits with 65 without running.
+//!
+//! Each script runs in an empty temporary directory, so it can write files
+//! by relative path. Scripts in a `modules` directory are only imported by
+//! others and are not run themselves.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn scripts(directory: &Path, found: &mut Vec<PathBuf>) {
+    let mut entries: Vec<PathBuf> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if path.file_name().unwrap() != "modules" {
+                scripts(&path, found);
+            }
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            found.push(path);
+        }
+    }
+}
+
+struct Expectations {
+    stdout: String,
+    runtime_error: Option<String>,
+    parse_errors: Vec<String>,
+}
+
+fn expectations(source: &str) -> Expectations {
+    let mut expected = Expectations {
+        stdout: String::new(),
+        runtime_error: None,
+        parse_errors: Vec::new(),
+    };
+    for line in source.lines() {
+        let Some((_, comment)) = line.split_once("// expect") else {
+            continue;
+        };
+        if let Some(text) = comment.strip_prefix(": ") {
+            expected.stdout.push_str(text);
+            expected.stdout.push('\n');
+        } else if let Some(message) = comment.strip_prefix(" runtime error: ") {
+            expected.runtime_error = Some(message.to_string());
+        } else if let Some(message) = comment.strip_prefix(" parse error: ") {
+            expected.parse_errors.push(message.to_string());
+        }
+    }
+    expected
+}
+
+// Checks one script, returning what went wrong.
+fn check(path: &Path, directory: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(path).unwrap();
+    let expected = expectations(&source);
+    fs::create_dir_all(directory).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg(path)
+        .current_dir(directory)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let expected_code = if !expected.parse_errors.is_empty() {
+        65
+    } else if expected.runtime_error.is_some() {
+        70
+    } else {
+        0
+    };
+    if output.status.code() != Some(expected_code) {
+        return Err(format!(
+            "exited with {:?} instead of {}\nstderr:\n{}",
+            output.status.code(),
+            expected_code,
+            stderr
+        ));
+    }
+    if stdout != expected.stdout {
+        return Err(format!(
+            "stdout was:\n{}\nexpected:\n{}",
+            stdout, expected.stdout
+        ));
+    }
+    if let Some(message) = &expected.runtime_error {
+        if stderr.lines().next() != Some(message.as_str()) {
+            return Err(format!("stderr was:\n{}\nexpected: {}", stderr, message));
+        }
+    }
+    for message in &expected.parse_errors {
+        if !stderr.contains(message.as_str()) {
+            return Err(format!("stderr was:\n{}\nexpected: {}", stderr, message));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn scripts_match_their_expectations() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scripts");
+    let mut paths = Vec::new();
+    scripts(&root, &mut paths);
+    assert!(!paths.is_empty());
+
+    let scratch = env::temp_dir().join(format!("jlox-scripts-{}", std::process::id()));
+    let mut failures = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        let directory = scratch.join(index.to_string());
+        if let Err(message) = check(path, &directory) {
+            let name = path.strip_prefix(&root).unwrap().display();
+            failures.push(format!("{}: {}", name, message));
+        }
+    }
+    let _ = fs::remove_dir_all(&scratch);
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
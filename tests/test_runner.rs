@@ -0,0 +1,68 @@
+//! Runs `jlox test` on the files in `tests/test_runner` and checks its
+//! report and exit code.
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> (Option<i32>, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_runner"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn setup_runs_once_and_each_test_starts_from_it() {
+    let (code, stdout, _) = run(&["test", "counter.lox"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        stdout,
+        "setup\n\
+         PASS counter.lox: first test sees the setup\n\
+         PASS counter.lox: globals are reset before each test\n\
+         PASS counter.lox: variables stay in their test\n\
+         FAIL counter.lox: failure is reported\n\
+         \x20   counter.lox:40: Assertion failed: expected 2 but got 1.\n\
+         \n\
+         3 passed, 1 failed\n"
+    );
+}
+
+#[test]
+fn failing_setup_is_reported_once() {
+    let (code, stdout, _) = run(&["test", "broken_setup.lox"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        stdout,
+        "ERROR broken_setup.lox: setup failed\n\
+         \x20   broken_setup.lox:1: Uncaught exception: no setup\n\
+         \n\
+         0 passed, 1 failed\n"
+    );
+}
+
+#[test]
+fn nested_test_is_a_parse_error() {
+    let (code, stdout, stderr) = run(&["test", "nested.lox"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "ERROR nested.lox: could not be parsed\n\n0 passed, 1 failed\n");
+    assert_eq!(
+        stderr,
+        "[line 2] Error  at 'test': Test blocks must be at the top level.\n"
+    );
+
+    let (code, _, _) = run(&["nested.lox"]);
+    assert_eq!(code, Some(65));
+}
+
+#[test]
+fn passing_file_exits_zero() {
+    let (code, stdout, _) = run(&["test", "passing.lox"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "PASS passing.lox: adds\n\n1 passed, 0 failed\n");
+}
@@ -0,0 +1,104 @@
+//! Runs `jlox --trace` on `tests/debugger/squares.lox` and checks the log.
+
+use std::{env, fs, process::Command};
+
+const PROGRAM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/debugger/squares.lox");
+
+#[test]
+fn traces_statements_and_calls_to_stderr() {
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .args(["--trace", PROGRAM])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "14\n");
+
+    let trace = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<&str> = trace.lines().collect();
+    assert_eq!(
+        lines[..10],
+        [
+            "[line 1] fun square",
+            "[line 5] var total",
+            "[line 6] for",
+            "[line 6] var i",
+            "[line 7] expression",
+            "[line 7] call square(1)",
+            "  [line 2] var result",
+            "  [line 3] return",
+            "[line 7] square returned 1",
+            "[line 6] expression",
+        ]
+    );
+    assert_eq!(
+        lines[lines.len() - 2..],
+        ["[line 9] debugger", "[line 10] print"]
+    );
+}
+
+#[test]
+fn traces_only_named_functions_to_a_file() {
+    let path = env::temp_dir().join(format!("jlox-trace-{}.txt", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .args(["--trace", "--only", "square", "--output"])
+        .arg(&path)
+        .arg(PROGRAM)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "14\n");
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+
+    let trace = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(trace.lines().count(), 12);
+    assert_eq!(
+        trace.lines().take(4).collect::<Vec<_>>(),
+        [
+            "[line 7] call square(1)",
+            "  [line 2] var result",
+            "  [line 3] return",
+            "[line 7] square returned 1",
+        ]
+    );
+    assert!(trace.ends_with("[line 7] square returned 9\n"));
+}
+
+#[test]
+fn traces_throws_and_failures_up_the_stack() {
+    let output = Command::new(env!("CARGO_BIN_EXE_jlox_rustport"))
+        .arg("--trace")
+        .arg(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/trace/failures.lox"
+        ))
+        .output()
+        .unwrap();
+    let message = "len() expects argument 1 to be a string, a list or a map.";
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("negative\n{}\n", message)
+    );
+
+    // try statements only group others, so they have no line of their own.
+    let trace = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<&str> = trace.lines().collect();
+    assert_eq!(
+        lines[2..7],
+        [
+            "[line 9] expression",
+            "[line 9] call check(-1)",
+            "  [line 2] if",
+            "  [line 2] throw",
+            "[line 9] check threw \"negative\"",
+        ]
+    );
+    assert_eq!(
+        lines[lines.len() - 5..],
+        [
+            "    [line 6] call len(1)".to_string(),
+            format!("    [line 6] len failed: {}", message),
+            format!("  [line 3] inner failed: {}", message),
+            format!("[line 14] check failed: {}", message),
+            "[line 16] print".to_string(),
+        ]
+    );
+}